@@ -17,7 +17,10 @@ use sea_orm::DatabaseConnection;
 use serde::Serialize;
 
 use enote_lib::{
-    model::{McpPermission, Note, NoteSearchPageParam, Notebook, OperateSource, PageParam, Tag},
+    model::{
+        BatchNoteResult, BatchNoteStatus, LinkOrigin, McpPermission, Note, NoteCursorSearchParam,
+        NoteLink, NoteSearchPageParam, Notebook, OperateSource, PageParam, Tag,
+    },
     service,
 };
 
@@ -42,7 +45,7 @@ async fn get_enabled_tools(
             .map(String::from)
             .collect())
     } else {
-        // 没有配置 → 默认全部启用
+        // 没有配置 → 默认启用除永久删除外的全部工具
         Ok([
             "search_notes",
             "get_note",
@@ -53,7 +56,15 @@ async fn get_enabled_tools(
             "create_notebook",
             "list_tags",
             "create_tag",
+            "merge_tags",
+            "add_tag_to_notes",
+            "remove_tag_from_notes",
+            "set_notes_starred",
+            "set_notes_pinned",
+            "restore_notes",
             "note_stats",
+            "get_outgoing_links",
+            "get_backlinks",
         ]
         .iter()
         .map(|s| s.to_string())
@@ -73,6 +84,77 @@ async fn check_tool_enabled(db: &DatabaseConnection, tool_name: &str) -> Result<
     Ok(())
 }
 
+/// 按 AI 写入权限拆分笔记 ID，返回 (可写, 不可写或不存在)
+async fn split_writable(db: &DatabaseConnection, note_ids: Vec<i64>) -> (Vec<i64>, Vec<i64>) {
+    let mut writable = Vec::new();
+    let mut skipped = Vec::new();
+    for id in note_ids {
+        if service::mcp_access::check_write(db, id).await.is_ok() {
+            writable.push(id);
+        } else {
+            skipped.push(id);
+        }
+    }
+    (writable, skipped)
+}
+
+/// 按 AI 写入权限拆分批量操作的笔记，返回 (可写的笔记 ID, 其余笔记的结果)
+///
+/// 其余笔记为不存在（`NotFound`）或无写入权限（`Denied`）
+async fn split_batch(
+    db: &DatabaseConnection,
+    note_ids: &[i64],
+) -> (Vec<i64>, Vec<BatchNoteResult>) {
+    let mut writable = Vec::new();
+    let mut rejected = Vec::new();
+    for id in note_ids {
+        let status = match service::mcp_access::resolve_note_access(db, *id).await {
+            Ok(perm) if perm.can_write() => {
+                writable.push(*id);
+                continue;
+            }
+            Ok(_) => BatchNoteStatus::Denied,
+            Err(_) => BatchNoteStatus::NotFound,
+        };
+        rejected.push(BatchNoteResult {
+            note_id: *id,
+            status,
+        });
+    }
+    (writable, rejected)
+}
+
+/// 合并批量操作结果并按请求顺序以 JSON 返回
+fn batch_response(
+    note_ids: &[i64],
+    mut results: Vec<BatchNoteResult>,
+    rejected: Vec<BatchNoteResult>,
+) -> Result<CallToolResult, McpError> {
+    for r in rejected {
+        if !results.iter().any(|e| e.note_id == r.note_id) {
+            results.push(r);
+        }
+    }
+    results.sort_by_key(|r| note_ids.iter().position(|id| *id == r.note_id));
+    let content = ContentBlock::json(results)
+        .map_err(|e| McpError::internal_error(format!("JSON 序列化失败: {}", e), None))?;
+    Ok(CallToolResult::success(vec![content]))
+}
+
+/// 过滤掉 AI 无权读取的关联笔记
+async fn readable_links(db: &DatabaseConnection, links: Vec<NoteLink>) -> Vec<LinkInfo> {
+    let mut result = Vec::with_capacity(links.len());
+    for link in links {
+        if service::mcp_access::check_read(db, link.note_id)
+            .await
+            .is_ok()
+        {
+            result.push(LinkInfo::from(link));
+        }
+    }
+    result
+}
+
 /// ENote MCP Server
 #[derive(Clone)]
 pub struct ENoteMcpServer {
@@ -87,16 +169,40 @@ pub struct ENoteMcpServer {
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 #[schemars(description = "搜索笔记参数")]
 pub struct SearchNotesParams {
-    #[schemars(description = "搜索关键词（匹配标题和内容）")]
+    #[schemars(
+        description = "搜索关键词（匹配标题、内容和标签），支持结构化语法：tag:名称、notebook:名称、is:starred、is:pinned、before:YYYY-MM-DD、after:YYYY-MM-DD、\"精确短语\"，前缀 - 表示排除（如 -draft、-tag:archived）"
+    )]
     keyword: Option<String>,
     #[schemars(description = "按笔记本 ID 过滤（0 或不传表示不过滤）")]
     notebook_id: Option<i64>,
     #[schemars(description = "按标签 ID 过滤（0 或不传表示不过滤）")]
     tag_id: Option<i64>,
-    #[schemars(description = "页码，从 1 开始（默认 1）")]
+    #[schemars(description = "按多个笔记本 ID 过滤，命中任一即可")]
+    notebook_ids: Option<Vec<i64>>,
+    #[schemars(description = "笔记本过滤是否包含子孙笔记本（默认 false）")]
+    include_descendants: Option<bool>,
+    #[schemars(description = "必须同时包含的标签 ID 列表（all）")]
+    all_tag_ids: Option<Vec<i64>>,
+    #[schemars(description = "至少包含其一的标签 ID 列表（any）")]
+    any_tag_ids: Option<Vec<i64>>,
+    #[schemars(description = "需要排除的标签 ID 列表（none）")]
+    exclude_tag_ids: Option<Vec<i64>>,
+    #[schemars(description = "标签过滤（tag_id 与 all/any/none）是否包含子孙标签（默认 false）")]
+    include_tag_descendants: Option<bool>,
+    #[schemars(
+        description = "排序字段：relevance（按相关度，需提供关键词）、update_time、create_time、title（默认 update_time）"
+    )]
+    sort_field: Option<String>,
+    #[schemars(description = "页码，从 1 开始（默认 1）。按页码分页并返回总数")]
     page: Option<i64>,
     #[schemars(description = "每页数量（默认 20，最大 50）")]
     page_size: Option<i64>,
+    #[schemars(
+        description = "改用游标分页（深翻页更快）：第一页传空字符串，之后传上一次结果中的 nextCursor；不传时按页码分页"
+    )]
+    cursor: Option<String>,
+    #[schemars(description = "游标分页时是否同时返回总数（默认 false）")]
+    with_total: Option<bool>,
 }
 
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
@@ -106,6 +212,13 @@ pub struct GetNoteParams {
     note_id: i64,
 }
 
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+#[schemars(description = "笔记链接查询参数")]
+pub struct NoteLinksParams {
+    #[schemars(description = "笔记 ID")]
+    note_id: i64,
+}
+
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 #[schemars(description = "创建笔记参数")]
 pub struct CreateNoteParams {
@@ -132,6 +245,10 @@ pub struct UpdateNoteParams {
     notebook_id: Option<i64>,
     #[schemars(description = "新标签 ID 列表（不传则不修改）")]
     tag_ids: Option<Vec<i64>>,
+    #[schemars(
+        description = "读取笔记时得到的版本号（get_note 返回的 version）。笔记在此之后被其他客户端修改时拒绝更新；不传则以本次更新前读取的版本为准"
+    )]
+    version: Option<i64>,
 }
 
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
@@ -148,13 +265,62 @@ pub struct CreateNotebookParams {
     name: String,
     #[schemars(description = "笔记本描述")]
     description: Option<String>,
+    #[schemars(description = "父笔记本 ID（0 或不传表示顶级笔记本）")]
+    parent_id: Option<i64>,
 }
 
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 #[schemars(description = "创建标签参数")]
 pub struct CreateTagParams {
-    #[schemars(description = "标签名称")]
+    #[schemars(
+        description = "标签名称，可使用 a/b/c 形式的路径创建嵌套标签（缺失的上级标签自动创建，已存在的复用）"
+    )]
     name: String,
+    #[schemars(description = "父标签 ID（0 或不传表示顶级标签），路径相对于该标签")]
+    parent_id: Option<i64>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+#[schemars(description = "合并标签参数")]
+pub struct MergeTagsParams {
+    #[schemars(description = "要合并掉的标签 ID 列表（合并后删除）")]
+    source_ids: Vec<i64>,
+    #[schemars(description = "保留的目标标签 ID")]
+    target_id: i64,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+#[schemars(description = "批量添加或移除标签参数")]
+pub struct NotesTagParams {
+    #[schemars(description = "笔记 ID 列表")]
+    note_ids: Vec<i64>,
+    #[schemars(description = "标签 ID")]
+    tag_id: i64,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+#[schemars(description = "批量笔记操作参数")]
+pub struct NoteIdsParams {
+    #[schemars(description = "笔记 ID 列表")]
+    note_ids: Vec<i64>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+#[schemars(description = "批量设置收藏参数")]
+pub struct SetNotesStarredParams {
+    #[schemars(description = "笔记 ID 列表")]
+    note_ids: Vec<i64>,
+    #[schemars(description = "true 为收藏，false 为取消收藏")]
+    starred: bool,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+#[schemars(description = "批量设置置顶参数")]
+pub struct SetNotesPinnedParams {
+    #[schemars(description = "笔记 ID 列表")]
+    note_ids: Vec<i64>,
+    #[schemars(description = "true 为置顶，false 为取消置顶")]
+    pinned: bool,
 }
 
 // ============================================================================
@@ -190,15 +356,37 @@ struct NoteSummary {
     snippet: String,
     notebook_name: String,
     tags: Vec<String>,
+    /// 命中片段（字段: 文本，命中处以【】标记）
+    matches: Vec<String>,
+    /// 关键词是否命中附件文本
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    attachment_match: bool,
+    /// 相关度得分（按 relevance 排序时返回）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    score: Option<f64>,
     update_time: Option<String>,
 }
 
 impl From<Note> for NoteSummary {
     fn from(note: Note) -> Self {
+        // 有内容命中片段时优先展示命中上下文
+        let snippet = note
+            .snippets
+            .iter()
+            .find(|s| s.field == "content")
+            .map(|s| s.text.clone())
+            .unwrap_or_else(|| strip_html(&note.content, 200));
         Self {
             id: note.id,
             title: note.title,
-            snippet: strip_html(&note.content, 200),
+            snippet,
+            matches: note
+                .snippets
+                .iter()
+                .map(|s| format!("{}: {}", s.field, s.marked("【", "】")))
+                .collect(),
+            attachment_match: note.attachment_match,
+            score: note.score,
             notebook_name: note.notebook_name,
             tags: note.tags.iter().map(|t| t.name.clone()).collect(),
             update_time: note
@@ -212,9 +400,14 @@ impl From<Note> for NoteSummary {
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 struct SearchResult {
-    total: i64,
-    total_pages: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    total: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    total_pages: Option<i64>,
     notes: Vec<NoteSummary>,
+    /// 下一页游标（游标分页且还有更多结果时返回）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    next_cursor: Option<String>,
 }
 
 /// 笔记详情
@@ -228,6 +421,8 @@ struct NoteDetail {
     notebook_name: String,
     content_type: i32,
     is_pinned: i32,
+    /// 版本号（update_note 时传回，用于冲突检测）
+    version: i64,
     tags: Vec<TagInfo>,
     create_time: Option<String>,
     update_time: Option<String>,
@@ -239,6 +434,34 @@ struct TagInfo {
     name: String,
 }
 
+/// 笔记链接（出链时为被引用的笔记，反向链接时为引用方笔记）
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LinkInfo {
+    note_id: i64,
+    title: String,
+    /// 关系类型，未指定时为空
+    #[serde(skip_serializing_if = "String::is_empty")]
+    relation: String,
+    /// 来源：manual / content
+    origin: &'static str,
+}
+
+impl From<NoteLink> for LinkInfo {
+    fn from(link: NoteLink) -> Self {
+        Self {
+            note_id: link.note_id,
+            title: link.note_title,
+            relation: link.relation,
+            origin: if link.origin == LinkOrigin::Content.as_i32() {
+                "content"
+            } else {
+                "manual"
+            },
+        }
+    }
+}
+
 impl From<Note> for NoteDetail {
     fn from(note: Note) -> Self {
         Self {
@@ -249,6 +472,7 @@ impl From<Note> for NoteDetail {
             notebook_name: note.notebook_name,
             content_type: note.content_type,
             is_pinned: note.is_pinned,
+            version: note.version,
             tags: note
                 .tags
                 .iter()
@@ -279,7 +503,7 @@ impl ENoteMcpServer {
     // ---- 笔记操作 ----
 
     #[tool(
-        description = "搜索笔记。支持关键词搜索、按笔记本或标签过滤，返回分页结果（标题+摘要）。注意：设置了 AI 访问禁止的笔记不会出现在结果中"
+        description = "搜索笔记。支持关键词搜索（含 tag:/notebook:/is:/before:/after: 结构化语法）、按笔记本或标签过滤（多个笔记本任一命中，标签支持 all/any/none 组合），返回分页结果（标题+摘要），默认按页码分页并返回总数，传入 cursor 时改用游标分页，通过 nextCursor 获取下一页。注意：设置了 AI 访问禁止的笔记不会出现在结果中"
     )]
     async fn search_notes(
         &self,
@@ -294,23 +518,49 @@ impl ENoteMcpServer {
             notebook_id: params.notebook_id.unwrap_or(0),
             tag_id: params.tag_id.unwrap_or(0),
             keyword: params.keyword.unwrap_or_default(),
-            sort_field: String::new(),
+            sort_field: params.sort_field.unwrap_or_default(),
             sort_order: String::new(),
             is_starred: false,
+            notebook_ids: params.notebook_ids.unwrap_or_default(),
+            include_descendants: params.include_descendants.unwrap_or(false),
+            all_tag_ids: params.all_tag_ids.unwrap_or_default(),
+            any_tag_ids: params.any_tag_ids.unwrap_or_default(),
+            exclude_tag_ids: params.exclude_tag_ids.unwrap_or_default(),
+            include_tag_descendants: params.include_tag_descendants.unwrap_or(false),
         };
         search_param.normalize();
 
-        let result = service::note::search_page(&self.db, &search_param)
-            .await
-            .map_err(|e| McpError::internal_error(format!("搜索失败: {}", e), None))?;
+        // 默认按页码分页；传入游标（且未传页码）时使用游标分页
+        let cursor = params.cursor.filter(|_| params.page.is_none());
+        let (notes, total, total_pages, next_cursor) = if let Some(cursor) = cursor {
+            let mut cursor_param = NoteCursorSearchParam {
+                search_param,
+                cursor,
+                with_total: params.with_total.unwrap_or(false),
+            };
+            cursor_param.normalize();
+            let result = service::note::search_cursor(&self.db, &cursor_param)
+                .await
+                .map_err(|e| McpError::internal_error(format!("搜索失败: {}", e), None))?;
+            (result.data, result.total, None, result.next_cursor)
+        } else {
+            let result = service::note::search_page(&self.db, &search_param)
+                .await
+                .map_err(|e| McpError::internal_error(format!("搜索失败: {}", e), None))?;
+            (
+                result.data,
+                Some(result.total),
+                Some(result.total_pages),
+                None,
+            )
+        };
 
         // 后置过滤：根据 MCP 访问控制过滤掉 Deny 的笔记
-        let access_map = service::mcp_access::resolve_notes_access(&self.db, &result.data)
+        let access_map = service::mcp_access::resolve_notes_access(&self.db, &notes)
             .await
             .map_err(|e| McpError::internal_error(format!("权限解析失败: {}", e), None))?;
 
-        let filtered_notes: Vec<NoteSummary> = result
-            .data
+        let filtered_notes: Vec<NoteSummary> = notes
             .into_iter()
             .filter(|note| {
                 access_map
@@ -322,9 +572,10 @@ impl ENoteMcpServer {
             .collect();
 
         let search_result = SearchResult {
-            total: result.total,
-            total_pages: result.total_pages,
+            total,
+            total_pages,
             notes: filtered_notes,
+            next_cursor,
         };
 
         let content = ContentBlock::json(search_result)
@@ -409,7 +660,9 @@ impl ENoteMcpServer {
         }
     }
 
-    #[tool(description = "更新已有笔记的标题、内容、笔记本或标签。只需传入要修改的字段")]
+    #[tool(
+        description = "更新已有笔记的标题、内容、笔记本或标签。只需传入要修改的字段。传入 version 可避免覆盖其他客户端的修改"
+    )]
     async fn update_note(
         &self,
         rmcp::handler::server::wrapper::Parameters(params): rmcp::handler::server::wrapper::Parameters<UpdateNoteParams>,
@@ -452,13 +705,23 @@ impl ENoteMcpServer {
             notebook_id: params.notebook_id.unwrap_or(existing.notebook_id),
             content_type: existing.content_type,
             is_pinned: existing.is_pinned,
+            version: params.version.unwrap_or(existing.version),
             tags,
             ..Default::default()
         };
 
         let result = service::note::update(&self.db, &note, OperateSource::Mcp)
             .await
-            .map_err(|e| McpError::internal_error(format!("更新失败: {}", e), None))?;
+            .map_err(|e| match service::note::conflict_version(&e) {
+                Some(version) => McpError::invalid_request(
+                    format!(
+                        "笔记已被其他客户端修改，当前版本为 {}，请重新获取笔记、合并修改后重试",
+                        version
+                    ),
+                    None,
+                ),
+                None => McpError::internal_error(format!("更新失败: {}", e), None),
+            })?;
 
         match result {
             Some(note) => {
@@ -495,7 +758,7 @@ impl ENoteMcpServer {
 
     // ---- 笔记本操作 ----
 
-    #[tool(description = "列出所有笔记本（包含 AI 访问权限设置）")]
+    #[tool(description = "列出所有笔记本（包含父笔记本 ID 与 AI 访问权限设置）")]
     async fn list_notebooks(&self) -> Result<CallToolResult, McpError> {
         check_tool_enabled(&self.db, "list_notebooks").await?;
         let notebooks = service::notebook::find_all(&self.db)
@@ -506,6 +769,7 @@ impl ENoteMcpServer {
         #[serde(rename_all = "camelCase")]
         struct NotebookInfo {
             id: i64,
+            parent_id: i64,
             name: String,
             description: String,
             mcp_access: i32,
@@ -515,6 +779,7 @@ impl ENoteMcpServer {
             .into_iter()
             .map(|n| NotebookInfo {
                 id: n.id,
+                parent_id: n.parent_id,
                 name: n.name,
                 description: n.description,
                 mcp_access: n.mcp_access,
@@ -526,15 +791,20 @@ impl ENoteMcpServer {
         Ok(CallToolResult::success(vec![content]))
     }
 
-    #[tool(description = "创建新笔记本")]
+    #[tool(description = "创建新笔记本，可指定父笔记本以创建子笔记本")]
     async fn create_notebook(
         &self,
         rmcp::handler::server::wrapper::Parameters(params): rmcp::handler::server::wrapper::Parameters<CreateNotebookParams>,
     ) -> Result<CallToolResult, McpError> {
         check_tool_enabled(&self.db, "create_notebook").await?;
+        let parent_id = params.parent_id.unwrap_or(0);
+        service::mcp_access::check_notebook_write(&self.db, parent_id)
+            .await
+            .map_err(|e| McpError::invalid_request(format!("{}", e), None))?;
         let notebook = Notebook {
             name: params.name,
             description: params.description.unwrap_or_default(),
+            parent_id,
             ..Default::default()
         };
 
@@ -555,7 +825,7 @@ impl ENoteMcpServer {
 
     // ---- 标签操作 ----
 
-    #[tool(description = "列出所有标签（包含 AI 访问权限设置）")]
+    #[tool(description = "列出所有标签（包含父标签 ID、完整路径与 AI 访问权限设置）")]
     async fn list_tags(&self) -> Result<CallToolResult, McpError> {
         check_tool_enabled(&self.db, "list_tags").await?;
         let tags = service::tag::find_all(&self.db)
@@ -566,14 +836,19 @@ impl ENoteMcpServer {
         #[serde(rename_all = "camelCase")]
         struct TagInfo {
             id: i64,
+            parent_id: i64,
             name: String,
+            path: String,
             mcp_access: i32,
         }
 
+        let mut paths = service::tag::tag_paths(&tags);
         let list: Vec<TagInfo> = tags
             .into_iter()
             .map(|t| TagInfo {
                 id: t.id,
+                parent_id: t.parent_id,
+                path: paths.remove(&t.id).unwrap_or_default(),
                 name: t.name,
                 mcp_access: t.mcp_access,
             })
@@ -584,7 +859,7 @@ impl ENoteMcpServer {
         Ok(CallToolResult::success(vec![content]))
     }
 
-    #[tool(description = "创建新标签")]
+    #[tool(description = "创建新标签，名称可为 a/b/c 形式的路径以创建嵌套标签")]
     async fn create_tag(
         &self,
         rmcp::handler::server::wrapper::Parameters(params): rmcp::handler::server::wrapper::Parameters<CreateTagParams>,
@@ -592,6 +867,7 @@ impl ENoteMcpServer {
         check_tool_enabled(&self.db, "create_tag").await?;
         let tag = Tag {
             name: params.name,
+            parent_id: params.parent_id.unwrap_or(0),
             ..Default::default()
         };
 
@@ -610,6 +886,234 @@ impl ENoteMcpServer {
         }
     }
 
+    #[tool(
+        description = "合并标签：把源标签在所有笔记上改为目标标签（不产生重复，保留排序）后删除源标签，每条受影响的笔记记录一条历史。源标签与受影响的笔记必须全部允许 AI 写入"
+    )]
+    async fn merge_tags(
+        &self,
+        rmcp::handler::server::wrapper::Parameters(params): rmcp::handler::server::wrapper::Parameters<MergeTagsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        check_tool_enabled(&self.db, "merge_tags").await?;
+
+        // 源标签合并后被删除，需要源标签自身（或继承的）权限允许写入
+        for tag_id in params
+            .source_ids
+            .iter()
+            .filter(|id| **id != params.target_id)
+        {
+            service::mcp_access::check_tag_write(&self.db, *tag_id)
+                .await
+                .map_err(|e| {
+                    McpError::invalid_request(
+                        format!("源标签 ID {} 不允许 AI 写入，拒绝合并: {}", tag_id, e),
+                        None,
+                    )
+                })?;
+        }
+
+        let note_ids = service::tag::find_tagged_note_ids(&self.db, &params.source_ids)
+            .await
+            .map_err(|e| McpError::internal_error(format!("查询失败: {}", e), None))?;
+        let (writable, denied) = split_writable(&self.db, note_ids).await;
+        if !denied.is_empty() {
+            return Err(McpError::invalid_request(
+                format!(
+                    "有 {} 条使用源标签的笔记不允许 AI 写入，拒绝合并: {:?}",
+                    denied.len(),
+                    denied
+                ),
+                None,
+            ));
+        }
+
+        // 合并在事务中再次确认受影响的笔记都已检查过权限
+        let affected = service::tag::merge_tags(
+            &self.db,
+            &params.source_ids,
+            params.target_id,
+            Some(&writable),
+            OperateSource::Mcp,
+            None,
+        )
+        .await
+        .map_err(|e| match service::tag::merge_unexpected_notes(&e) {
+            Some(note_ids) => McpError::invalid_request(
+                format!(
+                    "合并期间有新的笔记使用了源标签，拒绝合并，请重试: {:?}",
+                    note_ids
+                ),
+                None,
+            ),
+            None => McpError::internal_error(format!("合并失败: {}", e), None),
+        })?;
+
+        Ok(CallToolResult::success(vec![ContentBlock::text(format!(
+            "标签已合并到 ID {}，{} 条笔记的标签发生变化",
+            params.target_id, affected
+        ))]))
+    }
+
+    #[tool(description = "批量为笔记添加标签（跳过已有该标签、在回收站中或不允许 AI 写入的笔记）")]
+    async fn add_tag_to_notes(
+        &self,
+        rmcp::handler::server::wrapper::Parameters(params): rmcp::handler::server::wrapper::Parameters<NotesTagParams>,
+    ) -> Result<CallToolResult, McpError> {
+        check_tool_enabled(&self.db, "add_tag_to_notes").await?;
+
+        let (writable, skipped) = split_writable(&self.db, params.note_ids).await;
+        let changed = service::tag::add_tag_to_notes(
+            &self.db,
+            &writable,
+            params.tag_id,
+            OperateSource::Mcp,
+            None,
+        )
+        .await
+        .map_err(|e| McpError::internal_error(format!("添加失败: {}", e), None))?;
+
+        let mut msg = format!("已为 {} 条笔记添加标签 ID {}", changed, params.tag_id);
+        if !skipped.is_empty() {
+            msg.push_str(&format!("，跳过无写入权限的笔记: {:?}", skipped));
+        }
+        Ok(CallToolResult::success(vec![ContentBlock::text(msg)]))
+    }
+
+    #[tool(description = "批量从笔记移除标签（跳过没有该标签、在回收站中或不允许 AI 写入的笔记）")]
+    async fn remove_tag_from_notes(
+        &self,
+        rmcp::handler::server::wrapper::Parameters(params): rmcp::handler::server::wrapper::Parameters<NotesTagParams>,
+    ) -> Result<CallToolResult, McpError> {
+        check_tool_enabled(&self.db, "remove_tag_from_notes").await?;
+
+        let (writable, skipped) = split_writable(&self.db, params.note_ids).await;
+        let changed = service::tag::remove_tag_from_notes(
+            &self.db,
+            &writable,
+            params.tag_id,
+            OperateSource::Mcp,
+            None,
+        )
+        .await
+        .map_err(|e| McpError::internal_error(format!("移除失败: {}", e), None))?;
+
+        let mut msg = format!("已从 {} 条笔记移除标签 ID {}", changed, params.tag_id);
+        if !skipped.is_empty() {
+            msg.push_str(&format!("，跳过无写入权限的笔记: {:?}", skipped));
+        }
+        Ok(CallToolResult::success(vec![ContentBlock::text(msg)]))
+    }
+
+    // ---- 批量操作 ----
+
+    #[tool(
+        description = "批量收藏或取消收藏笔记。返回每条笔记的结果：changed、unchanged、notFound、skipped（在回收站中）、denied（不允许 AI 写入）"
+    )]
+    async fn set_notes_starred(
+        &self,
+        rmcp::handler::server::wrapper::Parameters(params): rmcp::handler::server::wrapper::Parameters<SetNotesStarredParams>,
+    ) -> Result<CallToolResult, McpError> {
+        check_tool_enabled(&self.db, "set_notes_starred").await?;
+
+        let (writable, rejected) = split_batch(&self.db, &params.note_ids).await;
+        let results = service::note::batch_set_star(&self.db, &writable, params.starred)
+            .await
+            .map_err(|e| McpError::internal_error(format!("设置收藏失败: {}", e), None))?;
+        batch_response(&params.note_ids, results, rejected)
+    }
+
+    #[tool(
+        description = "批量置顶或取消置顶笔记。返回每条笔记的结果：changed、unchanged、notFound、skipped（在回收站中）、denied（不允许 AI 写入）"
+    )]
+    async fn set_notes_pinned(
+        &self,
+        rmcp::handler::server::wrapper::Parameters(params): rmcp::handler::server::wrapper::Parameters<SetNotesPinnedParams>,
+    ) -> Result<CallToolResult, McpError> {
+        check_tool_enabled(&self.db, "set_notes_pinned").await?;
+
+        let (writable, rejected) = split_batch(&self.db, &params.note_ids).await;
+        let results = service::note::batch_set_pin(&self.db, &writable, params.pinned)
+            .await
+            .map_err(|e| McpError::internal_error(format!("设置置顶失败: {}", e), None))?;
+        batch_response(&params.note_ids, results, rejected)
+    }
+
+    #[tool(
+        description = "批量从回收站恢复笔记。返回每条笔记的结果：changed、unchanged（未在回收站中）、notFound、denied（不允许 AI 写入）"
+    )]
+    async fn restore_notes(
+        &self,
+        rmcp::handler::server::wrapper::Parameters(params): rmcp::handler::server::wrapper::Parameters<NoteIdsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        check_tool_enabled(&self.db, "restore_notes").await?;
+
+        let (writable, rejected) = split_batch(&self.db, &params.note_ids).await;
+        let results = service::note::batch_restore(&self.db, &writable)
+            .await
+            .map_err(|e| McpError::internal_error(format!("恢复失败: {}", e), None))?;
+        batch_response(&params.note_ids, results, rejected)
+    }
+
+    #[tool(
+        description = "批量永久删除回收站中的笔记（不可恢复）。返回每条笔记的结果：changed、notFound、skipped（未在回收站中）、denied（不允许 AI 写入）"
+    )]
+    async fn permanent_delete_notes(
+        &self,
+        rmcp::handler::server::wrapper::Parameters(params): rmcp::handler::server::wrapper::Parameters<NoteIdsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        check_tool_enabled(&self.db, "permanent_delete_notes").await?;
+
+        let (writable, rejected) = split_batch(&self.db, &params.note_ids).await;
+        let results =
+            service::note::batch_permanent_delete(&self.db, &writable, OperateSource::Mcp, None)
+                .await
+                .map_err(|e| McpError::internal_error(format!("永久删除失败: {}", e), None))?;
+        batch_response(&params.note_ids, results, rejected)
+    }
+
+    // ---- 链接 ----
+
+    #[tool(
+        description = "获取笔记的出链：该笔记引用的其他笔记，包含关系类型（如 references、depends-on、supersedes）和来源（manual 手动创建 / content 来自内容中的 [[标题]] 引用）"
+    )]
+    async fn get_outgoing_links(
+        &self,
+        rmcp::handler::server::wrapper::Parameters(params): rmcp::handler::server::wrapper::Parameters<NoteLinksParams>,
+    ) -> Result<CallToolResult, McpError> {
+        check_tool_enabled(&self.db, "get_outgoing_links").await?;
+
+        service::mcp_access::check_read(&self.db, params.note_id)
+            .await
+            .map_err(|e| McpError::invalid_request(format!("{}", e), None))?;
+
+        let links = service::note_link::find_outgoing(&self.db, params.note_id)
+            .await
+            .map_err(|e| McpError::internal_error(format!("查询失败: {}", e), None))?;
+        let content = ContentBlock::json(readable_links(&self.db, links).await)
+            .map_err(|e| McpError::internal_error(format!("JSON 序列化失败: {}", e), None))?;
+        Ok(CallToolResult::success(vec![content]))
+    }
+
+    #[tool(
+        description = "获取笔记的反向链接：引用了该笔记的其他笔记，包含关系类型和来源（manual / content）"
+    )]
+    async fn get_backlinks(
+        &self,
+        rmcp::handler::server::wrapper::Parameters(params): rmcp::handler::server::wrapper::Parameters<NoteLinksParams>,
+    ) -> Result<CallToolResult, McpError> {
+        check_tool_enabled(&self.db, "get_backlinks").await?;
+
+        service::mcp_access::check_read(&self.db, params.note_id)
+            .await
+            .map_err(|e| McpError::invalid_request(format!("{}", e), None))?;
+
+        let links = service::note_link::find_backlinks(&self.db, params.note_id)
+            .await
+            .map_err(|e| McpError::internal_error(format!("查询失败: {}", e), None))?;
+        let content = ContentBlock::json(readable_links(&self.db, links).await)
+            .map_err(|e| McpError::internal_error(format!("JSON 序列化失败: {}", e), None))?;
+        Ok(CallToolResult::success(vec![content]))
+    }
+
     // ---- 统计 ----
 
     #[tool(description = "获取笔记统计信息（总数和各笔记本的笔记数量）")]
@@ -651,11 +1155,21 @@ impl ServerHandler for ENoteMcpServer {
                  - create_notebook: 创建笔记本\n\
                  - list_tags: 列出标签\n\
                  - create_tag: 创建标签\n\
+                 - merge_tags: 合并标签\n\
+                 - add_tag_to_notes: 批量为笔记添加标签\n\
+                 - remove_tag_from_notes: 批量从笔记移除标签\n\
+                 - set_notes_starred: 批量收藏或取消收藏笔记\n\
+                 - set_notes_pinned: 批量置顶或取消置顶笔记\n\
+                 - restore_notes: 批量从回收站恢复笔记\n\
+                 - permanent_delete_notes: 批量永久删除回收站中的笔记（默认关闭）\n\
                  - note_stats: 笔记统计\n\
+                 - get_outgoing_links: 获取笔记的出链\n\
+                 - get_backlinks: 获取笔记的反向链接\n\
                  \n\
                  访问控制说明：\n\
                  笔记本、标签和笔记都有 mcp_access 权限设置：\n\
                  0=继承上层, 1=读写, 2=只读, 3=禁止。\n\
+                 子笔记本、子标签设置为继承时沿用父笔记本、父标签的设置。\n\
                  加密笔记始终禁止 AI 访问。"
                     .to_string(),
             )
@@ -82,6 +82,26 @@ pub async fn auto_backup(app_state: tauri::State<'_, Arc<AppState>>) -> Result<S
     let filename = service::backup::auto_backup(&db, &app_state.app_data_dir)
         .await
         .map_err(AppError::from)?;
+    // 备份完成后按设置精简历史记录，失败不影响备份结果
+    match service::history_retention::compact_after_backup(&db).await {
+        Ok(Some(result)) => {
+            let _ = service::app_log::log_action(
+                &db,
+                "note_history",
+                "compact",
+                None,
+                None,
+                &format!(
+                    "Compacted note history after backup: deleted={}, freed={} bytes",
+                    result.deleted, result.freed_bytes
+                ),
+                None,
+            )
+            .await;
+        }
+        Ok(None) => {}
+        Err(e) => warn!("Note history compaction after backup failed: {}", e),
+    }
     Ok(filename)
 }
 
@@ -109,21 +109,55 @@ pub async fn find_note_links(
         .map_err(AppError::from)
 }
 
-/// 创建笔记链接
+/// 获取笔记的出链（该笔记引用的笔记）
+#[tauri::command]
+pub async fn find_note_outgoing_links(
+    app_state: tauri::State<'_, Arc<AppState>>,
+    note_id: i64,
+) -> Result<Vec<NoteLink>, AppError> {
+    if is_server_backend(&app_state).await {
+        let client = require_server(&app_state).await?;
+        return client.find_note_outgoing_links(note_id).await;
+    }
+    let db = require_db(&app_state).await?;
+    service::note_link::find_outgoing(&db, note_id)
+        .await
+        .map_err(AppError::from)
+}
+
+/// 获取笔记的反向链接（引用该笔记的笔记）
+#[tauri::command]
+pub async fn find_note_backlinks(
+    app_state: tauri::State<'_, Arc<AppState>>,
+    note_id: i64,
+) -> Result<Vec<NoteLink>, AppError> {
+    if is_server_backend(&app_state).await {
+        let client = require_server(&app_state).await?;
+        return client.find_note_backlinks(note_id).await;
+    }
+    let db = require_db(&app_state).await?;
+    service::note_link::find_backlinks(&db, note_id)
+        .await
+        .map_err(AppError::from)
+}
+
+/// 创建笔记链接（source 指向 target，可指定关系类型）
 #[tauri::command]
 pub async fn create_note_link(
     app_state: tauri::State<'_, Arc<AppState>>,
     source_note_id: i64,
     target_note_id: i64,
+    relation: Option<String>,
 ) -> Result<(), AppError> {
+    let relation = relation.unwrap_or_default();
     if is_server_backend(&app_state).await {
         let client = require_server(&app_state).await?;
         return client
-            .create_note_link(source_note_id, target_note_id)
+            .create_note_link(source_note_id, target_note_id, &relation)
             .await;
     }
     let db = require_db(&app_state).await?;
-    service::note_link::create_link(&db, source_note_id, target_note_id)
+    service::note_link::create_link(&db, source_note_id, target_note_id, &relation)
         .await
         .map_err(AppError::from)
 }
@@ -161,6 +195,138 @@ pub async fn search_linkable_notes(
         .map_err(AppError::from)
 }
 
+/// 检查笔记链接完整性，`repair` 为 true 时删除悬空链接并重新同步内容链接
+#[tauri::command]
+pub async fn check_note_link_integrity(
+    app_state: tauri::State<'_, Arc<AppState>>,
+    repair: bool,
+) -> Result<LinkIntegrityReport, AppError> {
+    if is_server_backend(&app_state).await {
+        let client = require_server(&app_state).await?;
+        return client.check_note_link_integrity(repair).await;
+    }
+    let db = require_db(&app_state).await?;
+    let enc_key = app_state.encryption_key.read().await;
+    let report = service::note_link::check_integrity(&db, enc_key.as_deref(), repair)
+        .await
+        .map_err(AppError::from)?;
+    if repair {
+        let _ = service::app_log::log_action(
+            &db,
+            "note_link",
+            "repair",
+            None,
+            None,
+            &format!(
+                "Repaired note links: dangling={}, stale_content_links={}, broken_references={}",
+                report.dangling_links.len(),
+                report.stale_content_links,
+                report.broken_references.len()
+            ),
+            None,
+        )
+        .await;
+    }
+    Ok(report)
+}
+
+// ============================================================================
+// 笔记关系图相关命令
+// ============================================================================
+
+/// 获取笔记关系图（全部笔记，或按笔记本、标签限定的子图）
+#[tauri::command]
+pub async fn get_note_graph(
+    app_state: tauri::State<'_, Arc<AppState>>,
+    query: NoteGraphQuery,
+) -> Result<NoteGraph, AppError> {
+    if is_server_backend(&app_state).await {
+        let client = require_server(&app_state).await?;
+        return client.get_note_graph(&query).await;
+    }
+    let db = require_db(&app_state).await?;
+    service::note_graph::build_graph(&db, &query)
+        .await
+        .map_err(AppError::from)
+}
+
+/// 获取以指定笔记为中心的 N 跳邻域
+#[tauri::command]
+pub async fn get_note_neighbourhood(
+    app_state: tauri::State<'_, Arc<AppState>>,
+    note_id: i64,
+    hops: u32,
+) -> Result<NoteGraph, AppError> {
+    if is_server_backend(&app_state).await {
+        let client = require_server(&app_state).await?;
+        return client.get_note_neighbourhood(note_id, hops).await;
+    }
+    let db = require_db(&app_state).await?;
+    service::note_graph::neighbourhood(&db, note_id, hops)
+        .await
+        .map_err(AppError::from)
+}
+
+/// 查找没有任何链接的孤立笔记
+#[tauri::command]
+pub async fn find_orphan_notes(
+    app_state: tauri::State<'_, Arc<AppState>>,
+    query: NoteGraphQuery,
+) -> Result<Vec<GraphNode>, AppError> {
+    if is_server_backend(&app_state).await {
+        let client = require_server(&app_state).await?;
+        return client.find_orphan_notes(&query).await;
+    }
+    let db = require_db(&app_state).await?;
+    service::note_graph::find_orphans(&db, &query)
+        .await
+        .map_err(AppError::from)
+}
+
+/// 将关系图按指定格式写入文件
+fn write_note_graph(graph: &NoteGraph, format: &str, path: &str) -> Result<(), AppError> {
+    let text = service::note_graph::render(graph, format).map_err(AppError::from)?;
+    std::fs::write(path, text)
+        .map_err(|e| AppError::code_with_args("FILE_WRITE_FAILED", vec![e.to_string()]))
+}
+
+/// 导出笔记关系图（graphml / dot / json）
+#[tauri::command]
+pub async fn export_note_graph(
+    app_state: tauri::State<'_, Arc<AppState>>,
+    query: NoteGraphQuery,
+    format: String,
+    path: String,
+) -> Result<(), AppError> {
+    if is_server_backend(&app_state).await {
+        let client = require_server(&app_state).await?;
+        let graph = client.get_note_graph(&query).await?;
+        return write_note_graph(&graph, &format, &path);
+    }
+    let db = require_db(&app_state).await?;
+    let graph = service::note_graph::build_graph(&db, &query)
+        .await
+        .map_err(AppError::from)?;
+    write_note_graph(&graph, &format, &path)?;
+    let _ = service::app_log::log_action(
+        &db,
+        "note_graph",
+        "export",
+        None,
+        None,
+        &format!(
+            "Exported note graph: format={}, nodes={}, edges={}, path={}",
+            format,
+            graph.nodes.len(),
+            graph.edges.len(),
+            path
+        ),
+        None,
+    )
+    .await;
+    Ok(())
+}
+
 // ============================================================================
 // 加密相关命令
 // ============================================================================
@@ -357,6 +523,54 @@ pub async fn cleanup_orphan_attachments(
         .map_err(AppError::from)
 }
 
+/// 重建附件文本索引
+///
+/// 为已有附件重新提取文本，返回成功提取文本的附件数
+#[tauri::command]
+pub async fn rebuild_attachment_text_index(
+    app_state: tauri::State<'_, Arc<AppState>>,
+) -> Result<u32, AppError> {
+    if is_server_backend(&app_state).await {
+        let client = require_server(&app_state).await?;
+        return client.rebuild_attachment_text_index().await;
+    }
+    let db = require_db(&app_state).await?;
+    service::attachment_text::rebuild_index(&db, &app_state.app_data_dir)
+        .await
+        .map_err(AppError::from)
+}
+
+/// 按保留策略精简笔记历史记录
+///
+/// 策略从设置中读取，返回删除的记录数与释放的空间
+#[tauri::command]
+pub async fn compact_note_history(
+    app_state: tauri::State<'_, Arc<AppState>>,
+) -> Result<HistoryCompactResult, AppError> {
+    if is_server_backend(&app_state).await {
+        let client = require_server(&app_state).await?;
+        return client.compact_note_history().await;
+    }
+    let db = require_db(&app_state).await?;
+    let result = service::history_retention::compact_with_settings(&db)
+        .await
+        .map_err(AppError::from)?;
+    let _ = service::app_log::log_action(
+        &db,
+        "note_history",
+        "compact",
+        None,
+        None,
+        &format!(
+            "Compacted note history: deleted={}, freed={} bytes",
+            result.deleted, result.freed_bytes
+        ),
+        None,
+    )
+    .await;
+    Ok(result)
+}
+
 // ============================================================================
 // 帮助手册相关命令
 // ============================================================================
@@ -9,12 +9,14 @@
 //! - 标签命令：CRUD 操作
 //! - 笔记命令：CRUD 和搜索操作
 //! - 历史记录命令：分页搜索
+//! - 保存的搜索命令：CRUD、执行和命中数量统计
+//! - 笔记自动规则命令：CRUD 和手动执行（支持预览）
 
 use std::sync::Arc;
 
 use std::collections::HashMap;
 
-use tracing::info;
+use tracing::{info, warn};
 
 use sea_orm::DatabaseConnection;
 use tauri::Manager;
@@ -23,10 +25,14 @@ use crate::{
     config::{AppState, ProfileBackend},
     error::AppError,
     model::{
-        AppLog, AppLogSearchParam, AttachmentStats, LogFileInfo, Note, NoteAttachment, NoteHistory,
-        NoteHistorySearchPageParam, NoteLink, NoteSearchPageParam, NoteStatsResult, NoteTemplate,
-        Notebook, OperateSource, PageParam, PageResult, SyncLog, SyncLogDetail, SyncOptions,
-        SyncPreview, Tag,
+        AppLog, AppLogSearchParam, AttachmentStats, BatchNoteResult, CursorPageResult, GraphNode,
+        HistoryCompactResult, LinkIntegrityReport, LogFileInfo, Note, NoteAttachment,
+        NoteCursorSearchParam, NoteGraph, NoteGraphQuery, NoteHistory,
+        NoteHistoryContentSearchParam, NoteHistoryHit, NoteHistorySearchPageParam, NoteLink,
+        NoteRevisionDiff, NoteRevisionDiffParam, NoteRule, NoteRuleRunResult, NoteSearchPageParam,
+        NoteStatsResult, NoteTemplate, Notebook, NotebookTreeNode, OperateSource, PageParam,
+        PageResult, SavedSearch, SyncLog, SyncLogDetail, SyncOptions, SyncPreview, Tag,
+        TagTreeNode, TrashPurgeResult,
     },
     service,
     service::enote_server::EnoteServerClient,
@@ -62,8 +68,10 @@ mod backup;
 mod cloud_backup;
 mod misc;
 mod note;
+mod note_rule;
 mod notebook;
 mod profile;
+mod saved_search;
 mod screen_saver;
 mod settings;
 mod sync;
@@ -74,8 +82,10 @@ pub use backup::*;
 pub use cloud_backup::*;
 pub use misc::*;
 pub use note::*;
+pub use note_rule::*;
 pub use notebook::*;
 pub use profile::*;
+pub use saved_search::*;
 pub use screen_saver::*;
 pub use settings::*;
 pub use sync::*;
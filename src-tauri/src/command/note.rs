@@ -121,6 +121,24 @@ pub async fn search_page_notes(
         .map_err(AppError::from)
 }
 
+/// 游标分页搜索笔记（用于无限滚动列表）
+#[tauri::command]
+pub async fn search_cursor_notes(
+    app_state: tauri::State<'_, Arc<AppState>>,
+    mut cursor_param: NoteCursorSearchParam,
+) -> Result<CursorPageResult<Note>, AppError> {
+    cursor_param.normalize();
+    if is_server_backend(&app_state).await {
+        let client = require_server(&app_state).await?;
+        return client.search_cursor_notes(&cursor_param).await;
+    }
+    let db = require_db(&app_state).await?;
+    let enc_key = app_state.encryption_key.read().await;
+    service::note::search_cursor_with_key(&db, &cursor_param, enc_key.as_deref())
+        .await
+        .map_err(AppError::from)
+}
+
 #[tauri::command]
 pub async fn note_stats(
     app_state: tauri::State<'_, Arc<AppState>>,
@@ -170,6 +188,106 @@ pub async fn batch_delete_notes(
         .map_err(AppError::from)
 }
 
+/// 批量设置笔记收藏状态，返回每条笔记的处理结果
+#[tauri::command]
+pub async fn batch_set_note_star(
+    app_state: tauri::State<'_, Arc<AppState>>,
+    note_ids: Vec<i64>,
+    starred: bool,
+) -> Result<Vec<BatchNoteResult>, AppError> {
+    if is_server_backend(&app_state).await {
+        let client = require_server(&app_state).await?;
+        return client.batch_set_note_star(&note_ids, starred).await;
+    }
+    let db = require_db(&app_state).await?;
+    service::note::batch_set_star(&db, &note_ids, starred)
+        .await
+        .map_err(AppError::from)
+}
+
+/// 批量设置笔记置顶状态，返回每条笔记的处理结果
+#[tauri::command]
+pub async fn batch_set_note_pin(
+    app_state: tauri::State<'_, Arc<AppState>>,
+    note_ids: Vec<i64>,
+    pinned: bool,
+) -> Result<Vec<BatchNoteResult>, AppError> {
+    if is_server_backend(&app_state).await {
+        let client = require_server(&app_state).await?;
+        return client.batch_set_note_pin(&note_ids, pinned).await;
+    }
+    let db = require_db(&app_state).await?;
+    service::note::batch_set_pin(&db, &note_ids, pinned)
+        .await
+        .map_err(AppError::from)
+}
+
+/// 批量从回收站恢复笔记，返回每条笔记的处理结果
+#[tauri::command]
+pub async fn batch_restore_notes(
+    app_state: tauri::State<'_, Arc<AppState>>,
+    note_ids: Vec<i64>,
+) -> Result<Vec<BatchNoteResult>, AppError> {
+    if is_server_backend(&app_state).await {
+        let client = require_server(&app_state).await?;
+        return client.batch_restore_notes(&note_ids).await;
+    }
+    let db = require_db(&app_state).await?;
+    let results = service::note::batch_restore(&db, &note_ids)
+        .await
+        .map_err(AppError::from)?;
+    let _ = service::app_log::log_action(
+        &db,
+        "note",
+        "batch_restore",
+        None,
+        None,
+        &format!(
+            "Restored notes {:?} from trash",
+            service::note::changed_ids(&results)
+        ),
+        None,
+    )
+    .await;
+    Ok(results)
+}
+
+/// 批量永久删除回收站中的笔记，返回每条笔记的处理结果
+#[tauri::command]
+pub async fn batch_permanent_delete_notes(
+    app_state: tauri::State<'_, Arc<AppState>>,
+    note_ids: Vec<i64>,
+) -> Result<Vec<BatchNoteResult>, AppError> {
+    if is_server_backend(&app_state).await {
+        let client = require_server(&app_state).await?;
+        return client.batch_permanent_delete_notes(&note_ids).await;
+    }
+    let db = require_db(&app_state).await?;
+    let enc_key = app_state.encryption_key.read().await;
+    let results = service::note::batch_permanent_delete(
+        &db,
+        &note_ids,
+        OperateSource::User,
+        enc_key.as_deref(),
+    )
+    .await
+    .map_err(AppError::from)?;
+    let _ = service::app_log::log_action(
+        &db,
+        "note",
+        "batch_permanent_delete",
+        None,
+        None,
+        &format!(
+            "Permanently deleted notes {:?}",
+            service::note::changed_ids(&results)
+        ),
+        None,
+    )
+    .await;
+    Ok(results)
+}
+
 /// 分页搜索笔记历史记录
 #[tauri::command]
 pub async fn search_page_note_histories(
@@ -187,6 +305,84 @@ pub async fn search_page_note_histories(
         .map_err(AppError::from)
 }
 
+/// 跨笔记搜索历史版本内容
+#[tauri::command]
+pub async fn search_note_history_content(
+    app_state: tauri::State<'_, Arc<AppState>>,
+    mut search_param: NoteHistoryContentSearchParam,
+) -> Result<PageResult<NoteHistoryHit>, AppError> {
+    search_param.normalize();
+    if is_server_backend(&app_state).await {
+        let client = require_server(&app_state).await?;
+        return client.search_note_history_content(&search_param).await;
+    }
+    let db = require_db(&app_state).await?;
+    service::note_history::search_content(&db, &search_param)
+        .await
+        .map_err(AppError::from)
+}
+
+/// 对比两个历史版本，或历史版本与当前版本
+#[tauri::command]
+pub async fn diff_note_revisions(
+    app_state: tauri::State<'_, Arc<AppState>>,
+    diff_param: NoteRevisionDiffParam,
+) -> Result<NoteRevisionDiff, AppError> {
+    if is_server_backend(&app_state).await {
+        let client = require_server(&app_state).await?;
+        return client.diff_note_revisions(&diff_param).await;
+    }
+    let db = require_db(&app_state).await?;
+    let enc_key = app_state.encryption_key.read().await;
+    service::note_revision::diff(&db, &diff_param, enc_key.as_deref())
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::code("REVISION_NOT_FOUND"))
+}
+
+/// 将笔记恢复到指定历史版本，恢复操作本身记录为新的历史版本
+///
+/// `version` 为前端读取到的笔记版本，传入时笔记已被修改则返回版本冲突
+#[tauri::command]
+pub async fn restore_note_revision(
+    app_state: tauri::State<'_, Arc<AppState>>,
+    note_id: i64,
+    history_id: i64,
+    version: Option<i64>,
+) -> Result<Note, AppError> {
+    let version = version.unwrap_or(0);
+    if is_server_backend(&app_state).await {
+        let client = require_server(&app_state).await?;
+        return client
+            .restore_note_revision(note_id, history_id, version)
+            .await;
+    }
+    let db = require_db(&app_state).await?;
+    let enc_key = app_state.encryption_key.read().await;
+    let note = service::note_revision::restore(
+        &db,
+        note_id,
+        history_id,
+        version,
+        OperateSource::User,
+        enc_key.as_deref(),
+    )
+    .await
+    .map_err(AppError::from)?
+    .ok_or_else(|| AppError::code("REVISION_NOT_FOUND"))?;
+    let _ = service::app_log::log_action(
+        &db,
+        "note",
+        "restore_revision",
+        Some(&note_id.to_string()),
+        Some(&note.title),
+        &format!("Restored note: {} to revision {}", note.title, history_id),
+        None,
+    )
+    .await;
+    Ok(note)
+}
+
 /// 切换笔记收藏/星标状态
 #[tauri::command]
 pub async fn toggle_note_star(
@@ -299,6 +495,46 @@ pub async fn empty_trash(app_state: tauri::State<'_, Arc<AppState>>) -> Result<(
     Ok(())
 }
 
+/// 按保留天数清理回收站
+///
+/// 保留天数从设置中读取；`dry_run` 为 true 时只返回将被删除的笔记
+#[tauri::command]
+pub async fn purge_trash(
+    app_state: tauri::State<'_, Arc<AppState>>,
+    dry_run: bool,
+) -> Result<TrashPurgeResult, AppError> {
+    if is_server_backend(&app_state).await {
+        let client = require_server(&app_state).await?;
+        return client.purge_trash(dry_run).await;
+    }
+    let db = require_db(&app_state).await?;
+    let enc_key = app_state.encryption_key.read().await;
+    let result = service::trash_retention::purge_with_settings(
+        &db,
+        &app_state.app_data_dir,
+        dry_run,
+        enc_key.as_deref(),
+    )
+    .await
+    .map_err(AppError::from)?;
+    if !dry_run {
+        let _ = service::app_log::log_action(
+            &db,
+            "note",
+            "purge_trash",
+            None,
+            None,
+            &format!(
+                "Purged trash: retention_days={}, purged={}",
+                result.retention_days, result.purged
+            ),
+            None,
+        )
+        .await;
+    }
+    Ok(result)
+}
+
 /// 获取回收站笔记列表（分页）
 #[tauri::command]
 pub async fn find_deleted_notes(
@@ -0,0 +1,146 @@
+use super::*;
+
+/// 获取所有笔记自动规则（按执行顺序）
+#[tauri::command]
+pub async fn find_all_note_rules(
+    app_state: tauri::State<'_, Arc<AppState>>,
+) -> Result<Vec<NoteRule>, AppError> {
+    if is_server_backend(&app_state).await {
+        let client = require_server(&app_state).await?;
+        return client.find_all_note_rules().await;
+    }
+    let db = require_db(&app_state).await?;
+    service::note_rule::find_all(&db)
+        .await
+        .map_err(AppError::from)
+}
+
+/// 创建笔记自动规则
+#[tauri::command]
+pub async fn create_note_rule(
+    app_state: tauri::State<'_, Arc<AppState>>,
+    note_rule: NoteRule,
+) -> Result<Option<NoteRule>, AppError> {
+    note_rule.validate().map_err(AppError::from)?;
+    if is_server_backend(&app_state).await {
+        let client = require_server(&app_state).await?;
+        return client.create_note_rule(&note_rule).await;
+    }
+    let db = require_db(&app_state).await?;
+    let result = service::note_rule::create(&db, &note_rule)
+        .await
+        .map_err(AppError::from)?;
+    if let Some(ref r) = result {
+        let _ = service::app_log::log_action(
+            &db,
+            "note_rule",
+            "create",
+            Some(&r.id.to_string()),
+            Some(&r.name),
+            &format!("Created note rule: {}", r.name),
+            None,
+        )
+        .await;
+    }
+    Ok(result)
+}
+
+/// 更新笔记自动规则
+#[tauri::command]
+pub async fn update_note_rule(
+    app_state: tauri::State<'_, Arc<AppState>>,
+    note_rule: NoteRule,
+) -> Result<Option<NoteRule>, AppError> {
+    note_rule.validate().map_err(AppError::from)?;
+    if is_server_backend(&app_state).await {
+        let client = require_server(&app_state).await?;
+        return client.update_note_rule(&note_rule).await;
+    }
+    let db = require_db(&app_state).await?;
+    let result = service::note_rule::update(&db, &note_rule)
+        .await
+        .map_err(AppError::from)?;
+    if result.is_some() {
+        let _ = service::app_log::log_action(
+            &db,
+            "note_rule",
+            "update",
+            Some(&note_rule.id.to_string()),
+            Some(&note_rule.name),
+            &format!("Updated note rule: {}", note_rule.name),
+            None,
+        )
+        .await;
+    }
+    Ok(result)
+}
+
+/// 根据 ID 删除笔记自动规则
+#[tauri::command]
+pub async fn delete_note_rule_by_id(
+    app_state: tauri::State<'_, Arc<AppState>>,
+    id: i64,
+) -> Result<(), AppError> {
+    if is_server_backend(&app_state).await {
+        let client = require_server(&app_state).await?;
+        return client.delete_note_rule_by_id(id).await;
+    }
+    let db = require_db(&app_state).await?;
+    service::note_rule::delete_by_id(&db, id)
+        .await
+        .map_err(AppError::from)?;
+    let _ = service::app_log::log_action(
+        &db,
+        "note_rule",
+        "delete",
+        Some(&id.to_string()),
+        None,
+        &format!("Deleted note rule id={}", id),
+        None,
+    )
+    .await;
+    Ok(())
+}
+
+/// 对已有笔记手动执行规则
+///
+/// `rule_ids` 为空时执行所有启用的规则；`dry_run` 为 true 时只返回预览
+#[tauri::command]
+pub async fn run_note_rules(
+    app_state: tauri::State<'_, Arc<AppState>>,
+    rule_ids: Vec<i64>,
+    dry_run: bool,
+) -> Result<NoteRuleRunResult, AppError> {
+    if is_server_backend(&app_state).await {
+        let client = require_server(&app_state).await?;
+        return client.run_note_rules(&rule_ids, dry_run).await;
+    }
+    let db = require_db(&app_state).await?;
+    let enc_key = app_state.encryption_key.read().await;
+    let result = service::note_rule::run(
+        &db,
+        &rule_ids,
+        dry_run,
+        OperateSource::User,
+        enc_key.as_deref(),
+    )
+    .await
+    .map_err(AppError::from)?;
+    if !dry_run {
+        let _ = service::app_log::log_action(
+            &db,
+            "note_rule",
+            "run",
+            None,
+            None,
+            &format!(
+                "Ran note rules {:?}, {} notes changed",
+                rule_ids,
+                result.matches.len()
+            ),
+            None,
+        )
+        .await;
+    }
+    Ok(result)
+}
@@ -16,6 +16,21 @@ pub async fn find_all_notebooks(
     Ok(notebooks)
 }
 
+/// 获取笔记本树（子笔记本位于各节点的 children 中）
+#[tauri::command]
+pub async fn find_notebook_tree(
+    app_state: tauri::State<'_, Arc<AppState>>,
+) -> Result<Vec<NotebookTreeNode>, AppError> {
+    if is_server_backend(&app_state).await {
+        let client = require_server(&app_state).await?;
+        return client.find_notebook_tree().await;
+    }
+    let db = require_db(&app_state).await?;
+    service::notebook::find_tree(&db)
+        .await
+        .map_err(AppError::from)
+}
+
 /// 创建笔记本
 #[tauri::command]
 pub async fn create_notebook(
@@ -102,3 +117,33 @@ pub async fn update_notebook(
     }
     Ok(result)
 }
+
+/// 移动笔记本（连同子孙笔记本）到新的父笔记本下，`parent_id` 为 0 表示移动到顶级
+#[tauri::command]
+pub async fn move_notebook(
+    app_state: tauri::State<'_, Arc<AppState>>,
+    id: i64,
+    parent_id: i64,
+) -> Result<Option<Notebook>, AppError> {
+    if is_server_backend(&app_state).await {
+        let client = require_server(&app_state).await?;
+        return client.move_notebook(id, parent_id).await;
+    }
+    let db = require_db(&app_state).await?;
+    let result = service::notebook::move_subtree(&db, id, parent_id)
+        .await
+        .map_err(AppError::from)?;
+    if let Some(ref nb) = result {
+        let _ = service::app_log::log_action(
+            &db,
+            "notebook",
+            "move",
+            Some(&nb.id.to_string()),
+            Some(&nb.name),
+            &format!("Moved notebook {} under parent id={}", nb.name, parent_id),
+            None,
+        )
+        .await;
+    }
+    Ok(result)
+}
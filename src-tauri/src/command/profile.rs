@@ -269,6 +269,12 @@ pub async fn reconnect_profile(
         #[cfg(not(any(feature = "desktop", feature = "db-full")))]
         let new_encryption_key: Option<String> = None;
 
+        // 迁移前写入的内容引用在后台回填
+        service::note_link::spawn_backfill_content_links(
+            new_db.clone(),
+            new_encryption_key.clone(),
+        );
+
         // 7. 替换 AppState 中的连接和相关状态
         {
             let mut db_guard = app_state.database_connection.write().await;
@@ -0,0 +1,123 @@
+use super::*;
+
+/// 获取所有保存的搜索
+#[tauri::command]
+pub async fn find_all_saved_searches(
+    app_state: tauri::State<'_, Arc<AppState>>,
+) -> Result<Vec<SavedSearch>, AppError> {
+    if is_server_backend(&app_state).await {
+        let client = require_server(&app_state).await?;
+        return client.find_all_saved_searches().await;
+    }
+    let db = require_db(&app_state).await?;
+    service::saved_search::find_all(&db)
+        .await
+        .map_err(AppError::from)
+}
+
+/// 创建保存的搜索
+#[tauri::command]
+pub async fn create_saved_search(
+    app_state: tauri::State<'_, Arc<AppState>>,
+    saved_search: SavedSearch,
+) -> Result<Option<SavedSearch>, AppError> {
+    saved_search.validate().map_err(AppError::from)?;
+    if is_server_backend(&app_state).await {
+        let client = require_server(&app_state).await?;
+        return client.create_saved_search(&saved_search).await;
+    }
+    let db = require_db(&app_state).await?;
+    let result = service::saved_search::create(&db, &saved_search)
+        .await
+        .map_err(AppError::from)?;
+    if let Some(ref s) = result {
+        let _ = service::app_log::log_action(
+            &db,
+            "saved_search",
+            "create",
+            Some(&s.id.to_string()),
+            Some(&s.name),
+            &format!("Created saved search: {}", s.name),
+            None,
+        )
+        .await;
+    }
+    Ok(result)
+}
+
+/// 更新保存的搜索
+#[tauri::command]
+pub async fn update_saved_search(
+    app_state: tauri::State<'_, Arc<AppState>>,
+    saved_search: SavedSearch,
+) -> Result<Option<SavedSearch>, AppError> {
+    saved_search.validate().map_err(AppError::from)?;
+    if is_server_backend(&app_state).await {
+        let client = require_server(&app_state).await?;
+        return client.update_saved_search(&saved_search).await;
+    }
+    let db = require_db(&app_state).await?;
+    let result = service::saved_search::update(&db, &saved_search)
+        .await
+        .map_err(AppError::from)?;
+    if result.is_some() {
+        let _ = service::app_log::log_action(
+            &db,
+            "saved_search",
+            "update",
+            Some(&saved_search.id.to_string()),
+            Some(&saved_search.name),
+            &format!("Updated saved search: {}", saved_search.name),
+            None,
+        )
+        .await;
+    }
+    Ok(result)
+}
+
+/// 根据 ID 删除保存的搜索
+#[tauri::command]
+pub async fn delete_saved_search_by_id(
+    app_state: tauri::State<'_, Arc<AppState>>,
+    id: i64,
+) -> Result<(), AppError> {
+    if is_server_backend(&app_state).await {
+        let client = require_server(&app_state).await?;
+        return client.delete_saved_search_by_id(id).await;
+    }
+    let db = require_db(&app_state).await?;
+    service::saved_search::delete_by_id(&db, id)
+        .await
+        .map_err(AppError::from)?;
+    let _ = service::app_log::log_action(
+        &db,
+        "saved_search",
+        "delete",
+        Some(&id.to_string()),
+        None,
+        &format!("Deleted saved search id={}", id),
+        None,
+    )
+    .await;
+    Ok(())
+}
+
+/// 执行保存的搜索（分页）
+#[tauri::command]
+pub async fn run_saved_search(
+    app_state: tauri::State<'_, Arc<AppState>>,
+    id: i64,
+    mut page_param: PageParam,
+) -> Result<PageResult<Note>, AppError> {
+    page_param.normalize();
+    if is_server_backend(&app_state).await {
+        let client = require_server(&app_state).await?;
+        return client.run_saved_search(id, &page_param).await;
+    }
+    let db = require_db(&app_state).await?;
+    let enc_key = app_state.encryption_key.read().await;
+    service::saved_search::run(&db, id, page_param, enc_key.as_deref())
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::code("SAVED_SEARCH_NOT_FOUND"))
+}
@@ -14,7 +14,20 @@ pub async fn find_all_tags(
     Ok(tags)
 }
 
-/// 创建标签
+/// 获取标签树（子标签位于各节点的 children 中）
+#[tauri::command]
+pub async fn find_tag_tree(
+    app_state: tauri::State<'_, Arc<AppState>>,
+) -> Result<Vec<TagTreeNode>, AppError> {
+    if is_server_backend(&app_state).await {
+        let client = require_server(&app_state).await?;
+        return client.find_tag_tree().await;
+    }
+    let db = require_db(&app_state).await?;
+    service::tag::find_tree(&db).await.map_err(AppError::from)
+}
+
+/// 创建标签（名称为 `a/b/c` 形式的路径时自动创建缺失的祖先标签）
 #[tauri::command]
 pub async fn create_tag(
     app_state: tauri::State<'_, Arc<AppState>>,
@@ -100,3 +113,120 @@ pub async fn update_tag(
     }
     Ok(result)
 }
+
+/// 移动标签（连同子孙标签）到新的父标签下，`parent_id` 为 0 表示移动到顶级
+#[tauri::command]
+pub async fn move_tag(
+    app_state: tauri::State<'_, Arc<AppState>>,
+    id: i64,
+    parent_id: i64,
+) -> Result<Option<Tag>, AppError> {
+    if is_server_backend(&app_state).await {
+        let client = require_server(&app_state).await?;
+        return client.move_tag(id, parent_id).await;
+    }
+    let db = require_db(&app_state).await?;
+    let result = service::tag::move_subtree(&db, id, parent_id)
+        .await
+        .map_err(AppError::from)?;
+    if let Some(ref t) = result {
+        let _ = service::app_log::log_action(
+            &db,
+            "tag",
+            "move",
+            Some(&t.id.to_string()),
+            Some(&t.name),
+            &format!("Moved tag {} under parent id={}", t.name, parent_id),
+            None,
+        )
+        .await;
+    }
+    Ok(result)
+}
+
+/// 合并标签：源标签改写为目标标签后删除源标签，返回标签发生变化的笔记数量
+#[tauri::command]
+pub async fn merge_tags(
+    app_state: tauri::State<'_, Arc<AppState>>,
+    source_ids: Vec<i64>,
+    target_id: i64,
+) -> Result<u64, AppError> {
+    if is_server_backend(&app_state).await {
+        let client = require_server(&app_state).await?;
+        return client.merge_tags(&source_ids, target_id).await;
+    }
+    let db = require_db(&app_state).await?;
+    let enc_key = app_state.encryption_key.read().await;
+    let affected = service::tag::merge_tags(
+        &db,
+        &source_ids,
+        target_id,
+        None,
+        OperateSource::User,
+        enc_key.as_deref(),
+    )
+    .await
+    .map_err(AppError::from)?;
+    let _ = service::app_log::log_action(
+        &db,
+        "tag",
+        "merge",
+        Some(&target_id.to_string()),
+        None,
+        &format!(
+            "Merged tags {:?} into tag id={}, {} notes affected",
+            source_ids, target_id, affected
+        ),
+        None,
+    )
+    .await;
+    Ok(affected)
+}
+
+/// 批量为笔记添加标签，返回新增标签的笔记数量
+#[tauri::command]
+pub async fn add_tag_to_notes(
+    app_state: tauri::State<'_, Arc<AppState>>,
+    note_ids: Vec<i64>,
+    tag_id: i64,
+) -> Result<u64, AppError> {
+    if is_server_backend(&app_state).await {
+        let client = require_server(&app_state).await?;
+        return client.add_tag_to_notes(&note_ids, tag_id).await;
+    }
+    let db = require_db(&app_state).await?;
+    let enc_key = app_state.encryption_key.read().await;
+    service::tag::add_tag_to_notes(
+        &db,
+        &note_ids,
+        tag_id,
+        OperateSource::User,
+        enc_key.as_deref(),
+    )
+    .await
+    .map_err(AppError::from)
+}
+
+/// 批量从笔记移除标签，返回移除了该标签的笔记数量
+#[tauri::command]
+pub async fn remove_tag_from_notes(
+    app_state: tauri::State<'_, Arc<AppState>>,
+    note_ids: Vec<i64>,
+    tag_id: i64,
+) -> Result<u64, AppError> {
+    if is_server_backend(&app_state).await {
+        let client = require_server(&app_state).await?;
+        return client.remove_tag_from_notes(&note_ids, tag_id).await;
+    }
+    let db = require_db(&app_state).await?;
+    let enc_key = app_state.encryption_key.read().await;
+    service::tag::remove_tag_from_notes(
+        &db,
+        &note_ids,
+        tag_id,
+        OperateSource::User,
+        enc_key.as_deref(),
+    )
+    .await
+    .map_err(AppError::from)
+}
@@ -5,11 +5,14 @@ pub mod prelude;
 pub mod app_log;
 pub mod note;
 pub mod note_attachment;
+pub mod note_attachment_text;
 pub mod note_history;
 pub mod note_link;
+pub mod note_rule;
 pub mod note_tags;
 pub mod note_template;
 pub mod notebook;
+pub mod saved_search;
 pub mod settings;
 pub mod sync_log;
 pub mod sync_log_detail;
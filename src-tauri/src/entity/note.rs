@@ -50,6 +50,9 @@ pub struct Model {
     pub update_time: DateTime,
     /// 软删除时间，NULL 表示未删除
     pub deleted_at: Option<DateTime>,
+    /// 版本号：每次编辑后加 1，用于更新时的冲突检测
+    #[sea_orm(default_value = "1")]
+    pub version: i64,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -0,0 +1,37 @@
+//! 附件提取文本实体
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "note_attachment_text")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// 对应的附件 ID（唯一）
+    pub attachment_id: i64,
+    /// 附件所属笔记 ID（冗余存储，便于按笔记过滤）
+    pub note_id: i64,
+    /// 提取出的纯文本
+    #[sea_orm(column_type = "Text")]
+    pub content: String,
+    pub create_time: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::note_attachment::Entity",
+        from = "Column::AttachmentId",
+        to = "super::note_attachment::Column::Id"
+    )]
+    NoteAttachment,
+}
+
+impl Related<super::note_attachment::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::NoteAttachment.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -21,6 +21,12 @@ pub struct Model {
     pub operate_source: i32,
     pub operate_time: DateTime,
     pub create_time: DateTime,
+    /// 内容存储格式（位标志）：0 = 完整文本，1 = old_content 为增量，2 = new_content 为增量
+    #[sea_orm(default_value = "0")]
+    pub content_format: i32,
+    /// old_content 为增量时，基准记录（同一笔记的上一条历史记录）的 ID
+    #[sea_orm(default_value = "0")]
+    pub base_id: i64,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
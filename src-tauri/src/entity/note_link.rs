@@ -11,6 +11,12 @@ pub struct Model {
     pub source_note_id: i64,
     pub target_note_id: i64,
     pub create_time: DateTime,
+    /// 链接来源：0 = 手动创建，1 = 从笔记内容解析
+    #[sea_orm(default_value = "0")]
+    pub origin: i32,
+    /// 关系类型（如 references、depends-on、supersedes 或自定义标签），空字符串表示未指定
+    #[sea_orm(default_value = "")]
+    pub relation: String,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
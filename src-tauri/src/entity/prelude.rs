@@ -4,11 +4,14 @@
 
 pub use super::note::Entity as Note;
 pub use super::note_attachment::Entity as NoteAttachment;
+pub use super::note_attachment_text::Entity as NoteAttachmentText;
 pub use super::note_history::Entity as NoteHistory;
 pub use super::note_link::Entity as NoteLink;
+pub use super::note_rule::Entity as NoteRule;
 pub use super::note_tags::Entity as NoteTags;
 pub use super::note_template::Entity as NoteTemplate;
 pub use super::notebook::Entity as Notebook;
+pub use super::saved_search::Entity as SavedSearch;
 pub use super::settings::Entity as Settings;
 pub use super::sync_log::Entity as SyncLog;
 pub use super::sync_log_detail::Entity as SyncLogDetail;
@@ -8,6 +8,8 @@ use serde::{Deserialize, Serialize};
 pub struct Model {
     #[sea_orm(primary_key)]
     pub id: i64,
+    /// 父标签 ID，0 表示顶级
+    pub parent_id: i64,
     pub name: String,
     pub icon: String,
     pub cls: String,
@@ -162,6 +162,34 @@ fn zh_cn_messages() -> HashMap<String, String> {
         "validation.keywordTooLong".to_string(),
         "搜索关键词不能超过 {} 个字符".to_string(),
     );
+    messages.insert(
+        "validation.tooManyFilterIds".to_string(),
+        "筛选的笔记本或标签不能超过 {} 个".to_string(),
+    );
+    messages.insert(
+        "validation.keywordRequired".to_string(),
+        "搜索关键词不能为空".to_string(),
+    );
+    messages.insert(
+        "validation.invalidCursor".to_string(),
+        "分页游标无效或已过期，请重新搜索".to_string(),
+    );
+    messages.insert(
+        "validation.invalidRevision".to_string(),
+        "请选择两个不同的版本进行对比".to_string(),
+    );
+    messages.insert(
+        "validation.revisionEncrypted".to_string(),
+        "该版本内容已加密且无法解密，不能恢复".to_string(),
+    );
+    messages.insert(
+        "validation.revisionNoteDeleted".to_string(),
+        "笔记在回收站中，请先从回收站恢复后再恢复历史版本".to_string(),
+    );
+    messages.insert(
+        "validation.relationTooLong".to_string(),
+        "关系类型不能超过 {} 个字符".to_string(),
+    );
     messages.insert(
         "validation.nameRequired".to_string(),
         "名称不能为空".to_string(),
@@ -174,6 +202,18 @@ fn zh_cn_messages() -> HashMap<String, String> {
         "validation.titleTooLong".to_string(),
         "标题不能超过 {} 个字符".to_string(),
     );
+    messages.insert(
+        "validation.invalidRuleCondition".to_string(),
+        "规则的第 {} 个条件无效".to_string(),
+    );
+    messages.insert(
+        "validation.ruleActionRequired".to_string(),
+        "规则至少需要一个动作".to_string(),
+    );
+    messages.insert(
+        "validation.invalidRuleAction".to_string(),
+        "规则的第 {} 个动作无效".to_string(),
+    );
 
     messages
 }
@@ -317,6 +357,35 @@ fn en_us_messages() -> HashMap<String, String> {
         "validation.keywordTooLong".to_string(),
         "Search keyword cannot exceed {} characters".to_string(),
     );
+    messages.insert(
+        "validation.tooManyFilterIds".to_string(),
+        "Cannot filter by more than {} notebooks or tags".to_string(),
+    );
+    messages.insert(
+        "validation.keywordRequired".to_string(),
+        "Search keyword is required".to_string(),
+    );
+    messages.insert(
+        "validation.invalidCursor".to_string(),
+        "Invalid or expired page cursor, please search again".to_string(),
+    );
+    messages.insert(
+        "validation.invalidRevision".to_string(),
+        "Please select two different revisions to compare".to_string(),
+    );
+    messages.insert(
+        "validation.revisionEncrypted".to_string(),
+        "This revision is encrypted and cannot be decrypted, so it cannot be restored".to_string(),
+    );
+    messages.insert(
+        "validation.revisionNoteDeleted".to_string(),
+        "The note is in the trash; restore it from the trash before restoring a revision"
+            .to_string(),
+    );
+    messages.insert(
+        "validation.relationTooLong".to_string(),
+        "Relation type cannot exceed {} characters".to_string(),
+    );
     messages.insert(
         "validation.nameRequired".to_string(),
         "Name is required".to_string(),
@@ -329,6 +398,18 @@ fn en_us_messages() -> HashMap<String, String> {
         "validation.titleTooLong".to_string(),
         "Title cannot exceed {} characters".to_string(),
     );
+    messages.insert(
+        "validation.invalidRuleCondition".to_string(),
+        "Rule condition #{} is invalid".to_string(),
+    );
+    messages.insert(
+        "validation.ruleActionRequired".to_string(),
+        "A rule needs at least one action".to_string(),
+    );
+    messages.insert(
+        "validation.invalidRuleAction".to_string(),
+        "Rule action #{} is invalid".to_string(),
+    );
 
     messages
 }
@@ -149,25 +149,40 @@ pub fn run_with_config(config_path: Option<String>) {
             command::reconnect_profile,
             // 笔记本相关命令
             command::find_all_notebooks,
+            command::find_notebook_tree,
             command::create_notebook,
             command::delete_notebook_by_id,
             command::update_notebook,
+            command::move_notebook,
             // 标签相关命令
             command::find_all_tags,
+            command::find_tag_tree,
             command::create_tag,
             command::delete_tag_by_id,
             command::update_tag,
+            command::move_tag,
+            command::merge_tags,
+            command::add_tag_to_notes,
+            command::remove_tag_from_notes,
             // 笔记相关命令
             command::create_note,
             command::update_note,
             command::delete_note_by_id,
             command::search_page_notes,
+            command::search_cursor_notes,
             command::note_stats,
             // 批量操作相关命令
             command::batch_move_notes,
             command::batch_delete_notes,
+            command::batch_set_note_star,
+            command::batch_set_note_pin,
+            command::batch_restore_notes,
+            command::batch_permanent_delete_notes,
             // 历史记录相关命令
             command::search_page_note_histories,
+            command::search_note_history_content,
+            command::diff_note_revisions,
+            command::restore_note_revision,
             // 数据备份相关命令
             command::export_backup,
             command::import_backup,
@@ -182,6 +197,7 @@ pub fn run_with_config(config_path: Option<String>) {
             command::restore_note,
             command::permanent_delete_note,
             command::empty_trash,
+            command::purge_trash,
             command::find_deleted_notes,
             // 排序相关命令
             command::reorder_notebooks,
@@ -206,11 +222,29 @@ pub fn run_with_config(config_path: Option<String>) {
             command::create_template,
             command::update_template,
             command::delete_template_by_id,
+            command::find_all_saved_searches,
+            command::create_saved_search,
+            command::update_saved_search,
+            command::delete_saved_search_by_id,
+            command::run_saved_search,
+            command::find_all_note_rules,
+            command::create_note_rule,
+            command::update_note_rule,
+            command::delete_note_rule_by_id,
+            command::run_note_rules,
             // 笔记链接相关命令
             command::find_note_links,
+            command::find_note_outgoing_links,
+            command::find_note_backlinks,
             command::create_note_link,
             command::delete_note_link,
             command::search_linkable_notes,
+            command::check_note_link_integrity,
+            // 笔记关系图相关命令
+            command::get_note_graph,
+            command::get_note_neighbourhood,
+            command::find_orphan_notes,
+            command::export_note_graph,
             // 加密相关命令
             command::encrypt_content,
             command::decrypt_content,
@@ -247,6 +281,8 @@ pub fn run_with_config(config_path: Option<String>) {
             command::open_attachment,
             command::get_attachment_stats,
             command::cleanup_orphan_attachments,
+            command::rebuild_attachment_text_index,
+            command::compact_note_history,
             // 屏保相关命令
             command::ss_start,
             command::ss_stop,
@@ -464,6 +500,8 @@ async fn setup_normal_mode(
             std::process::exit(1);
         }
         info!("Database migration completed");
+        // 迁移前写入的内容引用在后台回填
+        service::note_link::spawn_backfill_content_links(db.clone(), encryption_key.clone());
     }
 
     let app_state = Arc::new(AppState {
@@ -476,6 +514,8 @@ async fn setup_normal_mode(
         settings_cache: tokio::sync::RwLock::new(None),
     });
 
+    #[cfg(feature = "desktop")]
+    start_trash_purge_loop(app_state.clone());
     app.manage(app_state);
 
     // 初始化屏保服务（先注册，计时器循环在托盘创建后启动）
@@ -515,6 +555,8 @@ async fn setup_wizard_mode_state(app: &mut tauri::App) -> Result<(), Box<dyn std
         settings_cache: tokio::sync::RwLock::new(None),
     });
 
+    #[cfg(feature = "desktop")]
+    start_trash_purge_loop(app_state.clone());
     app.manage(app_state);
 
     // 初始化屏保服务（先注册，计时器循环在托盘创建后启动）
@@ -534,6 +576,53 @@ async fn setup_wizard_mode_state(app: &mut tauri::App) -> Result<(), Box<dyn std
     Ok(())
 }
 
+/// 启动回收站自动清理任务（仅桌面端）
+///
+/// 启动时及之后每小时按设置中的保留天数清理一次回收站；
+/// 每次执行时读取当前连接，Profile 切换或向导完成后同样生效
+#[cfg(feature = "desktop")]
+fn start_trash_purge_loop(app_state: Arc<AppState>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60 * 60));
+        loop {
+            interval.tick().await;
+            if command::is_server_backend(&app_state).await {
+                continue;
+            }
+            let Some(db) = app_state.database_connection.read().await.clone() else {
+                continue;
+            };
+            let enc_key = app_state.encryption_key.read().await.clone();
+            let purged = service::trash_retention::purge_with_settings(
+                &db,
+                &app_state.app_data_dir,
+                false,
+                enc_key.as_deref(),
+            )
+            .await;
+            match purged {
+                Ok(result) if result.purged > 0 => {
+                    let _ = service::app_log::log_action(
+                        &db,
+                        "note",
+                        "purge_trash",
+                        None,
+                        None,
+                        &format!(
+                            "Automatically purged trash: retention_days={}, purged={}",
+                            result.retention_days, result.purged
+                        ),
+                        None,
+                    )
+                    .await;
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Automatic trash purge failed: {}", e),
+            }
+        }
+    });
+}
+
 /// 设置应用菜单栏（仅桌面端编译）
 #[cfg(feature = "desktop")]
 fn setup_app_menu(app: &mut tauri::App) -> Result<(), Box<dyn std::error::Error>> {
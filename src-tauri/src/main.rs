@@ -12,12 +12,18 @@ struct Args {
     /// 指定配置文件路径
     #[arg(short, long)]
     config: Option<String>,
+    /// 内部使用：从标准输入解析 PDF 并输出文本（附件索引子进程）
+    #[arg(long = "extract-pdf-text", hide = true)]
+    extract_pdf_text: bool,
 }
 
 fn main() {
     #[cfg(feature = "desktop")]
     {
         let args = Args::parse();
+        if args.extract_pdf_text {
+            std::process::exit(enote_lib::service::attachment_text::run_pdf_extractor());
+        }
         enote_lib::run_with_config(args.config);
     }
     #[cfg(not(feature = "desktop"))]
@@ -0,0 +1,295 @@
+//! 为 MySQL / PostgreSQL 添加原生全文索引
+//!
+//! SQLite 已有 `note_fts`（FTS5），此前 MySQL/PG 只能使用 `LIKE '%kw%'` 全表扫描。
+//!
+//! ## PostgreSQL
+//! - note 表新增 `search_vector tsvector` 列与 GIN 索引
+//! - 标题（权重 A）、标签（权重 B）、去 HTML 后的内容（权重 D）合并为一个 tsvector，
+//!   加密内容不参与索引
+//! - 触发器维护：note 的标题/内容变化、note_tags 的增删、tag 重命名
+//! - 使用 `simple` 配置，不做词干化，与 SQLite trigram 的“原文匹配”行为接近
+//!
+//! ## MySQL
+//! - note(title, content) 与 tag(name) 添加 FULLTEXT 索引，使用 ngram 解析器以支持中文
+//! - 不支持 ngram 解析器的服务端（如 MariaDB）退化为默认解析器；实际使用的解析器记录在索引定义中，
+//!   搜索时据此判断，默认解析器下不足 3 个字符或含中日韩字符的关键词回退到 LIKE
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// PostgreSQL: 计算单条笔记 tsvector 的函数
+const PG_VECTOR_FUNCTION: &str = r#"
+CREATE OR REPLACE FUNCTION note_search_vector(p_id BIGINT, p_title TEXT, p_content TEXT)
+RETURNS tsvector AS $$
+    SELECT setweight(to_tsvector('simple', coalesce(p_title, '')), 'A')
+        || setweight(to_tsvector('simple', coalesce(
+            (SELECT string_agg(t.name, ' ') FROM note_tags nt JOIN tag t ON t.id = nt.tag_id WHERE nt.note_id = p_id),
+            '')), 'B')
+        || setweight(to_tsvector('simple',
+            CASE WHEN p_content LIKE 'ENOTE_ENC_V1:%' OR p_content LIKE 'ENOTE_ENC_V2:%' THEN ''
+            ELSE regexp_replace(coalesce(p_content, ''), '<[^>]*>', ' ', 'g')
+            END), 'D')
+$$ LANGUAGE sql STABLE
+"#;
+
+/// PostgreSQL: note 表 BEFORE 触发器函数
+const PG_NOTE_TRIGGER_FUNCTION: &str = r#"
+CREATE OR REPLACE FUNCTION note_search_vector_trigger() RETURNS trigger AS $$
+BEGIN
+    NEW.search_vector := note_search_vector(NEW.id, NEW.title, NEW.content);
+    RETURN NEW;
+END
+$$ LANGUAGE plpgsql
+"#;
+
+/// PostgreSQL: note_tags 表 AFTER 触发器函数（标签增删后刷新所属笔记）
+const PG_NOTE_TAGS_TRIGGER_FUNCTION: &str = r#"
+CREATE OR REPLACE FUNCTION note_tags_search_vector_trigger() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        UPDATE note SET search_vector = note_search_vector(id, title, content) WHERE id = OLD.note_id;
+        RETURN OLD;
+    END IF;
+    UPDATE note SET search_vector = note_search_vector(id, title, content) WHERE id = NEW.note_id;
+    RETURN NEW;
+END
+$$ LANGUAGE plpgsql
+"#;
+
+/// PostgreSQL: tag 表 AFTER 触发器函数（标签重命名后刷新关联笔记）
+const PG_TAG_TRIGGER_FUNCTION: &str = r#"
+CREATE OR REPLACE FUNCTION tag_search_vector_trigger() RETURNS trigger AS $$
+BEGIN
+    UPDATE note SET search_vector = note_search_vector(id, title, content)
+    WHERE id IN (SELECT note_id FROM note_tags WHERE tag_id = NEW.id);
+    RETURN NEW;
+END
+$$ LANGUAGE plpgsql
+"#;
+
+/// PostgreSQL: 升级语句（按顺序执行）
+///
+/// 先建列与函数，再重建触发器，回填已有笔记后创建 GIN 索引
+const PG_UP_STATEMENTS: [&str; 13] = [
+    "ALTER TABLE note ADD COLUMN IF NOT EXISTS search_vector tsvector",
+    PG_VECTOR_FUNCTION,
+    PG_NOTE_TRIGGER_FUNCTION,
+    PG_NOTE_TAGS_TRIGGER_FUNCTION,
+    PG_TAG_TRIGGER_FUNCTION,
+    "DROP TRIGGER IF EXISTS note_search_vector_update ON note",
+    "CREATE TRIGGER note_search_vector_update BEFORE INSERT OR UPDATE OF title, content ON note FOR EACH ROW EXECUTE PROCEDURE note_search_vector_trigger()",
+    "DROP TRIGGER IF EXISTS note_tags_search_vector ON note_tags",
+    "CREATE TRIGGER note_tags_search_vector AFTER INSERT OR DELETE ON note_tags FOR EACH ROW EXECUTE PROCEDURE note_tags_search_vector_trigger()",
+    "DROP TRIGGER IF EXISTS tag_search_vector ON tag",
+    "CREATE TRIGGER tag_search_vector AFTER UPDATE OF name ON tag FOR EACH ROW EXECUTE PROCEDURE tag_search_vector_trigger()",
+    // 回填已有笔记
+    "UPDATE note SET search_vector = note_search_vector(id, title, content)",
+    "CREATE INDEX IF NOT EXISTS idx_note_search_vector ON note USING GIN (search_vector)",
+];
+
+/// PostgreSQL: 回滚语句（按顺序执行）
+const PG_DOWN_STATEMENTS: [&str; 9] = [
+    "DROP TRIGGER IF EXISTS note_search_vector_update ON note",
+    "DROP TRIGGER IF EXISTS note_tags_search_vector ON note_tags",
+    "DROP TRIGGER IF EXISTS tag_search_vector ON tag",
+    "DROP FUNCTION IF EXISTS note_search_vector_trigger()",
+    "DROP FUNCTION IF EXISTS note_tags_search_vector_trigger()",
+    "DROP FUNCTION IF EXISTS tag_search_vector_trigger()",
+    "DROP INDEX IF EXISTS idx_note_search_vector",
+    "ALTER TABLE note DROP COLUMN IF EXISTS search_vector",
+    "DROP FUNCTION IF EXISTS note_search_vector(BIGINT, TEXT, TEXT)",
+];
+
+/// MySQL: 添加 FULLTEXT 索引的语句，`ngram` 为 false 时使用默认解析器
+fn mysql_fulltext_sql(table: &str, index: &str, columns: &str, ngram: bool) -> String {
+    format!(
+        "ALTER TABLE {} ADD FULLTEXT INDEX {} ({}){}",
+        table,
+        index,
+        columns,
+        if ngram { " WITH PARSER ngram" } else { "" }
+    )
+}
+
+/// 检查 MySQL 索引是否已存在
+pub(super) async fn mysql_has_index<C: sea_orm::ConnectionTrait>(
+    db: &C,
+    table: &str,
+    index: &str,
+) -> Result<bool, DbErr> {
+    let rows = db
+        .query_all_raw(sea_orm::Statement::from_string(
+            sea_orm::DatabaseBackend::MySql,
+            format!(
+                "SELECT 1 FROM information_schema.statistics WHERE table_schema = DATABASE() AND table_name = '{}' AND index_name = '{}' LIMIT 1",
+                table, index
+            ),
+        ))
+        .await?;
+    Ok(!rows.is_empty())
+}
+
+/// MySQL: 创建 FULLTEXT 索引，ngram 解析器不可用时退化为默认解析器
+pub(super) async fn mysql_create_fulltext<C: sea_orm::ConnectionTrait>(
+    db: &C,
+    table: &str,
+    index: &str,
+    columns: &str,
+) -> Result<(), DbErr> {
+    if mysql_has_index(db, table, index).await? {
+        return Ok(());
+    }
+
+    let sql = mysql_fulltext_sql(table, index, columns, true);
+    if let Err(e) = db.execute_unprepared(&sql).await {
+        tracing::warn!(
+            "创建 ngram 全文索引 {} 失败，改用默认解析器（中文检索效果受限）: {}",
+            index,
+            e
+        );
+        db.execute_unprepared(&mysql_fulltext_sql(table, index, columns, false))
+            .await?;
+    }
+    Ok(())
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        let backend = db.get_database_backend();
+
+        match backend {
+            sea_orm::DatabaseBackend::Postgres => {
+                for sql in PG_UP_STATEMENTS {
+                    db.execute_unprepared(sql).await?;
+                }
+            }
+            sea_orm::DatabaseBackend::MySql => {
+                mysql_create_fulltext(db, "note", "ft_note_title_content", "title, content")
+                    .await?;
+                mysql_create_fulltext(db, "tag", "ft_tag_name", "name").await?;
+            }
+            _ => {
+                tracing::info!("跳过原生全文索引创建：SQLite 使用 note_fts");
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        let backend = db.get_database_backend();
+
+        match backend {
+            sea_orm::DatabaseBackend::Postgres => {
+                for sql in PG_DOWN_STATEMENTS {
+                    db.execute_unprepared(sql).await?;
+                }
+            }
+            sea_orm::DatabaseBackend::MySql => {
+                db.execute_unprepared("ALTER TABLE note DROP INDEX ft_note_title_content")
+                    .await
+                    .ok();
+                db.execute_unprepared("ALTER TABLE tag DROP INDEX ft_tag_name")
+                    .await
+                    .ok();
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 语句中 `marker` 之后的标识符（到空白或括号为止）
+    fn names_after<'a>(statements: &[&'a str], marker: &str) -> Vec<&'a str> {
+        statements
+            .iter()
+            .flat_map(|sql| {
+                sql.match_indices(marker)
+                    .map(move |(i, _)| &sql[i + marker.len()..])
+            })
+            .map(|rest| {
+                rest.split(|c: char| c.is_whitespace() || c == '(')
+                    .next()
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn pg_triggers_use_created_functions() {
+        let functions = names_after(&PG_UP_STATEMENTS, "CREATE OR REPLACE FUNCTION ");
+        for procedure in names_after(&PG_UP_STATEMENTS, "EXECUTE PROCEDURE ") {
+            assert!(
+                functions.contains(&procedure),
+                "{} is not created",
+                procedure
+            );
+        }
+        // 触发器先删除再创建，保证迁移可重复执行
+        for trigger in names_after(&PG_UP_STATEMENTS, "CREATE TRIGGER ") {
+            let drop = PG_UP_STATEMENTS
+                .iter()
+                .position(|s| s.starts_with(&format!("DROP TRIGGER IF EXISTS {} ", trigger)));
+            let create = PG_UP_STATEMENTS
+                .iter()
+                .position(|s| s.starts_with(&format!("CREATE TRIGGER {} ", trigger)));
+            assert!(
+                drop.is_some() && drop < create,
+                "{} is not dropped first",
+                trigger
+            );
+        }
+    }
+
+    #[test]
+    fn pg_down_drops_everything_created() {
+        let dropped_triggers = names_after(&PG_DOWN_STATEMENTS, "DROP TRIGGER IF EXISTS ");
+        for trigger in names_after(&PG_UP_STATEMENTS, "CREATE TRIGGER ") {
+            assert!(
+                dropped_triggers.contains(&trigger),
+                "{} is not dropped",
+                trigger
+            );
+        }
+        let dropped_functions = names_after(&PG_DOWN_STATEMENTS, "DROP FUNCTION IF EXISTS ");
+        for function in names_after(&PG_UP_STATEMENTS, "CREATE OR REPLACE FUNCTION ") {
+            assert!(
+                dropped_functions.contains(&function),
+                "{} is not dropped",
+                function
+            );
+        }
+        // 依赖 search_vector 的索引与触发器须在删除列之前删除
+        let drop_column = PG_DOWN_STATEMENTS
+            .iter()
+            .position(|s| s.contains("DROP COLUMN"))
+            .unwrap();
+        let drop_index = PG_DOWN_STATEMENTS
+            .iter()
+            .position(|s| s.starts_with("DROP INDEX"))
+            .unwrap();
+        assert!(drop_index < drop_column);
+    }
+
+    #[test]
+    fn mysql_fulltext_falls_back_to_default_parser() {
+        assert_eq!(
+            mysql_fulltext_sql("note", "ft_note_title_content", "title, content", true),
+            "ALTER TABLE note ADD FULLTEXT INDEX ft_note_title_content (title, content) WITH PARSER ngram"
+        );
+        assert_eq!(
+            mysql_fulltext_sql("tag", "ft_tag_name", "name", false),
+            "ALTER TABLE tag ADD FULLTEXT INDEX ft_tag_name (name)"
+        );
+    }
+}
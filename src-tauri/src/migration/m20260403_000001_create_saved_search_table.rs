@@ -0,0 +1,49 @@
+//! 创建 saved_search 表迁移
+//!
+//! 保存的搜索（智能笔记本）：以 JSON 存储一组命名的搜索条件，
+//! 可在侧边栏与笔记本、标签并列展示并实时统计命中数量。
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(SavedSearch::Table)
+                    .if_not_exists()
+                    .col(big_pk_auto(SavedSearch::Id))
+                    .col(string(SavedSearch::Name).not_null())
+                    .col(string(SavedSearch::Icon).not_null().default(""))
+                    .col(text(SavedSearch::Filter).not_null())
+                    .col(integer(SavedSearch::SortOrder).not_null().default(0))
+                    .col(date_time(SavedSearch::CreateTime).not_null())
+                    .col(date_time(SavedSearch::UpdateTime).not_null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SavedSearch::Table).to_owned())
+            .await
+    }
+}
+
+/// 保存的搜索表定义
+#[derive(DeriveIden)]
+enum SavedSearch {
+    Table,
+    Id,
+    Name,
+    Icon,
+    Filter,
+    SortOrder,
+    CreateTime,
+    UpdateTime,
+}
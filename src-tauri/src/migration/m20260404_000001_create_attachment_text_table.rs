@@ -0,0 +1,142 @@
+//! 创建 note_attachment_text 表迁移
+//!
+//! 保存从附件中提取出的纯文本（每个附件一行），用于关键词搜索命中附件内容。
+//!
+//! ## 全文索引
+//! - SQLite: FTS5 trigram 外部内容表 `attachment_fts`，由触发器同步
+//! - PostgreSQL: `to_tsvector('simple', content)` 表达式 GIN 索引
+//! - MySQL: content 列 FULLTEXT 索引（ngram 解析器，不可用时退化为默认解析器）
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+use super::m20260402_000001_add_note_fulltext_index::mysql_create_fulltext;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(NoteAttachmentText::Table)
+                    .if_not_exists()
+                    .col(big_pk_auto(NoteAttachmentText::Id))
+                    .col(big_integer(NoteAttachmentText::AttachmentId).not_null())
+                    .col(big_integer(NoteAttachmentText::NoteId).not_null())
+                    .col(text(NoteAttachmentText::Content).not_null())
+                    .col(date_time(NoteAttachmentText::CreateTime).not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_note_attachment_text_attachment_id")
+                    .table(NoteAttachmentText::Table)
+                    .col(NoteAttachmentText::AttachmentId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_note_attachment_text_note_id")
+                    .table(NoteAttachmentText::Table)
+                    .col(NoteAttachmentText::NoteId)
+                    .to_owned(),
+            )
+            .await?;
+
+        let db = manager.get_connection();
+        match db.get_database_backend() {
+            sea_orm::DatabaseBackend::Sqlite => {
+                db.execute_unprepared(
+                    r#"
+                    CREATE VIRTUAL TABLE IF NOT EXISTS attachment_fts USING fts5(
+                        content,
+                        content='note_attachment_text',
+                        content_rowid='id',
+                        tokenize='trigram'
+                    );
+                    "#,
+                )
+                .await?;
+
+                db.execute_unprepared(
+                    r#"
+                    CREATE TRIGGER IF NOT EXISTS attachment_fts_insert AFTER INSERT ON note_attachment_text BEGIN
+                        INSERT INTO attachment_fts(rowid, content) VALUES (new.id, new.content);
+                    END;
+                    "#,
+                )
+                .await?;
+
+                db.execute_unprepared(
+                    r#"
+                    CREATE TRIGGER IF NOT EXISTS attachment_fts_delete AFTER DELETE ON note_attachment_text BEGIN
+                        INSERT INTO attachment_fts(attachment_fts, rowid, content) VALUES ('delete', old.id, old.content);
+                    END;
+                    "#,
+                )
+                .await?;
+
+                db.execute_unprepared(
+                    r#"
+                    CREATE TRIGGER IF NOT EXISTS attachment_fts_update AFTER UPDATE ON note_attachment_text BEGIN
+                        INSERT INTO attachment_fts(attachment_fts, rowid, content) VALUES ('delete', old.id, old.content);
+                        INSERT INTO attachment_fts(rowid, content) VALUES (new.id, new.content);
+                    END;
+                    "#,
+                )
+                .await?;
+            }
+            sea_orm::DatabaseBackend::Postgres => {
+                db.execute_unprepared(
+                    "CREATE INDEX IF NOT EXISTS idx_note_attachment_text_fts ON note_attachment_text USING GIN (to_tsvector('simple', content))",
+                )
+                .await?;
+            }
+            sea_orm::DatabaseBackend::MySql => {
+                mysql_create_fulltext(db, "note_attachment_text", "ft_attachment_text", "content")
+                    .await?;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        if db.get_database_backend() == sea_orm::DatabaseBackend::Sqlite {
+            for sql in [
+                "DROP TRIGGER IF EXISTS attachment_fts_insert",
+                "DROP TRIGGER IF EXISTS attachment_fts_delete",
+                "DROP TRIGGER IF EXISTS attachment_fts_update",
+                "DROP TABLE IF EXISTS attachment_fts",
+            ] {
+                db.execute_unprepared(sql).await?;
+            }
+        }
+
+        manager
+            .drop_table(Table::drop().table(NoteAttachmentText::Table).to_owned())
+            .await
+    }
+}
+
+/// 附件文本表定义
+#[derive(DeriveIden)]
+enum NoteAttachmentText {
+    Table,
+    Id,
+    AttachmentId,
+    NoteId,
+    Content,
+    CreateTime,
+}
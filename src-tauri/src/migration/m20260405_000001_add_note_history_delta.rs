@@ -0,0 +1,329 @@
+//! 为 note_history 表添加增量存储列，并将已有记录转换为增量格式
+//!
+//! - `content_format`：内容存储格式（位标志），0 = 完整文本，
+//!   1 = `old_content` 为相对上一条记录的增量，2 = `new_content` 为相对 `old_content` 的增量
+//! - `base_id`：`old_content` 为增量时，基准记录（同一笔记的上一条历史记录）的 ID
+//!
+//! 迁移使用本文件内固定的编解码实现（与引入时的 `service::history_storage` 格式一致），
+//! 不随服务层代码变化。逐条改写，中断后重新执行时会先解码已转换的记录，可从中断处继续。
+
+use std::time::{Duration, Instant};
+
+use sea_orm_migration::prelude::*;
+use serde::{Deserialize, Serialize};
+use similar::{Algorithm, DiffTag, capture_diff_slices_deadline};
+
+/// `old_content` 为相对上一条记录 `new_content` 的增量
+const OLD_DELTA: i32 = 1;
+/// `new_content` 为相对本条记录 `old_content` 的增量
+const NEW_DELTA: i32 = 2;
+
+/// 增量链的最大长度（每隔多少条记录保存一次完整快照）
+const SNAPSHOT_INTERVAL: u64 = 16;
+
+/// 单次比较的时间上限
+const DIFF_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// 每批读取的记录数
+const BATCH_SIZE: u64 = 200;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite 不支持一条 ALTER TABLE 添加多列；中断后重新执行时列可能已存在
+        if !manager.has_column("note_history", "content_format").await? {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(NoteHistory::Table)
+                        .add_column(
+                            ColumnDef::new(NoteHistory::ContentFormat)
+                                .integer()
+                                .not_null()
+                                .default(0),
+                        )
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        if !manager.has_column("note_history", "base_id").await? {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(NoteHistory::Table)
+                        .add_column(
+                            ColumnDef::new(NoteHistory::BaseId)
+                                .big_integer()
+                                .not_null()
+                                .default(0),
+                        )
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        rewrite_all(manager.get_connection(), true).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 删除列前先还原为完整文本
+        rewrite_all(manager.get_connection(), false).await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(NoteHistory::Table)
+                    .drop_column(NoteHistory::BaseId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(NoteHistory::Table)
+                    .drop_column(NoteHistory::ContentFormat)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum NoteHistory {
+    Table,
+    Id,
+    NoteId,
+    OldContent,
+    NewContent,
+    ContentFormat,
+    BaseId,
+}
+
+/// 增量操作
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+enum DeltaOp {
+    /// 复制基准文本的字节区间 `[start, end)`
+    Copy(usize, usize),
+    /// 插入文本
+    Insert(String),
+}
+
+/// 按换行与 HTML 标签结束符切分文本（分隔符保留在片段末尾）
+fn tokenize(text: &str) -> Vec<&str> {
+    text.split_inclusive(['\n', '>']).collect()
+}
+
+/// 生成从 `base` 到 `target` 的增量
+fn make_delta(base: &str, target: &str) -> String {
+    let old = tokenize(base);
+    let new = tokenize(target);
+
+    let mut offsets = Vec::with_capacity(old.len() + 1);
+    let mut offset = 0;
+    offsets.push(offset);
+    for token in &old {
+        offset += token.len();
+        offsets.push(offset);
+    }
+
+    let deadline = Instant::now() + DIFF_TIMEOUT;
+    let mut ops: Vec<DeltaOp> = Vec::new();
+    for op in capture_diff_slices_deadline(Algorithm::Myers, &old, &new, Some(deadline)) {
+        let (tag, old_range, new_range) = op.as_tag_tuple();
+        match tag {
+            DiffTag::Equal => {
+                let (start, end) = (offsets[old_range.start], offsets[old_range.end]);
+                match ops.last_mut() {
+                    Some(DeltaOp::Copy(_, last_end)) if *last_end == start => *last_end = end,
+                    _ => ops.push(DeltaOp::Copy(start, end)),
+                }
+            }
+            DiffTag::Delete => {}
+            DiffTag::Insert | DiffTag::Replace => {
+                let text = new[new_range].concat();
+                match ops.last_mut() {
+                    Some(DeltaOp::Insert(last)) => last.push_str(&text),
+                    _ => ops.push(DeltaOp::Insert(text)),
+                }
+            }
+        }
+    }
+
+    serde_json::to_string(&ops).unwrap_or_default()
+}
+
+/// 将增量应用到基准文本
+fn apply_delta(base: &str, delta: &str) -> Result<String, DbErr> {
+    let ops: Vec<DeltaOp> =
+        serde_json::from_str(delta).map_err(|e| DbErr::Migration(e.to_string()))?;
+    let mut text = String::with_capacity(base.len());
+    for op in ops {
+        match op {
+            DeltaOp::Copy(start, end) => text.push_str(base.get(start..end).ok_or_else(|| {
+                DbErr::Migration(format!("Invalid history delta range: {}..{}", start, end))
+            })?),
+            DeltaOp::Insert(insert) => text.push_str(&insert),
+        }
+    }
+    Ok(text)
+}
+
+/// 增量比原文短时返回增量
+fn encode_field(base: &str, target: &str) -> Option<String> {
+    if target.is_empty() {
+        return None;
+    }
+    let delta = make_delta(base, target);
+    (delta.len() < target.len()).then_some(delta)
+}
+
+/// 上一条记录：(ID, 完整的 new_content, 所在增量链的长度)
+type Predecessor = (i64, String, u64);
+
+/// 重新编码全部历史记录，`compress` 为 false 时还原为完整文本
+async fn rewrite_all<C: ConnectionTrait>(db: &C, compress: bool) -> Result<(), DbErr> {
+    let note_ids = db
+        .query_all(
+            &Query::select()
+                .distinct()
+                .column(NoteHistory::NoteId)
+                .from(NoteHistory::Table)
+                .to_owned(),
+        )
+        .await?
+        .into_iter()
+        .map(|row| row.try_get::<i64>("", "note_id"))
+        .collect::<Result<Vec<i64>, DbErr>>()?;
+
+    for note_id in note_ids {
+        rewrite_note(db, note_id, compress).await?;
+    }
+    Ok(())
+}
+
+/// 按 ID 顺序重新编码一篇笔记的历史记录，内存中只保留上一条记录
+async fn rewrite_note<C: ConnectionTrait>(
+    db: &C,
+    note_id: i64,
+    compress: bool,
+) -> Result<(), DbErr> {
+    let mut prev: Option<Predecessor> = None;
+    let mut last_id = i64::MIN;
+
+    loop {
+        let rows = db
+            .query_all(
+                &Query::select()
+                    .columns([
+                        NoteHistory::Id,
+                        NoteHistory::OldContent,
+                        NoteHistory::NewContent,
+                        NoteHistory::ContentFormat,
+                        NoteHistory::BaseId,
+                    ])
+                    .from(NoteHistory::Table)
+                    .and_where(Expr::col(NoteHistory::NoteId).eq(note_id))
+                    .and_where(Expr::col(NoteHistory::Id).gt(last_id))
+                    .order_by(NoteHistory::Id, Order::Asc)
+                    .limit(BATCH_SIZE)
+                    .to_owned(),
+            )
+            .await?;
+        if rows.is_empty() {
+            break;
+        }
+
+        for row in rows {
+            let id: i64 = row.try_get("", "id")?;
+            let stored_old: String = row.try_get("", "old_content")?;
+            let stored_new: String = row.try_get("", "new_content")?;
+            let format: i32 = row.try_get("", "content_format")?;
+            let base_id: i64 = row.try_get("", "base_id")?;
+            last_id = id;
+
+            // 解码：已转换的记录以上一条记录为基准
+            let old_content = if format & OLD_DELTA != 0 {
+                match &prev {
+                    Some((prev_id, base, _)) if *prev_id == base_id => {
+                        apply_delta(base, &stored_old)?
+                    }
+                    _ => {
+                        return Err(DbErr::Migration(format!(
+                            "Missing base revision: {}",
+                            base_id
+                        )));
+                    }
+                }
+            } else {
+                stored_old.clone()
+            };
+            let new_content = if format & NEW_DELTA != 0 {
+                apply_delta(&old_content, &stored_new)?
+            } else {
+                stored_new.clone()
+            };
+
+            // 编码
+            let mut encoded = (old_content.clone(), new_content.clone(), 0, 0);
+            let mut depth = 0;
+            if compress {
+                if let Some(delta) = encode_field(&old_content, &new_content) {
+                    encoded.1 = delta;
+                    encoded.2 |= NEW_DELTA;
+                }
+                if let Some((prev_id, base, prev_depth)) = &prev
+                    && prev_depth + 1 < SNAPSHOT_INTERVAL
+                    && let Some(delta) = encode_field(base, &old_content)
+                {
+                    encoded.0 = delta;
+                    encoded.2 |= OLD_DELTA;
+                    encoded.3 = *prev_id;
+                    depth = prev_depth + 1;
+                }
+            }
+
+            if encoded != (stored_old, stored_new, format, base_id) {
+                db.execute(
+                    &Query::update()
+                        .table(NoteHistory::Table)
+                        .values([
+                            (NoteHistory::OldContent, encoded.0.into()),
+                            (NoteHistory::NewContent, encoded.1.into()),
+                            (NoteHistory::ContentFormat, encoded.2.into()),
+                            (NoteHistory::BaseId, encoded.3.into()),
+                        ])
+                        .and_where(Expr::col(NoteHistory::Id).eq(id))
+                        .to_owned(),
+                )
+                .await?;
+            }
+
+            prev = Some((id, new_content, depth));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frozen_delta_round_trip() {
+        let base = "<p>第一段</p><p>第二段</p>\n# 标题\n正文\n";
+        let target = "<p>第一段</p><p>修改后的第二段</p>\n# 标题\n正文\n新增一行\n";
+        assert_eq!(
+            apply_delta(base, &make_delta(base, target)).unwrap(),
+            target
+        );
+        assert_eq!(apply_delta("", &make_delta("", target)).unwrap(), target);
+        assert_eq!(encode_field(base, ""), None);
+        assert!(apply_delta("short", "[[0,100]]").is_err());
+    }
+}
@@ -0,0 +1,44 @@
+//! 为 note 表添加 version 列
+//!
+//! 桌面端与 MCP 进程可能同时编辑同一篇笔记，更新时比较版本号实现乐观并发控制
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Note::Table)
+                    .add_column(
+                        ColumnDef::new(Note::Version)
+                            .big_integer()
+                            .not_null()
+                            .default(1),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Note::Table)
+                    .drop_column(Note::Version)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Note {
+    Table,
+    Version,
+}
@@ -0,0 +1,98 @@
+//! 为 note_link 表添加 origin 列
+//!
+//! 区分手动创建的链接与从笔记内容（`[[标题]]`、`[[id:123]]`、`enote://note/123`）解析出的链接，
+//! 唯一约束改为包含 origin，同一对笔记可同时存在两种链接
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(NoteLink::Table)
+                    .add_column(
+                        ColumnDef::new(NoteLink::Origin)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_note_link_unique")
+                    .table(NoteLink::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_note_link_unique")
+                    .table(NoteLink::Table)
+                    .col(NoteLink::SourceNoteId)
+                    .col(NoteLink::TargetNoteId)
+                    .col(NoteLink::Origin)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 内容链接可由笔记内容重新生成，回滚时直接删除
+        manager
+            .get_connection()
+            .execute_unprepared("DELETE FROM note_link WHERE origin <> 0")
+            .await?;
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_note_link_unique")
+                    .table(NoteLink::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_note_link_unique")
+                    .table(NoteLink::Table)
+                    .col(NoteLink::SourceNoteId)
+                    .col(NoteLink::TargetNoteId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(NoteLink::Table)
+                    .drop_column(NoteLink::Origin)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum NoteLink {
+    Table,
+    SourceNoteId,
+    TargetNoteId,
+    Origin,
+}
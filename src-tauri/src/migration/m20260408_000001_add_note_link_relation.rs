@@ -0,0 +1,108 @@
+//! 为 note_link 表添加 relation 列
+//!
+//! 链接保留方向（source 引用 target），并可标注关系类型（如 references、depends-on、
+//! supersedes 或自定义标签），空字符串表示未指定。唯一约束改为包含 relation，
+//! 同一对笔记之间可存在多种关系
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(NoteLink::Table)
+                    .add_column(
+                        ColumnDef::new(NoteLink::Relation)
+                            .string_len(64)
+                            .not_null()
+                            .default(""),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_note_link_unique")
+                    .table(NoteLink::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_note_link_unique")
+                    .table(NoteLink::Table)
+                    .col(NoteLink::SourceNoteId)
+                    .col(NoteLink::TargetNoteId)
+                    .col(NoteLink::Origin)
+                    .col(NoteLink::Relation)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 同一对笔记同一来源只保留最早的一条链接，避免恢复旧唯一约束时冲突
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "DELETE FROM note_link WHERE EXISTS (\
+                 SELECT 1 FROM (SELECT id, source_note_id, target_note_id, origin FROM note_link) o \
+                 WHERE o.source_note_id = note_link.source_note_id \
+                 AND o.target_note_id = note_link.target_note_id \
+                 AND o.origin = note_link.origin AND o.id < note_link.id)",
+            )
+            .await?;
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_note_link_unique")
+                    .table(NoteLink::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_note_link_unique")
+                    .table(NoteLink::Table)
+                    .col(NoteLink::SourceNoteId)
+                    .col(NoteLink::TargetNoteId)
+                    .col(NoteLink::Origin)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(NoteLink::Table)
+                    .drop_column(NoteLink::Relation)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum NoteLink {
+    Table,
+    SourceNoteId,
+    TargetNoteId,
+    Origin,
+    Relation,
+}
@@ -0,0 +1,58 @@
+//! 创建 note_rule 表迁移
+//!
+//! 笔记自动规则：按标题、内容、来源、笔记本与标签匹配笔记，
+//! 在创建/更新笔记时或手动执行时自动移动、打标签、收藏、置顶或设置 MCP 权限。
+//! 条件与动作以 JSON 存储。
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(NoteRule::Table)
+                    .if_not_exists()
+                    .col(big_pk_auto(NoteRule::Id))
+                    .col(string(NoteRule::Name).not_null())
+                    .col(integer(NoteRule::Enabled).not_null().default(1))
+                    .col(integer(NoteRule::RunOnCreate).not_null().default(1))
+                    .col(integer(NoteRule::RunOnUpdate).not_null().default(0))
+                    .col(integer(NoteRule::MatchAny).not_null().default(0))
+                    .col(text(NoteRule::Conditions).not_null())
+                    .col(text(NoteRule::Actions).not_null())
+                    .col(integer(NoteRule::SortOrder).not_null().default(0))
+                    .col(date_time(NoteRule::CreateTime).not_null())
+                    .col(date_time(NoteRule::UpdateTime).not_null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(NoteRule::Table).to_owned())
+            .await
+    }
+}
+
+/// 笔记自动规则表定义
+#[derive(DeriveIden)]
+enum NoteRule {
+    Table,
+    Id,
+    Name,
+    Enabled,
+    RunOnCreate,
+    RunOnUpdate,
+    MatchAny,
+    Conditions,
+    Actions,
+    SortOrder,
+    CreateTime,
+    UpdateTime,
+}
@@ -40,6 +40,15 @@ mod m20260330_000001_create_note_attachment_table;
 mod m20260330_000002_add_note_starred;
 mod m20260331_000001_fix_note_fts_triggers;
 mod m20260401_000001_add_attachment_hash;
+mod m20260402_000001_add_note_fulltext_index;
+mod m20260403_000001_create_saved_search_table;
+mod m20260404_000001_create_attachment_text_table;
+mod m20260405_000001_add_note_history_delta;
+mod m20260406_000001_add_note_version;
+mod m20260407_000001_add_note_link_origin;
+mod m20260408_000001_add_note_link_relation;
+mod m20260409_000001_add_tag_parent;
+mod m20260410_000001_create_note_rule_table;
 
 /// 数据库迁移器
 ///
@@ -77,6 +86,15 @@ impl MigratorTrait for Migrator {
             Box::new(m20260330_000002_add_note_starred::Migration),
             Box::new(m20260331_000001_fix_note_fts_triggers::Migration),
             Box::new(m20260401_000001_add_attachment_hash::Migration),
+            Box::new(m20260402_000001_add_note_fulltext_index::Migration),
+            Box::new(m20260403_000001_create_saved_search_table::Migration),
+            Box::new(m20260404_000001_create_attachment_text_table::Migration),
+            Box::new(m20260405_000001_add_note_history_delta::Migration),
+            Box::new(m20260406_000001_add_note_version::Migration),
+            Box::new(m20260407_000001_add_note_link_origin::Migration),
+            Box::new(m20260408_000001_add_note_link_relation::Migration),
+            Box::new(m20260409_000001_add_tag_parent::Migration),
+            Box::new(m20260410_000001_create_note_rule_table::Migration),
         ]
     }
 }
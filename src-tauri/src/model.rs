@@ -11,7 +11,7 @@
 
 use std::collections::HashMap;
 
-use chrono::NaiveDateTime;
+use chrono::{NaiveDate, NaiveDateTime};
 use sea_orm::ActiveValue::Set;
 use serde::{Deserialize, Serialize};
 use serde_with::{DefaultOnNull, serde_as};
@@ -26,7 +26,7 @@ use crate::i18n::{t, t_simple};
 
 /// 笔记历史操作类型
 ///
-/// 用于记录笔记的创建、更新、删除等操作
+/// 用于记录笔记的创建、更新、删除、恢复等操作
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[repr(i32)]
 pub enum OperationType {
@@ -36,6 +36,8 @@ pub enum OperationType {
     Update = 2,
     /// 删除操作
     Delete = 3,
+    /// 恢复历史版本
+    Restore = 4,
 }
 
 impl OperationType {
@@ -78,6 +80,33 @@ impl From<OperateSource> for i32 {
     }
 }
 
+impl From<i32> for OperateSource {
+    fn from(value: i32) -> Self {
+        match value {
+            1 => OperateSource::Mcp,
+            2 => OperateSource::Sync,
+            _ => OperateSource::User,
+        }
+    }
+}
+
+/// 笔记链接来源
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(i32)]
+pub enum LinkOrigin {
+    /// 手动创建
+    Manual = 0,
+    /// 从 source 笔记的内容中解析（`[[标题]]`、`[[id:123]]` 等）
+    Content = 1,
+}
+
+impl LinkOrigin {
+    /// 转换为 i32 值（用于数据库存储）
+    pub fn as_i32(self) -> i32 {
+        self as i32
+    }
+}
+
 // ============================================================================
 // MCP 访问控制枚举
 // ============================================================================
@@ -279,6 +308,31 @@ impl<T> Default for PageResult<T> {
     }
 }
 
+/// 游标分页结果
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CursorPageResult<T> {
+    /// 当前页数据列表
+    #[serde(default = "Vec::new")]
+    pub data: Vec<T>,
+    /// 下一页游标（没有更多数据时为空）
+    #[serde(default)]
+    pub next_cursor: Option<String>,
+    /// 总记录数（仅请求统计时返回）
+    #[serde(default)]
+    pub total: Option<i64>,
+}
+
+impl<T> Default for CursorPageResult<T> {
+    fn default() -> Self {
+        Self {
+            data: Vec::new(),
+            next_cursor: None,
+            total: None,
+        }
+    }
+}
+
 // ============================================================================
 // 业务对象
 // ============================================================================
@@ -386,6 +440,17 @@ impl From<entity::notebook::Model> for Notebook {
     }
 }
 
+/// 笔记本树节点
+///
+/// 笔记本字段展开到同一层级，`children` 为按排序值排列的子笔记本
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NotebookTreeNode {
+    #[serde(flatten)]
+    pub notebook: Notebook,
+    pub children: Vec<NotebookTreeNode>,
+}
+
 /// 笔记数据传输对象
 ///
 /// 用于前后端交互的笔记数据结构，包含关联的标签信息
@@ -437,9 +502,20 @@ pub struct Note {
         deserialize_with = "deserialize_option_dt"
     )]
     pub deleted_at: Option<NaiveDateTime>,
+    /// 版本号（更新时传入读取到的版本，与当前版本不一致则拒绝；0 表示不检查）
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub version: i64,
     /// 关联的标签列表
     #[serde_as(deserialize_as = "DefaultOnNull")]
     pub tags: Vec<Tag>,
+    /// 搜索命中片段（仅关键词搜索结果填充）
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub snippets: Vec<SearchSnippet>,
+    /// 关键词是否命中附件文本（仅关键词搜索结果填充，对应片段的 field 为 `attachment`）
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub attachment_match: bool,
+    /// 相关度得分（仅按 relevance 排序时填充，越大越相关）
+    pub score: Option<f64>,
 }
 
 impl Note {
@@ -476,6 +552,7 @@ impl From<entity::note::Model> for Note {
             create_time: Some(value.create_time),
             update_time: Some(value.update_time),
             deleted_at: value.deleted_at,
+            version: value.version,
             ..Default::default()
         }
     }
@@ -496,6 +573,7 @@ impl From<&entity::note::Model> for Note {
             create_time: Some(value.create_time),
             update_time: Some(value.update_time),
             deleted_at: value.deleted_at,
+            version: value.version,
             ..Default::default()
         }
     }
@@ -516,6 +594,7 @@ impl From<Note> for entity::note::ActiveModel {
             create_time: Set(note.create_time.unwrap_or_default()),
             update_time: Set(note.update_time.unwrap_or_default()),
             deleted_at: Set(note.deleted_at),
+            version: Set(note.version),
         }
     }
 }
@@ -535,13 +614,14 @@ impl From<&Note> for entity::note::ActiveModel {
             create_time: Set(note.create_time.unwrap_or_default()),
             update_time: Set(note.update_time.unwrap_or_default()),
             deleted_at: Set(note.deleted_at),
+            version: Set(note.version),
         }
     }
 }
 
 /// 笔记统计结果
 ///
-/// 笔记本的笔记数量统计
+/// 笔记本的笔记数量统计，以及各保存的搜索当前命中的笔记数量
 #[serde_as]
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase", default)]
@@ -549,6 +629,34 @@ pub struct NoteStatsResult {
     pub total: i64,
     #[serde_as(deserialize_as = "DefaultOnNull")]
     pub notebook_counts: HashMap<i64, i64>,
+    /// 保存的搜索 ID → 命中数量（按各自保存的条件统计，与本次搜索条件无关）
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub saved_search_counts: HashMap<i64, i64>,
+}
+
+/// 批量操作中单条笔记的处理结果
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BatchNoteStatus {
+    /// 已修改
+    #[default]
+    Changed,
+    /// 已是目标状态，无需修改
+    Unchanged,
+    /// 笔记不存在
+    NotFound,
+    /// 笔记当前状态不支持该操作（如收藏回收站中的笔记、永久删除未在回收站中的笔记）
+    Skipped,
+    /// 无权修改（AI 对该笔记没有写入权限）
+    Denied,
+}
+
+/// 批量操作的单条笔记结果
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BatchNoteResult {
+    pub note_id: i64,
+    pub status: BatchNoteStatus,
 }
 
 /// 标签数据传输对象
@@ -559,7 +667,10 @@ pub struct Tag {
     /// 标签 ID
     #[serde_as(deserialize_as = "DefaultOnNull")]
     pub id: i64,
-    /// 标签名称
+    /// 父标签 ID（用于层级结构，0 表示顶级）
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub parent_id: i64,
+    /// 标签名称（创建时可传入 `a/b/c` 形式的路径，缺失的祖先标签自动创建）
     #[serde_as(deserialize_as = "DefaultOnNull")]
     pub name: String,
     /// 图标（Lucide 图标名称）
@@ -614,6 +725,7 @@ impl From<entity::tag::Model> for Tag {
     fn from(value: entity::tag::Model) -> Self {
         Self {
             id: value.id,
+            parent_id: value.parent_id,
             name: value.name,
             icon: value.icon,
             cls: value.cls,
@@ -629,6 +741,7 @@ impl From<&entity::tag::Model> for Tag {
     fn from(value: &entity::tag::Model) -> Self {
         Self {
             id: value.id,
+            parent_id: value.parent_id,
             name: value.name.clone(),
             icon: value.icon.clone(),
             cls: value.cls.clone(),
@@ -640,6 +753,17 @@ impl From<&entity::tag::Model> for Tag {
     }
 }
 
+/// 标签树节点
+///
+/// 标签字段展开到同一层级，`children` 为按排序值排列的子标签
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TagTreeNode {
+    #[serde(flatten)]
+    pub tag: Tag,
+    pub children: Vec<TagTreeNode>,
+}
+
 // ============================================================================
 // 搜索参数
 // ============================================================================
@@ -658,10 +782,12 @@ pub struct NoteSearchPageParam {
     /// 筛选标签 ID（0 表示不筛选）
     #[serde_as(deserialize_as = "DefaultOnNull")]
     pub tag_id: i64,
-    /// 搜索关键词（搜索标题和内容）
+    /// 搜索关键词（搜索标题、内容和标签，支持 `tag:`/`notebook:`/`is:`/`before:`/`after:` 等结构化语法）
     #[serde_as(deserialize_as = "DefaultOnNull")]
     pub keyword: String,
-    /// 排序字段：title, create_time, update_time（默认 update_time）
+    /// 排序字段：title, create_time, update_time, relevance（默认 update_time）
+    ///
+    /// relevance 仅在关键词包含全文检索词时生效，否则回退为 update_time
     #[serde_as(deserialize_as = "DefaultOnNull")]
     pub sort_field: String,
     /// 排序方向：asc, desc（默认 desc）
@@ -670,11 +796,31 @@ pub struct NoteSearchPageParam {
     /// 仅显示收藏/星标笔记
     #[serde_as(deserialize_as = "DefaultOnNull")]
     pub is_starred: bool,
+    /// 筛选多个笔记本（命中任一即可），与 `notebook_id` 同时生效
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub notebook_ids: Vec<i64>,
+    /// 笔记本筛选（`notebook_id` 与 `notebook_ids`）是否包含子孙笔记本
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub include_descendants: bool,
+    /// 必须同时包含的标签 ID（all）
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub all_tag_ids: Vec<i64>,
+    /// 至少包含其一的标签 ID（any）
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub any_tag_ids: Vec<i64>,
+    /// 不能包含的标签 ID（none）
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub exclude_tag_ids: Vec<i64>,
+    /// 标签筛选（`tag_id` 与 all / any / none）是否包含子孙标签
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub include_tag_descendants: bool,
 }
 
 impl NoteSearchPageParam {
     /// 最大关键词长度
     const MAX_KEYWORD_LENGTH: usize = 500;
+    /// 每个 ID 过滤列表的最大长度
+    const MAX_FILTER_IDS: usize = 100;
 
     /// 验证搜索参数
     pub fn validate(&self) -> anyhow::Result<()> {
@@ -690,6 +836,23 @@ impl NoteSearchPageParam {
             )
         }
 
+        for ids in [
+            &self.notebook_ids,
+            &self.all_tag_ids,
+            &self.any_tag_ids,
+            &self.exclude_tag_ids,
+        ] {
+            if ids.len() > Self::MAX_FILTER_IDS {
+                anyhow::bail!(
+                    "{}",
+                    t(
+                        "validation.tooManyFilterIds",
+                        &[&Self::MAX_FILTER_IDS.to_string()]
+                    )
+                )
+            }
+        }
+
         Ok(())
     }
 
@@ -708,12 +871,109 @@ impl NoteSearchPageParam {
 
         // 去除关键词首尾空格
         self.keyword = self.keyword.trim().to_string();
+
+        // ID 列表去重并剔除非法值
+        for ids in [
+            &mut self.notebook_ids,
+            &mut self.all_tag_ids,
+            &mut self.any_tag_ids,
+            &mut self.exclude_tag_ids,
+        ] {
+            ids.retain(|id| *id > 0);
+            ids.sort_unstable();
+            ids.dedup();
+        }
+    }
+}
+
+/// 笔记游标分页搜索参数
+///
+/// 用于无限滚动等只需“下一页”的场景：按当前排序键（置顶、排序列、ID）定位，
+/// 避免深分页的 OFFSET 扫描；总数统计可选。`page_index` 被忽略，`page_size` 为每页数量
+#[serde_as]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NoteCursorSearchParam {
+    /// 搜索条件（展开到同一层级）
+    #[serde(flatten)]
+    pub search_param: NoteSearchPageParam,
+    /// 上一页返回的 `nextCursor`，为空表示第一页
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub cursor: String,
+    /// 是否统计总数（默认 false，大表上 COUNT 开销较大）
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub with_total: bool,
+}
+
+impl NoteCursorSearchParam {
+    /// 游标的最大长度
+    const MAX_CURSOR_LENGTH: usize = 2048;
+
+    /// 验证搜索参数
+    pub fn validate(&self) -> anyhow::Result<()> {
+        self.search_param.validate()?;
+        if self.cursor.len() > Self::MAX_CURSOR_LENGTH {
+            anyhow::bail!("{}", t_simple("validation.invalidCursor"))
+        }
+        Ok(())
+    }
+
+    /// 规范化搜索参数
+    pub fn normalize(&mut self) {
+        self.search_param.normalize();
+        self.cursor = self.cursor.trim().to_string();
+    }
+}
+
+/// 高亮区间（基于片段文本的字符偏移，左闭右开）
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HighlightRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// 搜索命中片段
+///
+/// 说明笔记在哪个字段命中了关键词，以及命中位置
+#[serde_as]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SearchSnippet {
+    /// 命中字段：title / content / tags / attachment
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub field: String,
+    /// 片段纯文本（内容字段会截取命中位置附近的上下文）
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub text: String,
+    /// 命中区间列表
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub ranges: Vec<HighlightRange>,
+}
+
+impl SearchSnippet {
+    /// 使用给定标记包裹命中区间，生成便于纯文本展示的字符串
+    pub fn marked(&self, open: &str, close: &str) -> String {
+        let mut result = String::with_capacity(self.text.len());
+        let mut ranges = self.ranges.iter().peekable();
+        for (i, ch) in self.text.chars().enumerate() {
+            if ranges.peek().is_some_and(|r| r.start == i) {
+                result.push_str(open);
+            }
+            result.push(ch);
+            if ranges.peek().is_some_and(|r| r.end == i + 1) {
+                result.push_str(close);
+                ranges.next();
+            }
+        }
+        result
     }
 }
 
 /// 笔记历史记录额外信息
 ///
-/// 存储在历史记录中的快照信息，用于恢复或查看历史状态
+/// 存储在历史记录中的快照信息（操作之前的状态），用于恢复或查看历史状态。
+/// 早期版本写入的更新记录中笔记本为修改后的值，其余字段为修改前的值
 #[serde_as]
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase", default)]
@@ -757,7 +1017,7 @@ pub struct NoteHistory {
     /// 额外信息（JSON 解析）
     #[serde_as(deserialize_as = "DefaultOnNull")]
     pub extra: NoteHistoryExtra,
-    /// 操作类型：1=创建, 2=更新, 3=删除
+    /// 操作类型：1=创建, 2=更新, 3=删除, 4=恢复历史版本
     #[serde_as(deserialize_as = "DefaultOnNull")]
     pub operate_type: i32,
     /// 操作来源：0=用户操作, 1=MCP操作
@@ -822,6 +1082,247 @@ pub struct NoteHistorySearchPageParam {
     pub note_id: i64,
 }
 
+/// 历史内容搜索参数
+///
+/// 在所有笔记的历史版本（`old_content` / `new_content`）中查找关键词
+#[serde_as]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NoteHistoryContentSearchParam {
+    /// 分页参数
+    #[serde(flatten)]
+    pub page_param: PageParam,
+    /// 搜索关键词（必需，按原文子串匹配）
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub keyword: String,
+    /// 限定笔记 ID（0 表示所有笔记）
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub note_id: i64,
+    /// 操作来源：0=用户操作, 1=MCP操作, 2=同步（不传表示不过滤）
+    pub operate_source: Option<i32>,
+    /// 操作日期起始（含当天）
+    pub start_date: Option<NaiveDate>,
+    /// 操作日期截止（含当天）
+    pub end_date: Option<NaiveDate>,
+}
+
+impl NoteHistoryContentSearchParam {
+    /// 最大关键词长度
+    const MAX_KEYWORD_LENGTH: usize = 500;
+
+    /// 验证搜索参数
+    pub fn validate(&self) -> anyhow::Result<()> {
+        self.page_param.validate()?;
+
+        if self.keyword.trim().is_empty() {
+            anyhow::bail!("{}", t_simple("validation.keywordRequired"))
+        }
+        if self.keyword.len() > Self::MAX_KEYWORD_LENGTH {
+            anyhow::bail!(
+                "{}",
+                t(
+                    "validation.keywordTooLong",
+                    &[&Self::MAX_KEYWORD_LENGTH.to_string()]
+                )
+            )
+        }
+
+        Ok(())
+    }
+
+    /// 规范化搜索参数
+    pub fn normalize(&mut self) {
+        self.page_param.normalize();
+        self.keyword = self.keyword.trim().to_string();
+    }
+}
+
+/// 历史内容搜索命中项
+///
+/// 通过 `note_id` + `history_id` 定位到具体笔记的具体版本
+#[serde_as]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NoteHistoryHit {
+    /// 历史记录（版本）ID
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub history_id: i64,
+    /// 关联的笔记 ID
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub note_id: i64,
+    /// 笔记标题（笔记已被彻底删除时取该版本记录的标题）
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub note_title: String,
+    /// 笔记是否已删除（回收站中或已彻底删除）
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub note_deleted: bool,
+    /// 关键词在该版本中被删除（修改前包含、修改后不包含）
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub removed: bool,
+    /// 命中片段，字段为 oldContent / newContent
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub snippets: Vec<SearchSnippet>,
+    /// 操作类型：1=创建, 2=更新, 3=删除, 4=恢复历史版本
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub operate_type: i32,
+    /// 操作来源：0=用户操作, 1=MCP操作, 2=同步
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub operate_source: i32,
+    /// 操作时间
+    #[serde(serialize_with = "serialize_dt", deserialize_with = "deserialize_dt")]
+    pub operate_time: NaiveDateTime,
+}
+
+/// 版本对比参数
+#[serde_as]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NoteRevisionDiffParam {
+    /// 对比基准的历史记录 ID
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub from_history_id: i64,
+    /// 对比目标的历史记录 ID（0 表示笔记的当前版本）
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub to_history_id: i64,
+}
+
+impl NoteRevisionDiffParam {
+    /// 验证对比参数
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.from_history_id <= 0
+            || self.to_history_id < 0
+            || self.from_history_id == self.to_history_id
+        {
+            anyhow::bail!("{}", t_simple("validation.invalidRevision"))
+        }
+        Ok(())
+    }
+}
+
+/// 笔记的某个版本（历史记录或当前版本）的元信息
+#[serde_as]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NoteRevision {
+    /// 历史记录 ID（0 表示当前版本）
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub history_id: i64,
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub note_id: i64,
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub title: String,
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub notebook_id: i64,
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub notebook_name: String,
+    /// 内容类型：0 = HTML，1 = Markdown
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub content_type: i32,
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub tags: Vec<Tag>,
+    /// 内容仍为密文（未解锁或密钥不匹配），此时内容按空文本参与对比
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub content_encrypted: bool,
+    /// 版本时间（历史记录为操作时间，当前版本为更新时间）
+    #[serde(
+        serialize_with = "serialize_option_dt",
+        deserialize_with = "deserialize_option_dt"
+    )]
+    pub time: Option<NaiveDateTime>,
+}
+
+/// 差异片段类型
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffKind {
+    /// 未变化
+    #[default]
+    Equal,
+    /// 新增
+    Insert,
+    /// 删除
+    Delete,
+}
+
+/// 词级差异片段
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DiffSegment {
+    pub kind: DiffKind,
+    pub text: String,
+}
+
+/// 行级差异
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DiffLine {
+    pub kind: DiffKind,
+    /// 行文本（不含换行符）
+    pub text: String,
+    /// 被修改行的词级差异（仅新增/删除行与对侧存在对应行时填充）
+    pub segments: Vec<DiffSegment>,
+}
+
+/// 两个版本之间的差异
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NoteRevisionDiff {
+    pub from: NoteRevision,
+    pub to: NoteRevision,
+    /// 标题的词级差异
+    pub title: Vec<DiffSegment>,
+    /// 内容的行级差异（HTML 先转换为按块分行的纯文本）
+    pub content: Vec<DiffLine>,
+    /// 新增的标签
+    pub tags_added: Vec<Tag>,
+    /// 移除的标签
+    pub tags_removed: Vec<Tag>,
+    /// 是否移动了笔记本
+    pub notebook_changed: bool,
+    /// 是否切换了内容类型
+    pub content_type_changed: bool,
+}
+
+/// 历史记录精简结果
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HistoryCompactResult {
+    /// 检查的历史记录数
+    pub scanned: u64,
+    /// 删除的历史记录数
+    pub deleted: u64,
+    /// 释放的存储字节数（精简前后数据库存储大小之差）
+    pub freed_bytes: u64,
+}
+
+/// 回收站清理涉及的笔记
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TrashPurgeItem {
+    pub note_id: i64,
+    pub title: String,
+    /// 移入回收站的时间
+    #[serde(
+        serialize_with = "serialize_option_dt",
+        deserialize_with = "deserialize_option_dt"
+    )]
+    pub deleted_at: Option<NaiveDateTime>,
+}
+
+/// 回收站清理结果
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TrashPurgeResult {
+    /// 是否为预览（预览时不删除任何数据）
+    pub dry_run: bool,
+    /// 回收站保留天数（0 表示永久保留）
+    pub retention_days: i64,
+    /// 超过保留期的笔记（预览时为将被删除的笔记）
+    pub notes: Vec<TrashPurgeItem>,
+    /// 实际删除的笔记数
+    pub purged: u64,
+}
+
 /// 笔记链接数据传输对象
 #[serde_as]
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -835,6 +1336,15 @@ pub struct NoteLink {
     /// 链接的笔记标题
     #[serde_as(deserialize_as = "DefaultOnNull")]
     pub note_title: String,
+    /// 链接来源：0 = 手动创建，1 = 从笔记内容解析
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub origin: i32,
+    /// 关系类型（如 references、depends-on、supersedes），空字符串表示未指定
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub relation: String,
+    /// 链接方向：true = 当前笔记指向该笔记（出链），false = 该笔记指向当前笔记（反向链接）
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub outgoing: bool,
     #[serde(
         serialize_with = "serialize_option_dt",
         deserialize_with = "deserialize_option_dt"
@@ -842,6 +1352,111 @@ pub struct NoteLink {
     pub create_time: Option<NaiveDateTime>,
 }
 
+/// 笔记关系图查询条件
+#[serde_as]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NoteGraphQuery {
+    /// 限定笔记本 ID（含子孙笔记本），0 表示不限
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub notebook_id: i64,
+    /// 限定标签 ID，0 表示不限
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub tag_id: i64,
+}
+
+/// 笔记关系图节点
+#[serde_as]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GraphNode {
+    /// 笔记 ID
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub id: i64,
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub title: String,
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub notebook_id: i64,
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub notebook_name: String,
+    /// 标签名称
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub tags: Vec<String>,
+}
+
+/// 笔记关系图的边（source 笔记指向 target 笔记）
+#[serde_as]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GraphEdge {
+    /// 链接 ID
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub id: i64,
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub source: i64,
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub target: i64,
+    /// 关系类型，空字符串表示未指定
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub relation: String,
+    /// 链接来源：0 = 手动创建，1 = 从笔记内容解析
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub origin: i32,
+}
+
+/// 笔记关系图
+#[serde_as]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NoteGraph {
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub nodes: Vec<GraphNode>,
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub edges: Vec<GraphEdge>,
+}
+
+/// 悬空链接：source 或 target 笔记已不存在
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DanglingLink {
+    /// 链接 ID
+    pub id: i64,
+    pub source_note_id: i64,
+    pub target_note_id: i64,
+    /// 链接来源：0 = 手动创建，1 = 从笔记内容解析
+    pub origin: i32,
+    pub relation: String,
+}
+
+/// 笔记内容中无法解析的引用
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BrokenReference {
+    /// 包含引用的笔记
+    pub note_id: i64,
+    pub note_title: String,
+    /// 按 ID 引用时为引用的笔记 ID，按标题引用时为 0
+    pub target_id: i64,
+    /// 按标题引用时为引用的标题，按 ID 引用时为空
+    pub target_title: String,
+}
+
+/// 链接完整性检查结果
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LinkIntegrityReport {
+    /// 是否已执行修复（false 表示仅检查）
+    pub repaired: bool,
+    /// 悬空链接（修复时删除）
+    pub dangling_links: Vec<DanglingLink>,
+    /// 与笔记内容不一致、需新增或删除的内容链接数（修复时重新同步）
+    pub stale_content_links: u64,
+    /// 内容中引用了不存在笔记的位置（只报告，不修改内容）
+    pub broken_references: Vec<BrokenReference>,
+    /// 加密且无法解密、跳过内容检查的笔记数
+    pub skipped_notes: u64,
+}
+
 /// 笔记模板数据传输对象
 #[serde_as]
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -893,6 +1508,368 @@ impl From<entity::note_template::Model> for NoteTemplate {
     }
 }
 
+// ============================================================================
+// 保存的搜索
+// ============================================================================
+
+/// 保存的搜索条件定义（以 JSON 形式持久化）
+#[serde_as]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SavedSearchFilter {
+    /// 搜索关键词（支持结构化语法）
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub keyword: String,
+    /// 笔记本 ID 列表（命中任一即可）
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub notebook_ids: Vec<i64>,
+    /// 笔记本筛选是否包含子孙笔记本
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub include_descendants: bool,
+    /// 必须同时包含的标签 ID
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub all_tag_ids: Vec<i64>,
+    /// 至少包含其一的标签 ID
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub any_tag_ids: Vec<i64>,
+    /// 不能包含的标签 ID
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub exclude_tag_ids: Vec<i64>,
+    /// 标签筛选是否包含子孙标签
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub include_tag_descendants: bool,
+    /// 仅收藏/星标笔记
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub is_starred: bool,
+    /// 更新时间不早于该日期（含当天）
+    pub updated_after: Option<NaiveDate>,
+    /// 更新时间早于该日期（不含当天）
+    pub updated_before: Option<NaiveDate>,
+    /// 排序字段，取值同 `NoteSearchPageParam::sort_field`
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub sort_field: String,
+    /// 排序方向：asc, desc
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub sort_order: String,
+}
+
+impl SavedSearchFilter {
+    /// 转换为搜索参数
+    ///
+    /// 日期范围以 `after:` / `before:` 限定符追加到关键词，复用结构化搜索语法
+    pub fn to_search_param(&self, page_param: PageParam) -> NoteSearchPageParam {
+        let mut keyword = self.keyword.trim().to_string();
+        if let Some(date) = self.updated_after {
+            keyword.push_str(&format!(" after:{}", date.format("%Y-%m-%d")));
+        }
+        if let Some(date) = self.updated_before {
+            keyword.push_str(&format!(" before:{}", date.format("%Y-%m-%d")));
+        }
+
+        let mut param = NoteSearchPageParam {
+            page_param,
+            keyword,
+            sort_field: self.sort_field.clone(),
+            sort_order: self.sort_order.clone(),
+            is_starred: self.is_starred,
+            notebook_ids: self.notebook_ids.clone(),
+            include_descendants: self.include_descendants,
+            all_tag_ids: self.all_tag_ids.clone(),
+            any_tag_ids: self.any_tag_ids.clone(),
+            exclude_tag_ids: self.exclude_tag_ids.clone(),
+            include_tag_descendants: self.include_tag_descendants,
+            ..Default::default()
+        };
+        param.normalize();
+        param
+    }
+}
+
+/// 保存的搜索（智能笔记本）数据传输对象
+#[serde_as]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SavedSearch {
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub id: i64,
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub name: String,
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub icon: String,
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub sort_order: i32,
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub filter: SavedSearchFilter,
+    #[serde(
+        serialize_with = "serialize_option_dt",
+        deserialize_with = "deserialize_option_dt"
+    )]
+    pub create_time: Option<NaiveDateTime>,
+    #[serde(
+        serialize_with = "serialize_option_dt",
+        deserialize_with = "deserialize_option_dt"
+    )]
+    pub update_time: Option<NaiveDateTime>,
+}
+
+impl SavedSearch {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.name.trim().is_empty() {
+            anyhow::bail!("{}", t_simple("validation.nameRequired"))
+        }
+        self.filter.to_search_param(PageParam::default()).validate()
+    }
+}
+
+impl From<entity::saved_search::Model> for SavedSearch {
+    fn from(value: entity::saved_search::Model) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+            icon: value.icon,
+            sort_order: value.sort_order,
+            filter: serde_json::from_str(&value.filter).unwrap_or_else(|e| {
+                warn!("Failed to deserialize saved search filter: {}", e);
+                SavedSearchFilter::default()
+            }),
+            create_time: Some(value.create_time),
+            update_time: Some(value.update_time),
+        }
+    }
+}
+
+// ============================================================================
+// 笔记自动规则相关
+// ============================================================================
+
+/// 笔记自动规则条件
+///
+/// - `field` 为 `title` / `content`：`operator` 为 `contains`、`not_contains`、
+///   `starts_with`、`ends_with`、`equals`，`value` 为文本（不区分大小写）
+/// - `field` 为 `source`：`operator` 为 `equals`、`not_equals`，
+///   `value` 为 `user`、`mcp`、`sync`
+/// - `field` 为 `notebook`：`operator` 为 `equals`、`not_equals`、`within`（含子笔记本），
+///   `value` 为笔记本 ID
+/// - `field` 为 `tag`：`operator` 为 `has`、`not_has`、`has_within`（含子标签），
+///   `value` 为标签 ID
+#[serde_as]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NoteRuleCondition {
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub field: String,
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub operator: String,
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub value: String,
+}
+
+impl NoteRuleCondition {
+    /// 检查字段、比较方式与比较值是否有效
+    pub fn is_valid(&self) -> bool {
+        match self.field.as_str() {
+            "title" | "content" => {
+                matches!(
+                    self.operator.as_str(),
+                    "contains" | "not_contains" | "starts_with" | "ends_with" | "equals"
+                ) && !self.value.is_empty()
+            }
+            "source" => {
+                matches!(self.operator.as_str(), "equals" | "not_equals")
+                    && matches!(self.value.as_str(), "user" | "mcp" | "sync")
+            }
+            "notebook" => {
+                matches!(self.operator.as_str(), "equals" | "not_equals" | "within")
+                    && self.value.parse::<i64>().is_ok_and(|id| id >= 0)
+            }
+            "tag" => {
+                matches!(self.operator.as_str(), "has" | "not_has" | "has_within")
+                    && self.value.parse::<i64>().is_ok_and(|id| id > 0)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// 笔记自动规则动作
+///
+/// - `move`：移动到笔记本，`value` 为笔记本 ID
+/// - `add_tag` / `remove_tag`：添加/移除标签，`value` 为标签 ID
+/// - `star` / `unstar`、`pin` / `unpin`：收藏/取消收藏、置顶/取消置顶，忽略 `value`
+/// - `set_mcp_access`：设置 MCP 访问权限，`value` 为 0-3（见 [`McpAccess`]）
+#[serde_as]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NoteRuleAction {
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub action: String,
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub value: String,
+}
+
+impl NoteRuleAction {
+    /// 检查动作类型与参数是否有效
+    pub fn is_valid(&self) -> bool {
+        match self.action.as_str() {
+            "move" | "add_tag" | "remove_tag" => self.value.parse::<i64>().is_ok_and(|id| id > 0),
+            "star" | "unstar" | "pin" | "unpin" => true,
+            "set_mcp_access" => self
+                .value
+                .parse::<i32>()
+                .is_ok_and(|v| (0..=3).contains(&v)),
+            _ => false,
+        }
+    }
+}
+
+/// 笔记自动规则数据传输对象
+#[serde_as]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NoteRule {
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub id: i64,
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub name: String,
+    /// 是否启用
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub enabled: bool,
+    /// 创建笔记时是否执行
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub run_on_create: bool,
+    /// 更新笔记时是否执行
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub run_on_update: bool,
+    /// 条件组合方式：false=全部满足，true=任一满足（无条件时匹配所有笔记）
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub match_any: bool,
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub conditions: Vec<NoteRuleCondition>,
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub actions: Vec<NoteRuleAction>,
+    #[serde_as(deserialize_as = "DefaultOnNull")]
+    pub sort_order: i32,
+    #[serde(
+        serialize_with = "serialize_option_dt",
+        deserialize_with = "deserialize_option_dt"
+    )]
+    pub create_time: Option<NaiveDateTime>,
+    #[serde(
+        serialize_with = "serialize_option_dt",
+        deserialize_with = "deserialize_option_dt"
+    )]
+    pub update_time: Option<NaiveDateTime>,
+}
+
+impl NoteRule {
+    /// 最大名称长度
+    const MAX_NAME_LENGTH: usize = 255;
+
+    /// 验证规则：名称必填，条件与动作均有效且至少有一个动作
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.name.trim().is_empty() {
+            anyhow::bail!("{}", t_simple("validation.nameRequired"))
+        }
+        if self.name.len() > Self::MAX_NAME_LENGTH {
+            anyhow::bail!(
+                "{}",
+                t(
+                    "validation.nameTooLong",
+                    &[&Self::MAX_NAME_LENGTH.to_string()]
+                )
+            )
+        }
+        if let Some(index) = self.conditions.iter().position(|c| !c.is_valid()) {
+            anyhow::bail!(
+                "{}",
+                t(
+                    "validation.invalidRuleCondition",
+                    &[&(index + 1).to_string()]
+                )
+            )
+        }
+        if self.actions.is_empty() {
+            anyhow::bail!("{}", t_simple("validation.ruleActionRequired"))
+        }
+        if let Some(index) = self.actions.iter().position(|a| !a.is_valid()) {
+            anyhow::bail!(
+                "{}",
+                t("validation.invalidRuleAction", &[&(index + 1).to_string()])
+            )
+        }
+        Ok(())
+    }
+}
+
+impl From<entity::note_rule::Model> for NoteRule {
+    fn from(value: entity::note_rule::Model) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+            enabled: value.enabled == 1,
+            run_on_create: value.run_on_create == 1,
+            run_on_update: value.run_on_update == 1,
+            match_any: value.match_any == 1,
+            conditions: serde_json::from_str(&value.conditions).unwrap_or_else(|e| {
+                warn!("Failed to deserialize note rule conditions: {}", e);
+                Vec::new()
+            }),
+            actions: serde_json::from_str(&value.actions).unwrap_or_else(|e| {
+                warn!("Failed to deserialize note rule actions: {}", e);
+                Vec::new()
+            }),
+            sort_order: value.sort_order,
+            create_time: Some(value.create_time),
+            update_time: Some(value.update_time),
+        }
+    }
+}
+
+/// 规则对一篇笔记产生的变更（仅包含与笔记当前状态不同的部分）
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteRuleEffect {
+    /// 移动到的笔记本 ID
+    pub notebook_id: Option<i64>,
+    /// 新增的标签 ID
+    pub add_tag_ids: Vec<i64>,
+    /// 移除的标签 ID
+    pub remove_tag_ids: Vec<i64>,
+    pub is_starred: Option<bool>,
+    pub is_pinned: Option<bool>,
+    pub mcp_access: Option<i32>,
+}
+
+impl NoteRuleEffect {
+    /// 是否没有任何变更
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// 手动执行规则时单篇笔记的匹配结果
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteRuleMatch {
+    pub note_id: i64,
+    pub title: String,
+    /// 命中的规则 ID（按执行顺序）
+    pub rule_ids: Vec<i64>,
+    pub effect: NoteRuleEffect,
+}
+
+/// 手动执行规则的结果
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteRuleRunResult {
+    /// 是否为预览（未实际修改笔记）
+    pub dry_run: bool,
+    /// 检查的笔记数量
+    pub checked: u64,
+    /// 会产生变更（预览）或已变更的笔记
+    pub matches: Vec<NoteRuleMatch>,
+}
+
 // ============================================================================
 // 笔记附件相关
 // ============================================================================
@@ -3,17 +3,20 @@
 //! 提供附件的保存、查询、删除功能。
 //! 附件文件存储在 `app_data_dir/attachments/` 目录中。
 //! 支持 SHA256 去重：相同内容的文件只存储一份物理副本。
+//! 保存时同步提取附件文本（见 `attachment_text`），供关键词搜索使用。
 
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
 use chrono::Local;
+use sea_orm::prelude::Expr;
 use sea_orm::*;
 use sha2::{Digest, Sha256};
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::entity;
 use crate::model::{AttachmentStats, NoteAttachment};
+use crate::service::attachment_text;
 
 /// 最大附件大小：50 MB
 const MAX_FILE_SIZE: usize = 50 * 1024 * 1024;
@@ -129,6 +132,10 @@ pub async fn save_attachment(
         "Attachment saved: note_id={}, file={}",
         note_id, stored_name
     );
+
+    // 在后台提取附件文本用于搜索（PDF 解析可能较慢），失败不影响附件保存
+    attachment_text::spawn_index_attachment(db.clone(), entity.clone(), file_data.to_vec());
+
     Ok(NoteAttachment::from(entity))
 }
 
@@ -169,6 +176,7 @@ pub async fn delete_by_id(db: &DatabaseConnection, app_data_dir: &Path, id: i64)
         entity::note_attachment::Entity::delete_by_id(id)
             .exec(db)
             .await?;
+        attachment_text::delete_by_attachment_id(db, id).await?;
 
         // 仅当没有其他引用时删除物理文件
         if same_file_count <= 1 {
@@ -195,6 +203,74 @@ pub async fn delete_by_id(db: &DatabaseConnection, app_data_dir: &Path, id: i64)
     Ok(())
 }
 
+/// 在给定连接上删除笔记的全部附件记录（含附件文本），返回不再被引用的物理文件名
+///
+/// 只修改数据库，仍被其他附件引用的文件按 [`delete_by_id`] 的方式更新引用计数；
+/// 物理文件由调用方在事务提交后通过 [`remove_files`] 删除
+pub async fn delete_records_by_note_ids<C: ConnectionTrait>(
+    db: &C,
+    note_ids: &[i64],
+) -> Result<Vec<String>> {
+    if note_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let models = entity::note_attachment::Entity::find()
+        .filter(entity::note_attachment::Column::NoteId.is_in(note_ids.to_vec()))
+        .all(db)
+        .await?;
+
+    let mut file_paths: Vec<String> = Vec::new();
+    for model in &models {
+        // 安全校验：防止路径穿越
+        if model.file_path.contains("..")
+            || model.file_path.contains('/')
+            || model.file_path.contains('\\')
+        {
+            anyhow::bail!("Invalid attachment file path detected");
+        }
+        entity::note_attachment::Entity::delete_by_id(model.id)
+            .exec(db)
+            .await?;
+        attachment_text::delete_by_attachment_id(db, model.id).await?;
+        if !file_paths.contains(&model.file_path) {
+            file_paths.push(model.file_path.clone());
+        }
+    }
+
+    let mut orphaned = Vec::new();
+    for file_path in file_paths {
+        let remaining = entity::note_attachment::Entity::find()
+            .filter(entity::note_attachment::Column::FilePath.eq(&file_path))
+            .count(db)
+            .await?;
+        if remaining == 0 {
+            orphaned.push(file_path);
+        } else {
+            entity::note_attachment::Entity::update_many()
+                .col_expr(
+                    entity::note_attachment::Column::RefCount,
+                    Expr::value(remaining as i32),
+                )
+                .filter(entity::note_attachment::Column::FilePath.eq(&file_path))
+                .exec(db)
+                .await?;
+        }
+    }
+    Ok(orphaned)
+}
+
+/// 删除附件目录中的物理文件（数据库记录已删除，失败时仅记录警告）
+pub fn remove_files(app_data_dir: &Path, file_paths: &[String]) {
+    for file_path in file_paths {
+        let path = attachments_dir(app_data_dir).join(file_path);
+        if path.exists()
+            && let Err(e) = std::fs::remove_file(&path)
+        {
+            warn!("Failed to delete attachment file {}: {}", file_path, e);
+        }
+    }
+}
+
 /// 获取附件统计信息
 pub async fn get_stats(db: &DatabaseConnection, app_data_dir: &Path) -> Result<AttachmentStats> {
     let total_count = entity::note_attachment::Entity::find().count(db).await?;
@@ -0,0 +1,371 @@
+//! 附件文本提取与索引服务
+//!
+//! 附件保存后在后台从文件内容中提取纯文本，写入 `note_attachment_text` 表，
+//! 由各后端的全文索引（SQLite `attachment_fts` / PG GIN / MySQL FULLTEXT）参与关键词搜索。
+//! 加密笔记的附件不提取文本，笔记加密后删除其已有的附件文本，避免以明文形式暴露。
+//!
+//! 支持的格式：
+//! - 纯文本类（txt、md、csv、json、log 等），按 UTF-8 解码
+//! - HTML / XML，去除标签
+//! - Word 文档（docx），读取 `word/document.xml`
+//! - PDF，使用 `pdf-extract`，在子进程中解析（仅桌面端）
+//!
+//! 不支持的类型或提取失败时不写入文本，仅记录日志，不影响附件保存。
+
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::Path;
+
+use anyhow::Result;
+use chrono::Local;
+use sea_orm::*;
+use tracing::{info, warn};
+
+use crate::entity;
+use crate::service::crypto;
+use crate::service::note::content_to_text;
+
+/// 单个附件保留的最大文本字符数
+const MAX_TEXT_CHARS: usize = 200_000;
+
+/// 提取方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TextKind {
+    Plain,
+    Markup,
+    Docx,
+    Pdf,
+}
+
+/// 根据 MIME 类型与扩展名判断提取方式，不支持时返回 None
+fn detect_kind(file_name: &str, mime_type: &str) -> Option<TextKind> {
+    let mime = mime_type.to_ascii_lowercase();
+    let ext = Path::new(file_name)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+
+    match (mime.as_str(), ext.as_str()) {
+        ("application/pdf", _) | (_, "pdf") => Some(TextKind::Pdf),
+        ("application/vnd.openxmlformats-officedocument.wordprocessingml.document", _)
+        | (_, "docx") => Some(TextKind::Docx),
+        ("text/html" | "application/xhtml+xml" | "text/xml" | "application/xml", _)
+        | (_, "html" | "htm" | "xhtml" | "xml") => Some(TextKind::Markup),
+        ("application/json" | "application/x-yaml" | "application/yaml", _)
+        | (_, "txt" | "md" | "markdown" | "csv" | "tsv" | "json" | "log" | "yaml" | "yml") => {
+            Some(TextKind::Plain)
+        }
+        (m, _) if m.starts_with("text/") => Some(TextKind::Plain),
+        _ => None,
+    }
+}
+
+/// 读取 docx 正文 XML，段落结束处插入空白后去除标签
+fn extract_docx(data: &[u8]) -> Result<String> {
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(data))?;
+    let mut xml = String::new();
+    archive
+        .by_name("word/document.xml")?
+        .read_to_string(&mut xml)?;
+    Ok(content_to_text(&xml.replace("</w:p>", "</w:p> "), 0))
+}
+
+/// PDF 文本提取子进程的命令行参数（见 `main.rs`）
+pub const PDF_EXTRACT_ARG: &str = "--extract-pdf-text";
+
+/// PDF 文本提取子进程的最长运行时间
+#[cfg(feature = "desktop")]
+const PDF_EXTRACT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// 提取 PDF 文本
+///
+/// `pdf-extract` 在部分损坏文件上会 panic，而 release 构建使用 `panic = "abort"`，
+/// 进程内无法捕获，因此以 `PDF_EXTRACT_ARG` 启动当前程序的子进程解析：
+/// 子进程崩溃、出错或超时都只转换为错误，不影响主进程
+#[cfg(feature = "desktop")]
+fn extract_pdf(data: &[u8]) -> Result<String> {
+    use std::process::{Command, Stdio};
+    use std::time::Instant;
+
+    let mut child = Command::new(std::env::current_exe()?)
+        .arg(PDF_EXTRACT_ARG)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    // 输入输出在独立线程中读写，避免管道缓冲区写满导致双方互相等待
+    let mut stdin = child
+        .stdin
+        .take()
+        .ok_or_else(|| anyhow::anyhow!("PDF parser stdin unavailable"))?;
+    let data = data.to_vec();
+    let writer = std::thread::spawn(move || stdin.write_all(&data));
+    let mut stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| anyhow::anyhow!("PDF parser stdout unavailable"))?;
+    let reader = std::thread::spawn(move || {
+        let mut output = Vec::new();
+        stdout.read_to_end(&mut output).map(|_| output)
+    });
+
+    let deadline = Instant::now() + PDF_EXTRACT_TIMEOUT;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            anyhow::bail!("PDF parser timed out");
+        }
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    };
+
+    // 子进程提前退出时写入会失败，以退出状态为准
+    let _ = writer.join();
+    let output = reader
+        .join()
+        .map_err(|_| anyhow::anyhow!("PDF parser output thread panicked"))??;
+    if !status.success() {
+        anyhow::bail!("PDF parser exited with {}", status);
+    }
+    Ok(content_to_text(&String::from_utf8_lossy(&output), 1))
+}
+
+/// 移动端无法启动子进程，不提取 PDF 文本
+#[cfg(not(feature = "desktop"))]
+fn extract_pdf(_data: &[u8]) -> Result<String> {
+    anyhow::bail!("PDF text extraction is only supported on desktop")
+}
+
+/// PDF 文本提取子进程入口：从标准输入读取 PDF，将文本写到标准输出
+///
+/// 返回进程退出码，解析失败时为 1
+pub fn run_pdf_extractor() -> i32 {
+    let mut data = Vec::new();
+    if std::io::stdin().read_to_end(&mut data).is_err() {
+        return 1;
+    }
+    match pdf_extract::extract_text_from_mem(&data) {
+        Ok(text) if std::io::stdout().write_all(text.as_bytes()).is_ok() => 0,
+        _ => 1,
+    }
+}
+
+/// 从附件内容中提取纯文本
+///
+/// 不支持的类型、提取失败或结果为空时返回 None；结果超过上限时截断
+pub fn extract_text(file_name: &str, mime_type: &str, data: &[u8]) -> Option<String> {
+    let kind = detect_kind(file_name, mime_type)?;
+
+    let result = match kind {
+        TextKind::Plain => Ok(content_to_text(&String::from_utf8_lossy(data), 1)),
+        TextKind::Markup => Ok(content_to_text(&String::from_utf8_lossy(data), 0)),
+        TextKind::Docx => extract_docx(data),
+        TextKind::Pdf => extract_pdf(data),
+    };
+
+    match result {
+        Ok(text) if !text.is_empty() => Some(match text.char_indices().nth(MAX_TEXT_CHARS) {
+            Some((idx, _)) => text[..idx].to_string(),
+            None => text,
+        }),
+        Ok(_) => None,
+        Err(e) => {
+            warn!(
+                "Attachment text extraction failed: file={}, {}",
+                file_name, e
+            );
+            None
+        }
+    }
+}
+
+/// 附件所属笔记的内容是否已加密
+async fn note_encrypted(db: &DatabaseConnection, note_id: i64) -> Result<bool> {
+    Ok(entity::note::Entity::find_by_id(note_id)
+        .one(db)
+        .await?
+        .is_some_and(|note| crypto::is_encrypted(&note.content)))
+}
+
+/// 为附件提取并保存文本
+///
+/// 相同哈希的文件已有提取结果时直接复用；提取在阻塞线程池中进行。
+/// 加密笔记的附件不提取文本。返回是否写入了文本
+pub async fn index_attachment(
+    db: &DatabaseConnection,
+    attachment: &entity::note_attachment::Model,
+    data: &[u8],
+) -> Result<bool> {
+    if note_encrypted(db, attachment.note_id).await? {
+        delete_by_attachment_id(db, attachment.id).await?;
+        return Ok(false);
+    }
+
+    let reused = entity::note_attachment_text::Entity::find()
+        .inner_join(entity::note_attachment::Entity)
+        .filter(entity::note_attachment::Column::FileHash.eq(&attachment.file_hash))
+        .filter(entity::note_attachment::Column::Id.ne(attachment.id))
+        .one(db)
+        .await?
+        .map(|m| m.content);
+
+    let content = match reused {
+        Some(content) => Some(content),
+        None => {
+            let file_name = attachment.file_name.clone();
+            let mime_type = attachment.mime_type.clone();
+            let data = data.to_vec();
+            tokio::task::spawn_blocking(move || extract_text(&file_name, &mime_type, &data)).await?
+        }
+    };
+
+    let Some(content) = content else {
+        return Ok(false);
+    };
+    // 提取期间附件可能已被删除，或笔记已被加密
+    if entity::note_attachment::Entity::find_by_id(attachment.id)
+        .one(db)
+        .await?
+        .is_none()
+        || note_encrypted(db, attachment.note_id).await?
+    {
+        return Ok(false);
+    }
+
+    // 重建索引时可能已有旧记录
+    delete_by_attachment_id(db, attachment.id).await?;
+
+    entity::note_attachment_text::ActiveModel {
+        id: ActiveValue::NotSet,
+        attachment_id: ActiveValue::Set(attachment.id),
+        note_id: ActiveValue::Set(attachment.note_id),
+        content: ActiveValue::Set(content),
+        create_time: ActiveValue::Set(Local::now().naive_local()),
+    }
+    .insert(db)
+    .await?;
+
+    Ok(true)
+}
+
+/// 在后台执行 `index_attachment`，失败时只记录日志，不影响附件保存
+pub fn spawn_index_attachment(
+    db: DatabaseConnection,
+    attachment: entity::note_attachment::Model,
+    data: Vec<u8>,
+) {
+    tokio::spawn(async move {
+        if let Err(e) = index_attachment(&db, &attachment, &data).await {
+            warn!(
+                "Attachment text indexing failed: id={}, {}",
+                attachment.id, e
+            );
+        }
+    });
+}
+
+/// 删除笔记全部附件的提取文本（笔记内容加密时调用）
+pub async fn delete_by_note_id<C: ConnectionTrait>(db: &C, note_id: i64) -> Result<()> {
+    entity::note_attachment_text::Entity::delete_many()
+        .filter(entity::note_attachment_text::Column::NoteId.eq(note_id))
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+/// 删除附件的提取文本
+pub async fn delete_by_attachment_id<C: ConnectionTrait>(db: &C, attachment_id: i64) -> Result<()> {
+    entity::note_attachment_text::Entity::delete_many()
+        .filter(entity::note_attachment_text::Column::AttachmentId.eq(attachment_id))
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+/// 批量查询笔记的附件文本（note_id → 文本列表）
+pub async fn find_by_note_ids(
+    db: &DatabaseConnection,
+    note_ids: &[i64],
+) -> Result<HashMap<i64, Vec<String>>> {
+    if note_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let mut map = HashMap::<i64, Vec<String>>::new();
+    for model in entity::note_attachment_text::Entity::find()
+        .filter(entity::note_attachment_text::Column::NoteId.is_in(note_ids.to_vec()))
+        .order_by_asc(entity::note_attachment_text::Column::AttachmentId)
+        .all(db)
+        .await?
+    {
+        map.entry(model.note_id).or_default().push(model.content);
+    }
+    Ok(map)
+}
+
+/// 重建全部附件的文本索引
+///
+/// 用于升级后为历史附件补建索引，返回成功提取文本的附件数
+pub async fn rebuild_index(db: &DatabaseConnection, app_data_dir: &Path) -> Result<u32> {
+    entity::note_attachment_text::Entity::delete_many()
+        .exec(db)
+        .await?;
+
+    let dir = app_data_dir.join("attachments");
+    let mut indexed = 0u32;
+    for attachment in entity::note_attachment::Entity::find().all(db).await? {
+        let data = match std::fs::read(dir.join(&attachment.file_path)) {
+            Ok(data) => data,
+            Err(e) => {
+                warn!(
+                    "Skip attachment text indexing: file={}, {}",
+                    attachment.file_path, e
+                );
+                continue;
+            }
+        };
+        if index_attachment(db, &attachment, &data).await? {
+            indexed += 1;
+        }
+    }
+
+    info!("Attachment text index rebuilt: {} attachments", indexed);
+    Ok(indexed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_supported_types() {
+        assert_eq!(detect_kind("a.pdf", ""), Some(TextKind::Pdf));
+        assert_eq!(detect_kind("notes", "text/plain"), Some(TextKind::Plain));
+        assert_eq!(detect_kind("page.HTML", ""), Some(TextKind::Markup));
+        assert_eq!(detect_kind("photo.png", "image/png"), None);
+    }
+
+    #[test]
+    fn plain_and_markup_text() {
+        assert_eq!(
+            extract_text("a.md", "", "# 会议纪要\n\n第一项".as_bytes()).as_deref(),
+            Some("# 会议纪要 第一项")
+        );
+        assert_eq!(
+            extract_text("a.html", "text/html", b"<p>Hello&amp;<b>world</b></p>").as_deref(),
+            Some("Hello& world")
+        );
+        assert_eq!(extract_text("a.png", "image/png", b"\x89PNG"), None);
+        assert_eq!(extract_text("a.txt", "", b"   "), None);
+    }
+
+    #[test]
+    fn broken_docx_is_ignored() {
+        assert_eq!(extract_text("a.docx", "", b"not a zip"), None);
+    }
+}
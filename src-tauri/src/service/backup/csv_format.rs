@@ -4,8 +4,12 @@ use anyhow::Context;
 use sea_orm::*;
 use tracing::info;
 
-use super::{BATCH_SIZE, BackupData, clear_tables, format_dt, parse_dt, restore_data};
+use super::{
+    BATCH_SIZE, BackupData, clear_tables, format_dt, note_history_export_query, parse_dt,
+    restore_data,
+};
 use crate::entity::{note, note_history, note_tags, notebook, tag};
+use crate::service::history_storage::HistoryDecoder;
 
 /// 安全解析 CSV 字段为 i64，解析失败时返回带上下文的错误
 fn parse_i64(val: &str, table: &str, field: &str) -> anyhow::Result<i64> {
@@ -75,6 +79,7 @@ pub async fn export_csv(db: &DatabaseConnection, path: &str) -> anyhow::Result<(
             "sort_order",
             "create_time",
             "update_time",
+            "parent_id",
         ])?;
         for m in tag::Entity::find().all(db).await? {
             wtr.write_record(&[
@@ -85,6 +90,7 @@ pub async fn export_csv(db: &DatabaseConnection, path: &str) -> anyhow::Result<(
                 m.sort_order.to_string(),
                 format_dt(&m.create_time),
                 format_dt(&m.update_time),
+                m.parent_id.to_string(),
             ])?;
         }
         zip.start_file("tag.csv", opts)?;
@@ -162,9 +168,11 @@ pub async fn export_csv(db: &DatabaseConnection, path: &str) -> anyhow::Result<(
             "operate_time",
             "create_time",
         ])?;
-        let mut paginator = note_history::Entity::find().paginate(db, BATCH_SIZE);
+        let mut paginator = note_history_export_query().paginate(db, BATCH_SIZE);
+        let mut decoder = HistoryDecoder::default();
         while let Some(batch) = paginator.fetch_and_next().await? {
             for m in batch {
+                let m = decoder.decode(db, m).await?;
                 wtr.write_record(&[
                     m.id.to_string(),
                     m.note_id.to_string(),
@@ -235,6 +243,11 @@ pub async fn import_csv(db: &DatabaseConnection, path: &str) -> anyhow::Result<(
             }
             data.tags.push(tag::Model {
                 id: parse_i64(&r[0], "tag", "id")?,
+                parent_id: if r.len() > 7 {
+                    r[7].parse().unwrap_or(0)
+                } else {
+                    0
+                },
                 name: r[1].to_string(),
                 icon: r[2].to_string(),
                 cls: r[3].to_string(),
@@ -276,6 +289,7 @@ pub async fn import_csv(db: &DatabaseConnection, path: &str) -> anyhow::Result<(
                 create_time: parse_dt(&r[5])?,
                 update_time: parse_dt(&r[6])?,
                 deleted_at: None,
+                version: 1,
             });
         }
     }
@@ -321,6 +335,8 @@ pub async fn import_csv(db: &DatabaseConnection, path: &str) -> anyhow::Result<(
                 operate_source: 0,
                 operate_time: parse_dt(&r[6])?,
                 create_time: parse_dt(&r[7])?,
+                content_format: 0,
+                base_id: 0,
             });
         }
     }
@@ -2,8 +2,12 @@ use chrono::NaiveDateTime;
 use sea_orm::*;
 use tracing::info;
 
-use super::{BATCH_SIZE, BackupData, clear_tables, format_dt, parse_dt, restore_data};
+use super::{
+    BATCH_SIZE, BackupData, clear_tables, format_dt, note_history_export_query, parse_dt,
+    restore_data,
+};
 use crate::entity::{note, note_history, note_tags, notebook, tag};
+use crate::service::history_storage::HistoryDecoder;
 
 pub async fn export_excel(db: &DatabaseConnection, path: &str) -> anyhow::Result<()> {
     let mut workbook = rust_xlsxwriter::Workbook::new();
@@ -54,6 +58,7 @@ pub async fn export_excel(db: &DatabaseConnection, path: &str) -> anyhow::Result
             "sort_order",
             "create_time",
             "update_time",
+            "parent_id",
         ]
         .iter()
         .enumerate()
@@ -69,6 +74,7 @@ pub async fn export_excel(db: &DatabaseConnection, path: &str) -> anyhow::Result
             sheet.write_number(row, 4, m.sort_order as f64)?;
             sheet.write_string(row, 5, format_dt(&m.create_time))?;
             sheet.write_string(row, 6, format_dt(&m.update_time))?;
+            sheet.write_number(row, 7, m.parent_id as f64)?;
         }
     }
 
@@ -160,9 +166,12 @@ pub async fn export_excel(db: &DatabaseConnection, path: &str) -> anyhow::Result
             sheet.write_string(0, i as u16, *h)?;
         }
         let mut row_offset: u32 = 1;
-        let mut paginator = note_history::Entity::find().paginate(db, BATCH_SIZE);
+        let mut paginator = note_history_export_query().paginate(db, BATCH_SIZE);
+        let mut decoder = HistoryDecoder::default();
         while let Some(batch) = paginator.fetch_and_next().await? {
-            for (r, m) in batch.iter().enumerate() {
+            let batch_len = batch.len() as u32;
+            for (r, m) in batch.into_iter().enumerate() {
+                let m = decoder.decode(db, m).await?;
                 let row = row_offset + r as u32;
                 sheet.write_number(row, 0, m.id as f64)?;
                 sheet.write_number(row, 1, m.note_id as f64)?;
@@ -173,7 +182,7 @@ pub async fn export_excel(db: &DatabaseConnection, path: &str) -> anyhow::Result
                 sheet.write_string(row, 6, format_dt(&m.operate_time))?;
                 sheet.write_string(row, 7, format_dt(&m.create_time))?;
             }
-            row_offset += batch.len() as u32;
+            row_offset += batch_len;
         }
     }
 
@@ -221,6 +230,7 @@ pub async fn import_excel(db: &DatabaseConnection, path: &str) -> anyhow::Result
             }
             data.tags.push(tag::Model {
                 id: cell_i64(&row[0]),
+                parent_id: if row.len() > 7 { cell_i64(&row[7]) } else { 0 },
                 name: cell_str(&row[1]),
                 icon: cell_str(&row[2]),
                 cls: cell_str(&row[3]),
@@ -257,6 +267,7 @@ pub async fn import_excel(db: &DatabaseConnection, path: &str) -> anyhow::Result
                 create_time: cell_dt(&row[5])?,
                 update_time: cell_dt(&row[6])?,
                 deleted_at: None,
+                version: 1,
             });
         }
     }
@@ -292,6 +303,8 @@ pub async fn import_excel(db: &DatabaseConnection, path: &str) -> anyhow::Result
                 operate_source: 0,
                 operate_time: cell_dt(&row[6])?,
                 create_time: cell_dt(&row[7])?,
+                content_format: 0,
+                base_id: 0,
             });
         }
     }
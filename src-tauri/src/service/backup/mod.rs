@@ -10,6 +10,7 @@ pub use csv_format::*;
 pub use excel::*;
 pub use sql::*;
 
+use std::collections::HashSet;
 use std::path::Path;
 
 use chrono::NaiveDateTime;
@@ -17,6 +18,7 @@ use sea_orm::*;
 use tracing::info;
 
 use crate::entity::{note, note_history, note_tags, notebook, tag};
+use crate::service::history_storage;
 
 /// 分页批次大小
 pub(super) const BATCH_SIZE: u64 = 500;
@@ -39,6 +41,15 @@ pub(super) struct BackupData {
 // 通用工具函数
 // ============================================================================
 
+/// 历史记录导出查询
+///
+/// 按 (笔记, ID) 顺序读取，配合 `HistoryDecoder` 逐条还原为完整文本，备份文件中不包含增量
+pub(super) fn note_history_export_query() -> Select<note_history::Entity> {
+    note_history::Entity::find()
+        .order_by_asc(note_history::Column::NoteId)
+        .order_by_asc(note_history::Column::Id)
+}
+
 pub(super) fn format_dt(dt: &NaiveDateTime) -> String {
     dt.format(DT_FMT).to_string()
 }
@@ -67,14 +78,19 @@ pub(super) async fn restore_data(
 ) -> anyhow::Result<()> {
     use sea_orm::ActiveValue::Set;
 
-    // 批量插入 notebooks
+    // 批量插入 notebooks（保留 ID 与层级，父笔记本不在备份中的按顶级恢复）
     if !data.notebooks.is_empty() {
+        let notebook_ids: HashSet<i64> = data.notebooks.iter().map(|m| m.id).collect();
         let models: Vec<notebook::ActiveModel> = data
             .notebooks
             .iter()
             .map(|m| notebook::ActiveModel {
                 id: Set(m.id),
-                parent_id: Set(m.parent_id),
+                parent_id: Set(if notebook_ids.contains(&m.parent_id) {
+                    m.parent_id
+                } else {
+                    0
+                }),
                 name: Set(m.name.clone()),
                 description: Set(m.description.clone()),
                 icon: Set(m.icon.clone()),
@@ -88,13 +104,19 @@ pub(super) async fn restore_data(
         notebook::Entity::insert_many(models).exec(txn).await?;
     }
 
-    // 批量插入 tags
+    // 批量插入 tags（保留 ID 与层级，父标签不在备份中的按顶级恢复）
     if !data.tags.is_empty() {
+        let tag_ids: HashSet<i64> = data.tags.iter().map(|m| m.id).collect();
         let models: Vec<tag::ActiveModel> = data
             .tags
             .iter()
             .map(|m| tag::ActiveModel {
                 id: Set(m.id),
+                parent_id: Set(if tag_ids.contains(&m.parent_id) {
+                    m.parent_id
+                } else {
+                    0
+                }),
                 name: Set(m.name.clone()),
                 icon: Set(m.icon.clone()),
                 cls: Set(m.cls.clone()),
@@ -123,6 +145,7 @@ pub(super) async fn restore_data(
                 create_time: Set(m.create_time),
                 update_time: Set(m.update_time),
                 deleted_at: Set(m.deleted_at),
+                version: Set(m.version),
             })
             .collect();
         note::Entity::insert_many(models).exec(txn).await?;
@@ -160,11 +183,16 @@ pub(super) async fn restore_data(
                 operate_source: Set(m.operate_source),
                 operate_time: Set(m.operate_time),
                 create_time: Set(m.create_time),
+                content_format: Set(0),
+                base_id: Set(0),
             })
             .collect();
         note_history::Entity::insert_many(models).exec(txn).await?;
     }
 
+    // 备份中的历史记录为完整文本，导入后重新编码为增量格式
+    history_storage::repack(txn, None).await?;
+
     Ok(())
 }
 
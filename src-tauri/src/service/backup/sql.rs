@@ -3,8 +3,9 @@ use std::io::{BufWriter, Write};
 use sea_orm::*;
 use tracing::info;
 
-use super::{BATCH_SIZE, DT_FMT, clear_tables, escape_sql, format_dt};
-use crate::entity::{note, note_history, note_tags, notebook, tag};
+use super::{BATCH_SIZE, DT_FMT, clear_tables, escape_sql, format_dt, note_history_export_query};
+use crate::entity::{note, note_tags, notebook, tag};
+use crate::service::history_storage::{self, HistoryDecoder};
 
 pub async fn export_sql(db: &DatabaseConnection, path: &str) -> anyhow::Result<()> {
     let file = std::fs::File::create(path)?;
@@ -37,8 +38,9 @@ pub async fn export_sql(db: &DatabaseConnection, path: &str) -> anyhow::Result<(
     for m in tag::Entity::find().all(db).await? {
         writeln!(
             w,
-            "INSERT INTO tag (id, name, icon, cls, sort_order, create_time, update_time) VALUES ({}, {}, {}, {}, {}, {}, {});",
+            "INSERT INTO tag (id, parent_id, name, icon, cls, sort_order, create_time, update_time) VALUES ({}, {}, {}, {}, {}, {}, {}, {});",
             m.id,
+            m.parent_id,
             escape_sql(&m.name),
             escape_sql(&m.icon),
             escape_sql(&m.cls),
@@ -90,9 +92,11 @@ pub async fn export_sql(db: &DatabaseConnection, path: &str) -> anyhow::Result<(
 
     // note_history（大表，分页流式写入）
     writeln!(w, "-- Table: note_history")?;
-    let mut paginator = note_history::Entity::find().paginate(db, BATCH_SIZE);
+    let mut paginator = note_history_export_query().paginate(db, BATCH_SIZE);
+    let mut decoder = HistoryDecoder::default();
     while let Some(batch) = paginator.fetch_and_next().await? {
-        for m in &batch {
+        for m in batch {
+            let m = decoder.decode(db, m).await?;
             writeln!(
                 w,
                 "INSERT INTO note_history (id, note_id, old_content, new_content, extra, operate_type, operate_time, create_time) VALUES ({}, {}, {}, {}, {}, {}, {}, {});",
@@ -136,6 +140,9 @@ pub async fn import_sql(db: &DatabaseConnection, path: &str) -> anyhow::Result<(
             })?;
     }
 
+    // 备份中的历史记录为完整文本，导入后重新编码为增量格式
+    history_storage::repack(&txn, None).await?;
+
     txn.commit().await?;
     info!("SQL backup import completed: {}", path);
     Ok(())
@@ -33,6 +33,22 @@ impl EnoteServerClient {
         self.delete(&format!("/api/notebooks/{}", id)).await
     }
 
+    pub async fn find_notebook_tree(&self) -> Result<Vec<NotebookTreeNode>, AppError> {
+        self.get("/api/notebooks/tree").await
+    }
+
+    pub async fn move_notebook(
+        &self,
+        id: i64,
+        parent_id: i64,
+    ) -> Result<Option<Notebook>, AppError> {
+        self.put(
+            &format!("/api/notebooks/{}/parent", id),
+            &serde_json::json!({ "parentId": parent_id }),
+        )
+        .await
+    }
+
     pub async fn reorder_notebooks(&self, orders: &[(i64, i32)]) -> Result<(), AppError> {
         self.put_void("/api/notebooks/reorder", &orders).await
     }
@@ -59,6 +75,49 @@ impl EnoteServerClient {
         self.delete(&format!("/api/tags/{}", id)).await
     }
 
+    pub async fn find_tag_tree(&self) -> Result<Vec<TagTreeNode>, AppError> {
+        self.get("/api/tags/tree").await
+    }
+
+    pub async fn move_tag(&self, id: i64, parent_id: i64) -> Result<Option<Tag>, AppError> {
+        self.put(
+            &format!("/api/tags/{}/parent", id),
+            &serde_json::json!({ "parentId": parent_id }),
+        )
+        .await
+    }
+
+    pub async fn merge_tags(&self, source_ids: &[i64], target_id: i64) -> Result<u64, AppError> {
+        self.post(
+            "/api/tags/merge",
+            &serde_json::json!({
+                "sourceIds": source_ids,
+                "targetId": target_id,
+            }),
+        )
+        .await
+    }
+
+    pub async fn add_tag_to_notes(&self, note_ids: &[i64], tag_id: i64) -> Result<u64, AppError> {
+        self.post(
+            &format!("/api/tags/{}/batch-add", tag_id),
+            &serde_json::json!({ "noteIds": note_ids }),
+        )
+        .await
+    }
+
+    pub async fn remove_tag_from_notes(
+        &self,
+        note_ids: &[i64],
+        tag_id: i64,
+    ) -> Result<u64, AppError> {
+        self.post(
+            &format!("/api/tags/{}/batch-remove", tag_id),
+            &serde_json::json!({ "noteIds": note_ids }),
+        )
+        .await
+    }
+
     pub async fn reorder_tags(&self, orders: &[(i64, i32)]) -> Result<(), AppError> {
         self.put_void("/api/tags/reorder", &orders).await
     }
@@ -113,6 +172,14 @@ impl EnoteServerClient {
         self.delete("/api/notes/trash").await
     }
 
+    pub async fn purge_trash(&self, dry_run: bool) -> Result<TrashPurgeResult, AppError> {
+        self.post(
+            "/api/notes/trash/purge",
+            &serde_json::json!({ "dryRun": dry_run }),
+        )
+        .await
+    }
+
     pub async fn batch_move_notes(
         &self,
         note_ids: &[i64],
@@ -135,6 +202,58 @@ impl EnoteServerClient {
         )
         .await
     }
+
+    pub async fn batch_set_note_star(
+        &self,
+        note_ids: &[i64],
+        starred: bool,
+    ) -> Result<Vec<BatchNoteResult>, AppError> {
+        self.post(
+            "/api/notes/batch-star",
+            &serde_json::json!({
+                "noteIds": note_ids,
+                "starred": starred,
+            }),
+        )
+        .await
+    }
+
+    pub async fn batch_set_note_pin(
+        &self,
+        note_ids: &[i64],
+        pinned: bool,
+    ) -> Result<Vec<BatchNoteResult>, AppError> {
+        self.post(
+            "/api/notes/batch-pin",
+            &serde_json::json!({
+                "noteIds": note_ids,
+                "pinned": pinned,
+            }),
+        )
+        .await
+    }
+
+    pub async fn batch_restore_notes(
+        &self,
+        note_ids: &[i64],
+    ) -> Result<Vec<BatchNoteResult>, AppError> {
+        self.post(
+            "/api/notes/batch-restore",
+            &serde_json::json!({ "noteIds": note_ids }),
+        )
+        .await
+    }
+
+    pub async fn batch_permanent_delete_notes(
+        &self,
+        note_ids: &[i64],
+    ) -> Result<Vec<BatchNoteResult>, AppError> {
+        self.post(
+            "/api/notes/batch-permanent-delete",
+            &serde_json::json!({ "noteIds": note_ids }),
+        )
+        .await
+    }
 }
 
 // ============================================================================
@@ -149,6 +268,13 @@ impl EnoteServerClient {
         self.post("/api/notes/search", param).await
     }
 
+    pub async fn search_cursor_notes(
+        &self,
+        param: &NoteCursorSearchParam,
+    ) -> Result<CursorPageResult<Note>, AppError> {
+        self.post("/api/notes/search-cursor", param).await
+    }
+
     pub async fn note_stats(
         &self,
         param: &NoteSearchPageParam,
@@ -175,6 +301,33 @@ impl EnoteServerClient {
     ) -> Result<PageResult<NoteHistory>, AppError> {
         self.post("/api/note-histories/search", param).await
     }
+
+    pub async fn search_note_history_content(
+        &self,
+        param: &NoteHistoryContentSearchParam,
+    ) -> Result<PageResult<NoteHistoryHit>, AppError> {
+        self.post("/api/note-histories/search-content", param).await
+    }
+
+    pub async fn diff_note_revisions(
+        &self,
+        param: &NoteRevisionDiffParam,
+    ) -> Result<NoteRevisionDiff, AppError> {
+        self.post("/api/note-histories/diff", param).await
+    }
+
+    pub async fn restore_note_revision(
+        &self,
+        note_id: i64,
+        history_id: i64,
+        version: i64,
+    ) -> Result<Note, AppError> {
+        self.put(
+            &format!("/api/notes/{}/restore-revision", note_id),
+            &serde_json::json!({ "historyId": history_id, "version": version }),
+        )
+        .await
+    }
 }
 
 // ============================================================================
@@ -220,6 +373,91 @@ impl EnoteServerClient {
     }
 }
 
+// ============================================================================
+// Saved Search
+// ============================================================================
+
+impl EnoteServerClient {
+    pub async fn find_all_saved_searches(&self) -> Result<Vec<SavedSearch>, AppError> {
+        self.get("/api/saved-searches").await
+    }
+
+    pub async fn create_saved_search(
+        &self,
+        saved_search: &SavedSearch,
+    ) -> Result<Option<SavedSearch>, AppError> {
+        self.post("/api/saved-searches", saved_search).await
+    }
+
+    pub async fn update_saved_search(
+        &self,
+        saved_search: &SavedSearch,
+    ) -> Result<Option<SavedSearch>, AppError> {
+        self.put(
+            &format!("/api/saved-searches/{}", saved_search.id),
+            saved_search,
+        )
+        .await
+    }
+
+    pub async fn delete_saved_search_by_id(&self, id: i64) -> Result<(), AppError> {
+        self.delete(&format!("/api/saved-searches/{}", id)).await
+    }
+
+    pub async fn run_saved_search(
+        &self,
+        id: i64,
+        page_param: &PageParam,
+    ) -> Result<PageResult<Note>, AppError> {
+        self.post(&format!("/api/saved-searches/{}/run", id), page_param)
+            .await
+    }
+}
+
+// ============================================================================
+// Note Rule
+// ============================================================================
+
+impl EnoteServerClient {
+    pub async fn find_all_note_rules(&self) -> Result<Vec<NoteRule>, AppError> {
+        self.get("/api/note-rules").await
+    }
+
+    pub async fn create_note_rule(
+        &self,
+        note_rule: &NoteRule,
+    ) -> Result<Option<NoteRule>, AppError> {
+        self.post("/api/note-rules", note_rule).await
+    }
+
+    pub async fn update_note_rule(
+        &self,
+        note_rule: &NoteRule,
+    ) -> Result<Option<NoteRule>, AppError> {
+        self.put(&format!("/api/note-rules/{}", note_rule.id), note_rule)
+            .await
+    }
+
+    pub async fn delete_note_rule_by_id(&self, id: i64) -> Result<(), AppError> {
+        self.delete(&format!("/api/note-rules/{}", id)).await
+    }
+
+    pub async fn run_note_rules(
+        &self,
+        rule_ids: &[i64],
+        dry_run: bool,
+    ) -> Result<NoteRuleRunResult, AppError> {
+        self.post(
+            "/api/note-rules/run",
+            &serde_json::json!({
+                "ruleIds": rule_ids,
+                "dryRun": dry_run,
+            }),
+        )
+        .await
+    }
+}
+
 // ============================================================================
 // Note Link
 // ============================================================================
@@ -229,16 +467,27 @@ impl EnoteServerClient {
         self.get(&format!("/api/notes/{}/links", note_id)).await
     }
 
+    pub async fn find_note_outgoing_links(&self, note_id: i64) -> Result<Vec<NoteLink>, AppError> {
+        self.get(&format!("/api/notes/{}/links/outgoing", note_id))
+            .await
+    }
+
+    pub async fn find_note_backlinks(&self, note_id: i64) -> Result<Vec<NoteLink>, AppError> {
+        self.get(&format!("/api/notes/{}/backlinks", note_id)).await
+    }
+
     pub async fn create_note_link(
         &self,
         source_note_id: i64,
         target_note_id: i64,
+        relation: &str,
     ) -> Result<(), AppError> {
         self.post_void(
             "/api/note-links",
             &serde_json::json!({
                 "sourceNoteId": source_note_id,
                 "targetNoteId": target_note_id,
+                "relation": relation,
             }),
         )
         .await
@@ -262,6 +511,49 @@ impl EnoteServerClient {
         )
         .await
     }
+
+    pub async fn check_note_link_integrity(
+        &self,
+        repair: bool,
+    ) -> Result<LinkIntegrityReport, AppError> {
+        self.post(
+            "/api/note-links/integrity",
+            &serde_json::json!({ "repair": repair }),
+        )
+        .await
+    }
+}
+
+// ============================================================================
+// Note Graph
+// ============================================================================
+
+impl EnoteServerClient {
+    pub async fn get_note_graph(&self, query: &NoteGraphQuery) -> Result<NoteGraph, AppError> {
+        self.post("/api/note-graph", query).await
+    }
+
+    pub async fn get_note_neighbourhood(
+        &self,
+        note_id: i64,
+        hops: u32,
+    ) -> Result<NoteGraph, AppError> {
+        self.post(
+            "/api/note-graph/neighbourhood",
+            &serde_json::json!({
+                "noteId": note_id,
+                "hops": hops,
+            }),
+        )
+        .await
+    }
+
+    pub async fn find_orphan_notes(
+        &self,
+        query: &NoteGraphQuery,
+    ) -> Result<Vec<GraphNode>, AppError> {
+        self.post("/api/note-graph/orphans", query).await
+    }
 }
 
 // ============================================================================
@@ -385,4 +677,17 @@ impl EnoteServerClient {
         self.post("/api/attachments/cleanup", &serde_json::json!({}))
             .await
     }
+
+    pub async fn rebuild_attachment_text_index(&self) -> Result<u32, AppError> {
+        self.post(
+            "/api/attachments/text-index/rebuild",
+            &serde_json::json!({}),
+        )
+        .await
+    }
+
+    pub async fn compact_note_history(&self) -> Result<HistoryCompactResult, AppError> {
+        self.post("/api/note-histories/compact", &serde_json::json!({}))
+            .await
+    }
 }
@@ -20,6 +20,16 @@ pub fn map_http_error(status: reqwest::StatusCode, body: &str) -> AppError {
         401 => AppError::code("SERVER_AUTH_FAILED"),
         403 => AppError::code("SERVER_FORBIDDEN"),
         404 => AppError::code("SERVER_NOT_FOUND"),
+        409 => {
+            // 乐观并发冲突，服务端在 args 中返回当前版本
+            match serde_json::from_str::<ServerErrorResponse>(body) {
+                Ok(err) => AppError::BusinessCode {
+                    code: err.code,
+                    args: err.args.unwrap_or_default(),
+                },
+                Err(_) => AppError::code("NOTE_VERSION_CONFLICT"),
+            }
+        }
         422 => {
             // 尝试解析服务端业务错误
             if let Ok(err) = serde_json::from_str::<ServerErrorResponse>(body) {
@@ -0,0 +1,139 @@
+//! 层级结构工具模块
+//!
+//! 笔记本与标签均以 `parent_id` 组织为树（0 表示顶级），本模块提供二者共用的纯函数：
+//! 子节点分组、子树展开、父级优先排序与组装树。
+//!
+//! 所有函数以 (id, parent_id) 列表为输入；父级不存在的节点按顶级处理，
+//! 父子关系成环时通过已访问集合保证遍历结束。
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// 按父级分组的子节点 ID（保持输入顺序）
+///
+/// `pairs` 为 (id, parent_id)；父级不存在的节点归入顶级（0）
+pub fn children_map(pairs: &[(i64, i64)]) -> HashMap<i64, Vec<i64>> {
+    let ids: HashSet<i64> = pairs.iter().map(|(id, _)| *id).collect();
+    let mut children: HashMap<i64, Vec<i64>> = HashMap::new();
+    for (id, parent_id) in pairs {
+        let parent_id = if ids.contains(parent_id) {
+            *parent_id
+        } else {
+            0
+        };
+        children.entry(parent_id).or_default().push(*id);
+    }
+    children
+}
+
+/// 指定节点及其全部子孙节点的 ID（广度优先，含自身）
+pub fn subtree_ids(pairs: &[(i64, i64)], root_id: i64) -> Vec<i64> {
+    let children = children_map(pairs);
+    let mut visited = HashSet::from([root_id]);
+    let mut result = vec![root_id];
+    let mut queue = VecDeque::from([root_id]);
+    while let Some(id) = queue.pop_front() {
+        for child in children.get(&id).into_iter().flatten() {
+            if visited.insert(*child) {
+                result.push(*child);
+                queue.push_back(*child);
+            }
+        }
+    }
+    result
+}
+
+/// 按层级排序：父节点总在子节点之前
+///
+/// 父子关系成环的节点排在最后（其父级在插入时尚不存在，按顶级处理）
+pub fn parents_first(pairs: &[(i64, i64)]) -> Vec<i64> {
+    let mut order = subtree_ids(pairs, 0);
+    order.remove(0);
+    let placed: HashSet<i64> = order.iter().copied().collect();
+    order.extend(
+        pairs
+            .iter()
+            .map(|(id, _)| *id)
+            .filter(|id| !placed.contains(id)),
+    );
+    order
+}
+
+/// 将有序的节点列表组装为树，同级保持输入顺序
+///
+/// - `key`: 取节点的 (id, parent_id)
+/// - `node`: 由节点数据与其子节点构造树节点
+///
+/// 父子关系成环、无法从顶级到达的节点作为顶级返回
+pub fn build_tree<T, N>(
+    items: Vec<T>,
+    key: impl Fn(&T) -> (i64, i64),
+    node: impl Fn(T, Vec<N>) -> N,
+) -> Vec<N> {
+    fn build<T, N>(
+        id: i64,
+        items: &mut HashMap<i64, T>,
+        children: &HashMap<i64, Vec<i64>>,
+        node: &dyn Fn(T, Vec<N>) -> N,
+    ) -> Option<N> {
+        let item = items.remove(&id)?;
+        let child_nodes = children
+            .get(&id)
+            .into_iter()
+            .flatten()
+            .filter_map(|child| build(*child, items, children, node))
+            .collect();
+        Some(node(item, child_nodes))
+    }
+
+    let pairs: Vec<(i64, i64)> = items.iter().map(&key).collect();
+    let children = children_map(&pairs);
+    let mut items: HashMap<i64, T> = items.into_iter().map(|t| (key(&t).0, t)).collect();
+
+    let mut roots: Vec<N> = children
+        .get(&0)
+        .into_iter()
+        .flatten()
+        .filter_map(|id| build(*id, &mut items, &children, &node))
+        .collect();
+    for (id, _) in &pairs {
+        if let Some(n) = build(*id, &mut items, &children, &node) {
+            roots.push(n);
+        }
+    }
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Node {
+        id: i64,
+        children: Vec<Node>,
+    }
+
+    fn ids(nodes: &[Node]) -> Vec<i64> {
+        nodes.iter().map(|n| n.id).collect()
+    }
+
+    #[test]
+    fn subtree_and_parent_order() {
+        // 1 ─ 2 ─ 4, 1 ─ 3; 5 的父级不存在; 6 与 7 互为父级
+        let pairs = [(4, 2), (2, 1), (3, 1), (1, 0), (5, 99), (6, 7), (7, 6)];
+        assert_eq!(subtree_ids(&pairs, 1), vec![1, 2, 3, 4]);
+        assert_eq!(subtree_ids(&pairs, 2), vec![2, 4]);
+        assert_eq!(subtree_ids(&pairs, 6), vec![6, 7]);
+        assert_eq!(parents_first(&pairs), vec![1, 5, 2, 3, 4, 6, 7]);
+    }
+
+    #[test]
+    fn builds_tree_keeping_sibling_order() {
+        let pairs = vec![(3, 1), (1, 0), (2, 1), (4, 2), (5, 99), (6, 7), (7, 6)];
+        let tree = build_tree(pairs, |p| *p, |(id, _), children| Node { id, children });
+        assert_eq!(ids(&tree), vec![1, 5, 6]);
+        assert_eq!(ids(&tree[0].children), vec![3, 2]);
+        assert_eq!(ids(&tree[0].children[1].children), vec![4]);
+        assert_eq!(ids(&tree[2].children), vec![7]);
+    }
+}
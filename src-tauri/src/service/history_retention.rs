@@ -0,0 +1,315 @@
+//! 历史记录保留策略服务
+//!
+//! 每次更新笔记都会写入一条完整的历史记录，频繁编辑的笔记与 MCP 批量修改会让
+//! `note_history` 表持续增长。本模块按保留策略精简历史记录：
+//!
+//! - 最近 `keep_all_days` 天内的记录全部保留
+//! - 之后到 `keep_daily_days` 天内，每篇笔记每天保留最新的一条
+//! - 之后到 `keep_weekly_days` 天内（0 表示不限），每篇笔记每周保留最新的一条
+//! - 超出以上范围的记录被删除
+//!
+//! 创建与删除记录分别保存笔记的初始内容与删除前内容，始终保留，只精简更新与恢复记录。
+//! 策略保存在设置（KV）中，可在自动备份完成后执行一次精简。
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Result;
+use chrono::{Datelike, Duration, Local, NaiveDate, NaiveDateTime};
+use sea_orm::{
+    ConnectionTrait, DatabaseBackend, DatabaseConnection, EntityTrait, QuerySelect, Statement,
+    TransactionTrait,
+};
+use tracing::info;
+
+use crate::entity;
+use crate::model::{HistoryCompactResult, OperationType};
+use crate::service::{history_storage, settings};
+
+/// 历史记录保留策略
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    /// 全部保留的天数
+    pub keep_all_days: i64,
+    /// 按天保留的截止天数
+    pub keep_daily_days: i64,
+    /// 按周保留的截止天数（0 表示不限）
+    pub keep_weekly_days: i64,
+    /// 自动备份完成后是否执行精简
+    pub compact_after_backup: bool,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            keep_all_days: 7,
+            keep_daily_days: 90,
+            keep_weekly_days: 0,
+            compact_after_backup: false,
+        }
+    }
+}
+
+impl RetentionPolicy {
+    /// 从设置中读取策略，缺失或非法（负数）时使用默认值
+    ///
+    /// 各阶段的截止天数不会小于前一阶段
+    pub fn from_settings(settings: &HashMap<String, String>) -> Self {
+        let defaults = Self::default();
+        let read = |key: &str, default: i64| {
+            settings
+                .get(key)
+                .and_then(|v| v.trim().parse::<i64>().ok())
+                .filter(|v| *v >= 0)
+                .unwrap_or(default)
+        };
+        let keep_all_days = read("historyKeepAllDays", defaults.keep_all_days);
+        let keep_daily_days =
+            read("historyKeepDailyDays", defaults.keep_daily_days).max(keep_all_days);
+        let keep_weekly_days = match read("historyKeepWeeklyDays", defaults.keep_weekly_days) {
+            0 => 0,
+            days => days.max(keep_daily_days),
+        };
+        Self {
+            keep_all_days,
+            keep_daily_days,
+            keep_weekly_days,
+            compact_after_backup: settings
+                .get("historyCompactAfterBackup")
+                .is_some_and(|v| v == "1"),
+        }
+    }
+}
+
+/// 精简时需要的历史记录信息（不含内容）
+#[derive(Clone, Debug)]
+struct HistoryEntry {
+    id: i64,
+    note_id: i64,
+    operate_type: i32,
+    operate_time: NaiveDateTime,
+}
+
+/// 保留分组：同一笔记同一分组只保留最新的一条
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Bucket {
+    Day(NaiveDate),
+    Week(i32, u32),
+}
+
+/// 按策略选出需要删除的记录 ID
+fn select_removals(
+    entries: &mut [HistoryEntry],
+    policy: &RetentionPolicy,
+    now: NaiveDateTime,
+) -> Vec<i64> {
+    // 同一笔记内按时间从新到旧，每个分组先遇到的即为最新的一条
+    entries.sort_by(|a, b| {
+        a.note_id
+            .cmp(&b.note_id)
+            .then(b.operate_time.cmp(&a.operate_time))
+            .then(b.id.cmp(&a.id))
+    });
+
+    let mut seen = HashSet::new();
+    let mut removals = Vec::new();
+    for entry in entries.iter() {
+        if entry.operate_type == OperationType::Create.as_i32()
+            || entry.operate_type == OperationType::Delete.as_i32()
+        {
+            continue;
+        }
+        let age = now - entry.operate_time;
+        if age < Duration::days(policy.keep_all_days) {
+            continue;
+        }
+        let date = entry.operate_time.date();
+        let bucket = if age < Duration::days(policy.keep_daily_days) {
+            Bucket::Day(date)
+        } else if policy.keep_weekly_days == 0 || age < Duration::days(policy.keep_weekly_days) {
+            let week = date.iso_week();
+            Bucket::Week(week.year(), week.week())
+        } else {
+            removals.push(entry.id);
+            continue;
+        };
+        if !seen.insert((entry.note_id, bucket)) {
+            removals.push(entry.id);
+        }
+    }
+    removals
+}
+
+/// 当前数据库占用的存储字节数
+///
+/// SQLite 为数据库文件大小；PostgreSQL 为当前数据库大小；MySQL 为当前库所有表的数据与索引大小
+async fn database_size(db: &DatabaseConnection) -> Result<u64> {
+    let backend = db.get_database_backend();
+    let sql = match backend {
+        DatabaseBackend::Sqlite => {
+            "SELECT page_count * page_size AS size FROM pragma_page_count(), pragma_page_size()"
+        }
+        DatabaseBackend::Postgres => {
+            "SELECT CAST(pg_database_size(current_database()) AS BIGINT) AS size"
+        }
+        _ => {
+            "SELECT CAST(COALESCE(SUM(data_length + index_length), 0) AS SIGNED) AS size \
+             FROM information_schema.tables WHERE table_schema = DATABASE()"
+        }
+    };
+    let size = match db
+        .query_one_raw(Statement::from_string(backend, sql.to_owned()))
+        .await?
+    {
+        Some(row) => row.try_get::<i64>("", "size")?,
+        None => 0,
+    };
+    Ok(size.max(0) as u64)
+}
+
+/// 按策略精简历史记录
+///
+/// SQLite 删除后执行 VACUUM 回收文件空间；返回的释放字节数为精简前后数据库存储大小之差，
+/// 未回收文件空间的数据库（如 PostgreSQL 未执行 VACUUM FULL）可能为 0
+pub async fn compact(
+    db: &DatabaseConnection,
+    policy: &RetentionPolicy,
+) -> Result<HistoryCompactResult> {
+    let backend = db.get_database_backend();
+
+    let mut entries: Vec<HistoryEntry> = entity::note_history::Entity::find()
+        .select_only()
+        .column(entity::note_history::Column::Id)
+        .column(entity::note_history::Column::NoteId)
+        .column(entity::note_history::Column::OperateType)
+        .column(entity::note_history::Column::OperateTime)
+        .into_tuple::<(i64, i64, i32, NaiveDateTime)>()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|(id, note_id, operate_type, operate_time)| HistoryEntry {
+            id,
+            note_id,
+            operate_type,
+            operate_time,
+        })
+        .collect();
+
+    let scanned = entries.len() as u64;
+    let removals = select_removals(&mut entries, policy, Local::now().naive_local());
+    let removed: HashSet<i64> = removals.iter().copied().collect();
+    let mut freed_bytes = 0;
+
+    if !removals.is_empty() {
+        let size_before = database_size(db).await?;
+
+        // 删除后需重新编码同一笔记的其余记录，以免增量链断开
+        let mut by_note: HashMap<i64, HashSet<i64>> = HashMap::new();
+        for entry in entries.iter().filter(|e| removed.contains(&e.id)) {
+            by_note.entry(entry.note_id).or_default().insert(entry.id);
+        }
+        let txn = db.begin().await?;
+        for (note_id, ids) in &by_note {
+            history_storage::rewrite_note(&txn, *note_id, ids).await?;
+        }
+        txn.commit().await?;
+
+        // VACUUM 不能在事务中执行
+        if backend == DatabaseBackend::Sqlite {
+            db.execute_unprepared("VACUUM").await?;
+        }
+
+        freed_bytes = size_before.saturating_sub(database_size(db).await?);
+    }
+
+    info!(
+        "Note history compacted: scanned={}, deleted={}, freed={} bytes",
+        scanned,
+        removals.len(),
+        freed_bytes
+    );
+
+    Ok(HistoryCompactResult {
+        scanned,
+        deleted: removals.len() as u64,
+        freed_bytes,
+    })
+}
+
+/// 按设置中保存的策略精简历史记录
+pub async fn compact_with_settings(db: &DatabaseConnection) -> Result<HistoryCompactResult> {
+    let policy = RetentionPolicy::from_settings(&settings::get_all(db).await?);
+    compact(db, &policy).await
+}
+
+/// 自动备份完成后按设置执行精简，未启用时返回 None
+pub async fn compact_after_backup(db: &DatabaseConnection) -> Result<Option<HistoryCompactResult>> {
+    let policy = RetentionPolicy::from_settings(&settings::get_all(db).await?);
+    if !policy.compact_after_backup {
+        return Ok(None);
+    }
+    compact(db, &policy).await.map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: i64, operate_type: OperationType, time: &str) -> HistoryEntry {
+        HistoryEntry {
+            id,
+            note_id: 1,
+            operate_type: operate_type.as_i32(),
+            operate_time: NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M:%S").unwrap(),
+        }
+    }
+
+    #[test]
+    fn thins_by_day_then_week() {
+        let now =
+            NaiveDateTime::parse_from_str("2026-06-30 12:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        let policy = RetentionPolicy {
+            keep_weekly_days: 365,
+            ..Default::default()
+        };
+        let mut entries = vec![
+            // 7 天内：全部保留
+            entry(1, OperationType::Update, "2026-06-29 08:00:00"),
+            entry(2, OperationType::Update, "2026-06-29 09:00:00"),
+            // 按天：同一天只保留最新的一条
+            entry(3, OperationType::Update, "2026-06-01 08:00:00"),
+            entry(4, OperationType::Update, "2026-06-01 18:00:00"),
+            entry(5, OperationType::Restore, "2026-06-02 08:00:00"),
+            // 按周：2026-01-05 与 2026-01-07 同属第 2 周
+            entry(6, OperationType::Update, "2026-01-05 08:00:00"),
+            entry(7, OperationType::Update, "2026-01-07 08:00:00"),
+            // 超出保留范围
+            entry(8, OperationType::Update, "2025-01-07 08:00:00"),
+            // 创建与删除记录始终保留
+            entry(9, OperationType::Create, "2025-01-01 08:00:00"),
+            entry(10, OperationType::Delete, "2026-06-01 07:00:00"),
+        ];
+
+        let mut removals = select_removals(&mut entries, &policy, now);
+        removals.sort();
+        assert_eq!(removals, vec![3, 6, 8]);
+    }
+
+    #[test]
+    fn policy_from_settings() {
+        let settings = HashMap::from([
+            ("historyKeepAllDays".to_string(), "30".to_string()),
+            ("historyKeepDailyDays".to_string(), "10".to_string()),
+            ("historyKeepWeeklyDays".to_string(), "-1".to_string()),
+            ("historyCompactAfterBackup".to_string(), "1".to_string()),
+        ]);
+        assert_eq!(
+            RetentionPolicy::from_settings(&settings),
+            RetentionPolicy {
+                keep_all_days: 30,
+                keep_daily_days: 30,
+                keep_weekly_days: 0,
+                compact_after_backup: true,
+            }
+        );
+    }
+}
@@ -0,0 +1,554 @@
+//! 笔记历史内容存储格式
+//!
+//! 历史记录的 `old_content` / `new_content` 既可以保存完整文本，也可以保存相对基准文本的增量：
+//! - `old_content`：相对同一笔记上一条历史记录的 `new_content`（`base_id` 指向该记录）
+//! - `new_content`：相对本条记录的 `old_content`
+//!
+//! 每篇笔记最多连续 `SNAPSHOT_INTERVAL - 1` 条记录的 `old_content` 为增量，之后保存一次完整文本作为快照，
+//! 重建任意一条记录最多回溯 `SNAPSHOT_INTERVAL` 条。增量不比原文短时（如加密内容）直接保存原文。
+//!
+//! 增量是 JSON 数组：`[起始, 结束]` 复制基准文本的字节区间，字符串为插入的文本。
+//! 比较前文本按换行与 HTML 标签结束符切分。
+//!
+//! 读取历史内容统一经过 `decode` / `decode_all` / `HistoryDecoder`，写入经过 `encode`；
+//! 删除部分记录时需通过 `rewrite_note` 重新编码同一笔记的其余记录，以免增量链断开。
+
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, anyhow, bail};
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect, prelude::Expr,
+};
+use serde::{Deserialize, Serialize};
+use similar::{Algorithm, DiffTag, capture_diff_slices_deadline};
+use tracing::info;
+
+use crate::entity::note_history;
+
+/// `old_content` 为相对上一条记录 `new_content` 的增量
+pub const OLD_DELTA: i32 = 1;
+/// `new_content` 为相对本条记录 `old_content` 的增量
+pub const NEW_DELTA: i32 = 2;
+
+/// 增量链的最大长度（每隔多少条记录保存一次完整快照）
+const SNAPSHOT_INTERVAL: u64 = 16;
+
+/// 单次比较的时间上限，超时后得到的增量仍然正确，只是不够紧凑
+const DIFF_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// 重新编码时每批读取的记录数
+const REWRITE_BATCH_SIZE: u64 = 200;
+
+/// 单次删除的最大记录数（避免 IN 列表过长）
+const DELETE_CHUNK_SIZE: usize = 500;
+
+/// 回溯增量链的最大步数（防止损坏的数据形成环）
+const MAX_CHAIN_STEPS: usize = 1024;
+
+/// 增量操作
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+enum DeltaOp {
+    /// 复制基准文本的字节区间 `[start, end)`
+    Copy(usize, usize),
+    /// 插入文本
+    Insert(String),
+}
+
+/// 按换行与 HTML 标签结束符切分文本（分隔符保留在片段末尾）
+fn tokenize(text: &str) -> Vec<&str> {
+    text.split_inclusive(['\n', '>']).collect()
+}
+
+/// 生成从 `base` 到 `target` 的增量
+fn make_delta(base: &str, target: &str) -> String {
+    let old = tokenize(base);
+    let new = tokenize(target);
+
+    let mut offsets = Vec::with_capacity(old.len() + 1);
+    let mut offset = 0;
+    offsets.push(offset);
+    for token in &old {
+        offset += token.len();
+        offsets.push(offset);
+    }
+
+    let deadline = Instant::now() + DIFF_TIMEOUT;
+    let mut ops: Vec<DeltaOp> = Vec::new();
+    for op in capture_diff_slices_deadline(Algorithm::Myers, &old, &new, Some(deadline)) {
+        let (tag, old_range, new_range) = op.as_tag_tuple();
+        match tag {
+            DiffTag::Equal => {
+                let (start, end) = (offsets[old_range.start], offsets[old_range.end]);
+                match ops.last_mut() {
+                    Some(DeltaOp::Copy(_, last_end)) if *last_end == start => *last_end = end,
+                    _ => ops.push(DeltaOp::Copy(start, end)),
+                }
+            }
+            DiffTag::Delete => {}
+            DiffTag::Insert | DiffTag::Replace => {
+                let text = new[new_range].concat();
+                match ops.last_mut() {
+                    Some(DeltaOp::Insert(last)) => last.push_str(&text),
+                    _ => ops.push(DeltaOp::Insert(text)),
+                }
+            }
+        }
+    }
+
+    serde_json::to_string(&ops).unwrap_or_default()
+}
+
+/// 将增量应用到基准文本
+fn apply_delta(base: &str, delta: &str) -> Result<String> {
+    let ops: Vec<DeltaOp> = serde_json::from_str(delta)?;
+    let mut text = String::with_capacity(base.len());
+    for op in ops {
+        match op {
+            DeltaOp::Copy(start, end) => text.push_str(
+                base.get(start..end)
+                    .ok_or_else(|| anyhow!("Invalid history delta range: {}..{}", start, end))?,
+            ),
+            DeltaOp::Insert(insert) => text.push_str(&insert),
+        }
+    }
+    Ok(text)
+}
+
+/// 增量比原文短时返回增量
+fn encode_field(base: &str, target: &str) -> Option<String> {
+    if target.is_empty() {
+        return None;
+    }
+    let delta = make_delta(base, target);
+    (delta.len() < target.len()).then_some(delta)
+}
+
+/// 关键词在可能命中的笔记的存储内容中必然出现的片段（原文形式与 JSON 转义后的形式）
+///
+/// 增量只在切分边界处拼接基准文本与插入文本，插入文本按 JSON 字符串保存。关键词中不含切分符的
+/// 最长片段（关键词只由切分符组成时取第一个字符）在解码后内容中的每次出现，都位于同一笔记某条记录的
+/// 完整文本或插入文本之内，因此存储内容中从未出现过该片段（任一形式）的笔记无需解码
+pub fn keyword_stored_fragments(keyword: &str) -> Vec<String> {
+    let first_char = keyword.chars().next().map_or(0, char::len_utf8);
+    let fragment = keyword
+        .split(['\n', '>'])
+        .max_by_key(|piece| piece.len())
+        .filter(|piece| !piece.is_empty())
+        .unwrap_or(&keyword[..first_char]);
+    let escaped = serde_json::to_string(fragment).unwrap_or_default();
+    let escaped = escaped
+        .strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .unwrap_or(fragment);
+
+    let mut fragments = vec![fragment.to_string()];
+    if escaped != fragment {
+        fragments.push(escaped.to_string());
+    }
+    fragments
+}
+
+/// 编码后的历史内容
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EncodedContent {
+    pub old_content: String,
+    pub new_content: String,
+    pub content_format: i32,
+    pub base_id: i64,
+}
+
+/// 编码时的上一条记录
+struct Predecessor {
+    id: i64,
+    /// 重建后的 new_content
+    new_content: String,
+    /// 所在增量链的长度（快照为 0）
+    depth: u64,
+}
+
+/// 编码一条记录，返回编码结果与其增量链长度
+fn encode_with(
+    old_content: &str,
+    new_content: &str,
+    prev: Option<&Predecessor>,
+) -> (EncodedContent, u64) {
+    let mut encoded = EncodedContent {
+        old_content: old_content.to_string(),
+        new_content: new_content.to_string(),
+        content_format: 0,
+        base_id: 0,
+    };
+    let mut depth = 0;
+
+    if let Some(delta) = encode_field(old_content, new_content) {
+        encoded.new_content = delta;
+        encoded.content_format |= NEW_DELTA;
+    }
+    if let Some(prev) = prev.filter(|p| p.depth + 1 < SNAPSHOT_INTERVAL)
+        && let Some(delta) = encode_field(&prev.new_content, old_content)
+    {
+        encoded.old_content = delta;
+        encoded.content_format |= OLD_DELTA;
+        encoded.base_id = prev.id;
+        depth = prev.depth + 1;
+    }
+
+    (encoded, depth)
+}
+
+/// 按存储格式重建记录的完整内容，返回 (old_content, new_content)
+///
+/// `base` 为基准记录重建后的 new_content，仅 `old_content` 为增量时需要
+fn decode_with(model: &note_history::Model, base: Option<&str>) -> Result<(String, String)> {
+    let old_content = if model.content_format & OLD_DELTA != 0 {
+        let base = base.ok_or_else(|| anyhow!("Missing base revision: {}", model.base_id))?;
+        apply_delta(base, &model.old_content)?
+    } else {
+        model.old_content.clone()
+    };
+    let new_content = if model.content_format & NEW_DELTA != 0 {
+        apply_delta(&old_content, &model.new_content)?
+    } else {
+        model.new_content.clone()
+    };
+    Ok((old_content, new_content))
+}
+
+/// 重建指定记录的 new_content（沿 base_id 回溯到快照后依次应用增量）
+async fn load_base<C: ConnectionTrait>(db: &C, note_id: i64, base_id: i64) -> Result<String> {
+    // 增量链由同一笔记的连续记录组成，通常一次查询即可取全
+    let mut rows: HashMap<i64, note_history::Model> = note_history::Entity::find()
+        .filter(note_history::Column::NoteId.eq(note_id))
+        .filter(note_history::Column::Id.lte(base_id))
+        .order_by_desc(note_history::Column::Id)
+        .limit(SNAPSHOT_INTERVAL)
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|m| (m.id, m))
+        .collect();
+
+    let mut chain = Vec::new();
+    let mut id = base_id;
+    loop {
+        let row = match rows.remove(&id) {
+            Some(row) => row,
+            None => note_history::Entity::find_by_id(id)
+                .one(db)
+                .await?
+                .ok_or_else(|| anyhow!("Missing base revision: {}", id))?,
+        };
+        let next = (row.content_format & OLD_DELTA != 0).then_some(row.base_id);
+        chain.push(row);
+        match next {
+            Some(_) if chain.len() >= MAX_CHAIN_STEPS => {
+                bail!("History delta chain too long: {}", base_id)
+            }
+            Some(next) => id = next,
+            None => break,
+        }
+    }
+
+    let mut base = String::new();
+    for row in chain.iter().rev() {
+        base = decode_with(row, Some(&base))?.1;
+    }
+    Ok(base)
+}
+
+/// 历史记录解码器
+///
+/// 缓存上一条解码结果，按 (笔记, ID) 升序解码时每条记录都能直接复用上一条，无需回溯
+#[derive(Default)]
+pub struct HistoryDecoder {
+    last: Option<(i64, String)>,
+}
+
+impl HistoryDecoder {
+    /// 将记录还原为完整文本（`content_format` 与 `base_id` 置为 0）
+    pub async fn decode<C: ConnectionTrait>(
+        &mut self,
+        db: &C,
+        mut model: note_history::Model,
+    ) -> Result<note_history::Model> {
+        let base = if model.content_format & OLD_DELTA != 0 {
+            match self.last.take() {
+                Some((id, content)) if id == model.base_id => Some(content),
+                _ => Some(load_base(db, model.note_id, model.base_id).await?),
+            }
+        } else {
+            None
+        };
+
+        let (old_content, new_content) = decode_with(&model, base.as_deref())?;
+        self.last = Some((model.id, new_content.clone()));
+        model.old_content = old_content;
+        model.new_content = new_content;
+        model.content_format = 0;
+        model.base_id = 0;
+        Ok(model)
+    }
+}
+
+/// 将单条记录还原为完整文本
+pub async fn decode<C: ConnectionTrait>(
+    db: &C,
+    model: note_history::Model,
+) -> Result<note_history::Model> {
+    HistoryDecoder::default().decode(db, model).await
+}
+
+/// 将多条记录还原为完整文本，结果保持原有顺序
+pub async fn decode_all<C: ConnectionTrait>(
+    db: &C,
+    models: Vec<note_history::Model>,
+) -> Result<Vec<note_history::Model>> {
+    let mut indexed: Vec<(usize, note_history::Model)> = models.into_iter().enumerate().collect();
+    indexed.sort_by_key(|(_, m)| (m.note_id, m.id));
+
+    let mut decoder = HistoryDecoder::default();
+    let mut decoded = Vec::with_capacity(indexed.len());
+    for (index, model) in indexed {
+        decoded.push((index, decoder.decode(db, model).await?));
+    }
+    decoded.sort_by_key(|(index, _)| *index);
+    Ok(decoded.into_iter().map(|(_, m)| m).collect())
+}
+
+/// 为即将写入的历史记录编码内容
+///
+/// 以同一笔记的最新一条记录为基准；增量链已达上限或数据不连续时保存完整文本
+pub async fn encode<C: ConnectionTrait>(
+    db: &C,
+    note_id: i64,
+    old_content: &str,
+    new_content: &str,
+) -> Result<EncodedContent> {
+    let recent = note_history::Entity::find()
+        .filter(note_history::Column::NoteId.eq(note_id))
+        .order_by_desc(note_history::Column::Id)
+        .limit(SNAPSHOT_INTERVAL)
+        .all(db)
+        .await?;
+
+    // 最新记录所在增量链的长度：从最新一条向前数连续的增量记录，链须首尾相接并止于快照
+    let depth = recent
+        .iter()
+        .take_while(|m| m.content_format & OLD_DELTA != 0)
+        .count();
+    let linked = recent
+        .windows(2)
+        .take(depth)
+        .all(|pair| pair[0].base_id == pair[1].id);
+
+    let prev = match recent.first() {
+        Some(last) if depth < recent.len() && linked => {
+            let mut base = String::new();
+            for row in recent[..=depth].iter().rev() {
+                base = decode_with(row, Some(&base))?.1;
+            }
+            Some(Predecessor {
+                id: last.id,
+                new_content: base,
+                depth: depth as u64,
+            })
+        }
+        _ => None,
+    };
+
+    Ok(encode_with(old_content, new_content, prev.as_ref()).0)
+}
+
+/// 重新编码笔记的全部历史记录，同时删除 `remove` 中的记录
+///
+/// 按 ID 顺序分批处理，内存中只保留上一条记录；返回被改写的记录数
+async fn rewrite<C: ConnectionTrait>(db: &C, note_id: i64, remove: &HashSet<i64>) -> Result<u64> {
+    let mut decoder = HistoryDecoder::default();
+    let mut prev: Option<Predecessor> = None;
+    let mut rewritten = 0;
+    let mut last_id = i64::MIN;
+
+    loop {
+        let batch = note_history::Entity::find()
+            .filter(note_history::Column::NoteId.eq(note_id))
+            .filter(note_history::Column::Id.gt(last_id))
+            .order_by_asc(note_history::Column::Id)
+            .limit(REWRITE_BATCH_SIZE)
+            .all(db)
+            .await?;
+        let Some(last) = batch.last() else {
+            break;
+        };
+        last_id = last.id;
+
+        for stored in batch {
+            // 被删除的记录也要解码，后一条记录可能以它为基准
+            let decoded = decoder.decode(db, stored.clone()).await?;
+            if remove.contains(&stored.id) {
+                continue;
+            }
+
+            let (encoded, depth) =
+                encode_with(&decoded.old_content, &decoded.new_content, prev.as_ref());
+
+            let unchanged = encoded.content_format == stored.content_format
+                && encoded.base_id == stored.base_id
+                && encoded.old_content == stored.old_content
+                && encoded.new_content == stored.new_content;
+            if !unchanged {
+                note_history::Entity::update_many()
+                    .col_expr(
+                        note_history::Column::OldContent,
+                        Expr::value(encoded.old_content),
+                    )
+                    .col_expr(
+                        note_history::Column::NewContent,
+                        Expr::value(encoded.new_content),
+                    )
+                    .col_expr(
+                        note_history::Column::ContentFormat,
+                        Expr::value(encoded.content_format),
+                    )
+                    .col_expr(note_history::Column::BaseId, Expr::value(encoded.base_id))
+                    .filter(note_history::Column::Id.eq(stored.id))
+                    .exec(db)
+                    .await?;
+                rewritten += 1;
+            }
+
+            prev = Some(Predecessor {
+                id: stored.id,
+                new_content: decoded.new_content,
+                depth,
+            });
+        }
+    }
+
+    let remove: Vec<i64> = remove.iter().copied().collect();
+    for chunk in remove.chunks(DELETE_CHUNK_SIZE) {
+        note_history::Entity::delete_many()
+            .filter(note_history::Column::NoteId.eq(note_id))
+            .filter(note_history::Column::Id.is_in(chunk.to_vec()))
+            .exec(db)
+            .await?;
+    }
+
+    Ok(rewritten)
+}
+
+/// 删除笔记的部分历史记录，并重新编码其余记录
+pub async fn rewrite_note<C: ConnectionTrait>(
+    db: &C,
+    note_id: i64,
+    remove: &HashSet<i64>,
+) -> Result<u64> {
+    rewrite(db, note_id, remove).await
+}
+
+/// 历史记录涉及的全部笔记 ID
+async fn history_note_ids<C: ConnectionTrait>(db: &C) -> Result<Vec<i64>> {
+    Ok(note_history::Entity::find()
+        .select_only()
+        .column(note_history::Column::NoteId)
+        .distinct()
+        .into_tuple::<i64>()
+        .all(db)
+        .await?)
+}
+
+/// 将历史记录重新编码为增量格式（`note_ids` 为 None 时处理全部笔记）
+///
+/// 用于备份导入、跨 Profile 同步写入完整文本之后；返回被改写的记录数
+pub async fn repack<C: ConnectionTrait>(db: &C, note_ids: Option<&[i64]>) -> Result<u64> {
+    let note_ids = match note_ids {
+        Some(ids) => ids.to_vec(),
+        None => history_note_ids(db).await?,
+    };
+
+    let mut rewritten = 0;
+    for note_id in note_ids {
+        rewritten += rewrite(db, note_id, &HashSet::new()).await?;
+    }
+
+    info!("Note history repacked: {} revisions rewritten", rewritten);
+    Ok(rewritten)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delta_round_trip() {
+        let base = "<p>第一段</p><p>第二段</p>\n# 标题\n正文\n";
+        let target = "<p>第一段</p><p>修改后的第二段</p>\n# 标题\n正文\n新增一行\n";
+        let delta = make_delta(base, target);
+        assert_eq!(apply_delta(base, &delta).unwrap(), target);
+        assert_eq!(apply_delta("", &make_delta("", target)).unwrap(), target);
+        assert_eq!(apply_delta(base, &make_delta(base, "")).unwrap(), "");
+        assert!(apply_delta("short", "[[0,100]]").is_err());
+    }
+
+    #[test]
+    fn keyword_fragments_survive_delta_encoding() {
+        let base = "第一行\n<p>旧段落</p>\n";
+        let target = "第一行\n<p>新增\"关键词\"段落</p>\n";
+        let delta = make_delta(base, target);
+        assert_eq!(keyword_stored_fragments("关键词"), vec!["关键词"]);
+        assert!(delta.contains("关键词"));
+        // 含 JSON 转义字符时同时匹配转义后的形式
+        let fragments = keyword_stored_fragments("\"关键词\"");
+        assert_eq!(fragments, vec!["\"关键词\"", "\\\"关键词\\\""]);
+        assert!(delta.contains(&fragments[1]));
+        // 含切分符时取不含切分符的最长片段
+        assert_eq!(keyword_stored_fragments("</p>\n<p>新增"), vec!["新增"]);
+        assert_eq!(keyword_stored_fragments("第一行\n"), vec!["第一行"]);
+        assert_eq!(keyword_stored_fragments(">"), vec![">"]);
+    }
+
+    #[test]
+    fn encodes_against_predecessor() {
+        let line = "这是一段足够长的正文内容，用来确保增量比原文更短。\n";
+        let v1 = line.repeat(20);
+        let v2 = format!("{}最后一行\n", v1);
+        let v3 = format!("开头一行\n{}", v2);
+
+        let prev = Predecessor {
+            id: 7,
+            new_content: v2.clone(),
+            depth: 0,
+        };
+        let (encoded, depth) = encode_with(&v2, &v3, Some(&prev));
+        assert_eq!(encoded.content_format, OLD_DELTA | NEW_DELTA);
+        assert_eq!((encoded.base_id, depth), (7, 1));
+
+        let model = note_history::Model {
+            id: 8,
+            note_id: 1,
+            old_content: encoded.old_content,
+            new_content: encoded.new_content,
+            extra: String::new(),
+            operate_type: 2,
+            operate_source: 0,
+            operate_time: Default::default(),
+            create_time: Default::default(),
+            content_format: encoded.content_format,
+            base_id: encoded.base_id,
+        };
+        assert_eq!(decode_with(&model, Some(&v2)).unwrap(), (v2.clone(), v3));
+
+        // 增量链达到上限时保存完整快照；增量不更短时保存原文
+        let full = Predecessor {
+            depth: SNAPSHOT_INTERVAL - 1,
+            ..prev
+        };
+        let (encoded, depth) = encode_with(&v1, "x", Some(&full));
+        assert_eq!((encoded.content_format, encoded.base_id, depth), (0, 0, 0));
+        assert_eq!(
+            (encoded.old_content, encoded.new_content),
+            (v1, "x".to_string())
+        );
+    }
+}
@@ -3,13 +3,15 @@
 //! 实现笔记的三层访问控制权限解析：
 //! 1. 加密笔记 → 强制 Deny
 //! 2. note.mcp_access != Inherit → 使用笔记自身设置
-//! 3. 笔记的标签中，取所有非 Inherit 标签的最严格值
-//! 4. notebook.mcp_access != Inherit → 使用笔记本设置
+//! 3. 笔记的标签中，取所有非 Inherit 标签的最严格值（标签为 Inherit 时沿父标签向上查找）
+//! 4. notebook.mcp_access != Inherit → 使用笔记本设置（笔记本为 Inherit 时沿父笔记本向上查找）
 //! 5. 以上都是 Inherit → 默认 ReadWrite
 
 use std::collections::{HashMap, HashSet};
 
-use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter, Statement,
+};
 
 use crate::{
     entity,
@@ -22,12 +24,7 @@ pub async fn resolve_note_access(
     db: &DatabaseConnection,
     note_id: i64,
 ) -> anyhow::Result<McpPermission> {
-    let result = entity::note::Entity::find_by_id(note_id)
-        .find_also_related(entity::notebook::Entity)
-        .one(db)
-        .await?;
-
-    let Some((note_entity, notebook_opt)) = result else {
+    let Some(note_entity) = entity::note::Entity::find_by_id(note_id).one(db).await? else {
         anyhow::bail!("Note ID {} not found", note_id);
     };
 
@@ -44,27 +41,19 @@ pub async fn resolve_note_access(
 
     // 3. 标签权限（取最严格值）
     let tag_permission = resolve_tags_access(db, note_id).await?;
-    if let Some(perm) = tag_permission {
-        // 4. 与笔记本权限比较，取最严格值
-        if let Some(notebook) = &notebook_opt {
-            let nb_access = McpAccess::from(notebook.mcp_access);
-            if nb_access != McpAccess::Inherit {
-                return Ok(perm.stricter(mcp_access_to_permission(nb_access)));
-            }
-        }
-        return Ok(perm);
-    }
-
-    // 4. 笔记本设置
-    if let Some(notebook) = notebook_opt {
-        let nb_access = McpAccess::from(notebook.mcp_access);
-        if nb_access != McpAccess::Inherit {
-            return Ok(mcp_access_to_permission(nb_access));
-        }
-    }
 
-    // 5. 默认 ReadWrite
-    Ok(McpPermission::ReadWrite)
+    // 4. 笔记本设置（含继承自父笔记本的设置）
+    let nb_access = resolve_notebook_access(db, note_entity.notebook_id).await?;
+    let nb_permission =
+        (nb_access != McpAccess::Inherit).then(|| mcp_access_to_permission(nb_access));
+
+    // 标签与笔记本取最严格值，都未设置时默认 ReadWrite
+    Ok(match (tag_permission, nb_permission) {
+        (Some(tp), Some(np)) => tp.stricter(np),
+        (Some(tp), None) => tp,
+        (None, Some(np)) => np,
+        (None, None) => McpPermission::ReadWrite, // 5. 默认
+    })
 }
 
 /// 批量解析笔记的有效 MCP 权限
@@ -80,25 +69,9 @@ pub async fn resolve_notes_access(
         return Ok(result);
     }
 
-    // 收集需要查询的笔记本 ID
-    let notebook_ids: HashSet<i64> = notes
-        .iter()
-        .filter(|n| n.notebook_id > 0)
-        .map(|n| n.notebook_id)
-        .collect();
-
-    // 批量查询笔记本的 mcp_access
-    let notebook_access: HashMap<i64, i32> = if !notebook_ids.is_empty() {
-        entity::notebook::Entity::find()
-            .filter(entity::notebook::Column::Id.is_in(notebook_ids))
-            .all(db)
-            .await?
-            .into_iter()
-            .map(|nb| (nb.id, nb.mcp_access))
-            .collect()
-    } else {
-        HashMap::new()
-    };
+    // 查询笔记所属笔记本的有效 mcp_access（含继承自父笔记本的设置）
+    let notebook_ids: Vec<i64> = notes.iter().map(|n| n.notebook_id).collect();
+    let notebook_access = effective_access(&notebook_hierarchy(db, &notebook_ids).await?);
 
     // 收集所有笔记 ID
     let note_ids: Vec<i64> = notes.iter().map(|n| n.id).collect();
@@ -109,21 +82,9 @@ pub async fn resolve_notes_access(
         .all(db)
         .await?;
 
-    // 收集需要查询的标签 ID
-    let tag_ids: HashSet<i64> = note_tags.iter().map(|nt| nt.tag_id).collect();
-
-    // 批量查询标签的 mcp_access
-    let tag_access: HashMap<i64, i32> = if !tag_ids.is_empty() {
-        entity::tag::Entity::find()
-            .filter(entity::tag::Column::Id.is_in(tag_ids))
-            .all(db)
-            .await?
-            .into_iter()
-            .map(|t| (t.id, t.mcp_access))
-            .collect()
-    } else {
-        HashMap::new()
-    };
+    // 查询笔记标签的有效 mcp_access（含继承自父标签的设置）
+    let tag_ids: Vec<i64> = note_tags.iter().map(|nt| nt.tag_id).collect();
+    let tag_access = effective_access(&tag_hierarchy(db, &tag_ids).await?);
 
     // 构建 note_id -> tag_ids 映射
     let mut note_tag_map: HashMap<i64, Vec<i64>> = HashMap::new();
@@ -150,15 +111,14 @@ pub async fn resolve_notes_access(
         let tag_perm = if let Some(tids) = note_tag_map.get(&note.id) {
             let mut strictest: Option<McpPermission> = None;
             for tid in tids {
-                if let Some(&access) = tag_access.get(tid) {
-                    let a = McpAccess::from(access);
-                    if a != McpAccess::Inherit {
-                        let p = mcp_access_to_permission(a);
-                        strictest = Some(match strictest {
-                            Some(s) => s.stricter(p),
-                            None => p,
-                        });
-                    }
+                if let Some(&a) = tag_access.get(tid)
+                    && a != McpAccess::Inherit
+                {
+                    let p = mcp_access_to_permission(a);
+                    strictest = Some(match strictest {
+                        Some(s) => s.stricter(p),
+                        None => p,
+                    });
                 }
             }
             strictest
@@ -169,7 +129,7 @@ pub async fn resolve_notes_access(
         // 4. 笔记本设置
         let nb_perm = notebook_access
             .get(&note.notebook_id)
-            .map(|&a| McpAccess::from(a))
+            .copied()
             .filter(|a| *a != McpAccess::Inherit)
             .map(mcp_access_to_permission);
 
@@ -214,28 +174,128 @@ pub async fn check_notebook_write(db: &DatabaseConnection, notebook_id: i64) ->
         return Ok(()); // 不归属笔记本，允许
     }
 
-    let notebook = entity::notebook::Entity::find_by_id(notebook_id)
-        .one(db)
-        .await?;
+    // 笔记本自身为 Inherit 时沿父笔记本向上查找
+    match resolve_notebook_access(db, notebook_id).await? {
+        McpAccess::Deny => {
+            anyhow::bail!("AI access denied for target notebook (mcp_access=Deny)");
+        }
+        McpAccess::ReadOnly => {
+            anyhow::bail!(
+                "Target notebook is read-only, AI cannot create notes here (mcp_access=ReadOnly)"
+            );
+        }
+        _ => {}
+    }
 
-    if let Some(nb) = notebook {
-        let access = McpAccess::from(nb.mcp_access);
-        match access {
-            McpAccess::Deny => {
-                anyhow::bail!("AI access denied for target notebook (mcp_access=Deny)");
-            }
-            McpAccess::ReadOnly => {
-                anyhow::bail!(
-                    "Target notebook is read-only, AI cannot create notes here (mcp_access=ReadOnly)"
-                );
-            }
-            _ => {}
+    Ok(())
+}
+
+/// 检查标签是否可写（含继承自父标签的设置），用于合并等会删除或改写标签本身的操作
+pub async fn check_tag_write(db: &DatabaseConnection, tag_id: i64) -> anyhow::Result<()> {
+    match inherited_access(&tag_hierarchy(db, &[tag_id]).await?, tag_id) {
+        McpAccess::Deny => {
+            anyhow::bail!("AI access denied for tag {} (mcp_access=Deny)", tag_id);
+        }
+        McpAccess::ReadOnly => {
+            anyhow::bail!("Tag {} is read-only for AI (mcp_access=ReadOnly)", tag_id);
         }
+        _ => {}
     }
 
     Ok(())
 }
 
+/// 指定笔记本及其所有祖先的 id → (parent_id, mcp_access)
+async fn notebook_hierarchy(
+    db: &DatabaseConnection,
+    ids: &[i64],
+) -> anyhow::Result<HashMap<i64, (i64, i32)>> {
+    ancestor_hierarchy(db, "notebook", ids).await
+}
+
+/// 指定标签及其所有祖先的 id → (parent_id, mcp_access)
+async fn tag_hierarchy(
+    db: &DatabaseConnection,
+    ids: &[i64],
+) -> anyhow::Result<HashMap<i64, (i64, i32)>> {
+    ancestor_hierarchy(db, "tag", ids).await
+}
+
+/// 用递归 CTE 沿 parent_id 向上查找，只读取继承链上的记录
+///
+/// UNION 会去除重复行，父子关系成环时查询同样会结束
+async fn ancestor_hierarchy(
+    db: &DatabaseConnection,
+    table: &str,
+    ids: &[i64],
+) -> anyhow::Result<HashMap<i64, (i64, i32)>> {
+    let ids: HashSet<i64> = ids.iter().copied().filter(|id| *id > 0).collect();
+    if ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let id_list = ids
+        .iter()
+        .map(i64::to_string)
+        .collect::<Vec<_>>()
+        .join(", ");
+    let sql = format!(
+        "WITH RECURSIVE chain(id, parent_id, mcp_access) AS (SELECT id, parent_id, mcp_access FROM {table} WHERE id IN ({id_list}) UNION SELECT {table}.id, {table}.parent_id, {table}.mcp_access FROM {table} INNER JOIN chain ON {table}.id = chain.parent_id) SELECT id, parent_id, mcp_access FROM chain"
+    );
+
+    db.query_all_raw(Statement::from_string(db.get_database_backend(), sql))
+        .await?
+        .into_iter()
+        .map(|row| {
+            Ok((
+                row.try_get::<i64>("", "id")?,
+                (
+                    row.try_get::<i64>("", "parent_id")?,
+                    row.try_get::<i32>("", "mcp_access")?,
+                ),
+            ))
+        })
+        .collect()
+}
+
+/// 沿父级（父笔记本或父标签）向上查找第一个非 Inherit 的设置，都为 Inherit 时返回 Inherit
+///
+/// 父子关系成环时在回到已访问的节点处停止
+fn inherited_access(hierarchy: &HashMap<i64, (i64, i32)>, id: i64) -> McpAccess {
+    let mut visited = HashSet::new();
+    let mut current = id;
+    while visited.insert(current) {
+        let Some(&(parent_id, access)) = hierarchy.get(&current) else {
+            break;
+        };
+        let access = McpAccess::from(access);
+        if access != McpAccess::Inherit {
+            return access;
+        }
+        current = parent_id;
+    }
+    McpAccess::Inherit
+}
+
+/// 层级中所有节点的有效 mcp_access
+fn effective_access(hierarchy: &HashMap<i64, (i64, i32)>) -> HashMap<i64, McpAccess> {
+    hierarchy
+        .keys()
+        .map(|id| (*id, inherited_access(hierarchy, *id)))
+        .collect()
+}
+
+/// 解析单个笔记本的有效 mcp_access（含继承自父笔记本的设置）
+async fn resolve_notebook_access(
+    db: &DatabaseConnection,
+    notebook_id: i64,
+) -> anyhow::Result<McpAccess> {
+    if notebook_id == 0 {
+        return Ok(McpAccess::Inherit);
+    }
+    let hierarchy = notebook_hierarchy(db, &[notebook_id]).await?;
+    Ok(inherited_access(&hierarchy, notebook_id))
+}
+
 /// 将 McpAccess 转换为 McpPermission（不含 Inherit）
 fn mcp_access_to_permission(access: McpAccess) -> McpPermission {
     match access {
@@ -246,6 +306,8 @@ fn mcp_access_to_permission(access: McpAccess) -> McpPermission {
 }
 
 /// 解析笔记标签的聚合权限
+///
+/// 每个标签取其有效设置（自身为 Inherit 时沿父标签向上查找），再取最严格值
 async fn resolve_tags_access(
     db: &DatabaseConnection,
     note_id: i64,
@@ -260,15 +322,11 @@ async fn resolve_tags_access(
     }
 
     let tag_ids: Vec<i64> = note_tags.iter().map(|nt| nt.tag_id).collect();
-
-    let tags = entity::tag::Entity::find()
-        .filter(entity::tag::Column::Id.is_in(tag_ids))
-        .all(db)
-        .await?;
+    let hierarchy = tag_hierarchy(db, &tag_ids).await?;
 
     let mut strictest: Option<McpPermission> = None;
-    for tag in &tags {
-        let access = McpAccess::from(tag.mcp_access);
+    for nt in &note_tags {
+        let access = inherited_access(&hierarchy, nt.tag_id);
         if access != McpAccess::Inherit {
             let perm = mcp_access_to_permission(access);
             strictest = Some(match strictest {
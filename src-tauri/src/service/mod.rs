@@ -25,6 +25,11 @@ pub mod app_log;
 /// 提供附件的保存、查询、删除功能
 pub mod attachment;
 
+/// 附件文本提取服务模块
+///
+/// 从附件中提取纯文本并建立索引，供关键词搜索命中附件内容
+pub mod attachment_text;
+
 /// 笔记服务模块
 ///
 /// 提供笔记的完整 CRUD 功能，包括：
@@ -41,6 +46,32 @@ pub mod note;
 /// - 支持查看创建、更新、删除操作的历史
 pub mod note_history;
 
+/// 笔记版本对比服务模块
+///
+/// 对比两个历史版本或历史版本与当前版本的标题、内容与元信息差异，
+/// 以及将笔记恢复到指定历史版本
+pub mod note_revision;
+
+/// 历史记录存储格式模块
+///
+/// 历史内容按快照加增量保存，提供编码、解码与重新编码
+pub mod history_storage;
+
+/// 历史记录保留策略服务模块
+///
+/// 按保留策略精简历史记录（近期全部保留，之后按天、按周各保留一条）
+pub mod history_retention;
+
+/// 回收站保留策略服务模块
+///
+/// 彻底删除在回收站中超过保留天数的笔记（含历史记录、标签、链接与附件引用）
+pub mod trash_retention;
+
+/// 层级结构工具模块
+///
+/// 笔记本与标签树共用的子树展开、父级优先排序与组装树
+pub mod hierarchy;
+
 /// 笔记本服务模块
 ///
 /// 提供笔记本的 CRUD 功能：
@@ -51,8 +82,8 @@ pub mod notebook;
 /// 标签服务模块
 ///
 /// 提供标签的 CRUD 功能：
-/// - 获取所有标签（按排序和更新时间排序）
-/// - 创建、更新、删除标签
+/// - 获取所有标签（按排序和更新时间排序）或标签树
+/// - 创建（支持按 `a/b/c` 路径创建嵌套标签）、更新、移动、删除标签
 pub mod tag;
 
 /// 数据库备份与恢复服务模块
@@ -85,11 +116,27 @@ pub mod crypto;
 /// 实现笔记间的双向链接功能
 pub mod note_link;
 
+/// 笔记关系图服务模块
+///
+/// 基于笔记链接构建关系图，提供邻域查询、孤立笔记检测与 GraphML/DOT/JSON 导出
+pub mod note_graph;
+
 /// 笔记模板服务模块
 ///
 /// 提供笔记模板的 CRUD 功能
 pub mod note_template;
 
+/// 笔记自动规则服务模块
+///
+/// 按条件在创建/更新笔记时自动移动、打标签、收藏、置顶或设置 MCP 权限，
+/// 也可对已有笔记手动执行（支持预览）
+pub mod note_rule;
+
+/// 保存的搜索服务模块
+///
+/// 提供保存的搜索（智能笔记本）的 CRUD、执行与命中数量统计
+pub mod saved_search;
+
 /// 锁屏认证服务模块
 ///
 /// 提供锁屏密码的设置、验证和清除功能
@@ -1,20 +1,47 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use crate::{
     entity::{self},
-    model::{Note, NoteHistoryExtra, OperateSource, OperationType},
+    error::AppError,
+    model::{
+        BatchNoteResult, BatchNoteStatus, Note, NoteHistoryExtra, NoteRuleEffect, OperateSource,
+        OperationType, Tag,
+    },
+    service::{
+        attachment_text, crypto, history_storage, note_link,
+        note_rule::{self, RuleTrigger},
+    },
 };
 use chrono::Local;
 use sea_orm::{
     ActiveModelTrait,
     ActiveValue::{NotSet, Set},
-    ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, EntityTrait, IntoActiveModel,
+    ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, ExprTrait, IntoActiveModel,
     QueryFilter, QueryOrder, TransactionTrait,
     prelude::Expr,
 };
 
 use super::crypto_helper::{decrypt_content, encrypt_content};
 use super::search::{fetch_note_tags, find_by_id, find_by_id_with_key};
+use super::wiki_link::rename_wiki_refs;
+
+/// 版本冲突错误码（参数为笔记当前版本）
+const VERSION_CONFLICT: &str = "NOTE_VERSION_CONFLICT";
+
+/// 构造版本冲突错误
+fn version_conflict(current_version: i64) -> anyhow::Error {
+    AppError::code_with_args(VERSION_CONFLICT, vec![current_version.to_string()]).into()
+}
+
+/// 若错误为版本冲突，返回笔记的当前版本
+pub fn conflict_version(err: &anyhow::Error) -> Option<i64> {
+    match err.downcast_ref::<AppError>()? {
+        AppError::BusinessCode { code, args } if code == VERSION_CONFLICT => {
+            args.first()?.parse().ok()
+        }
+        _ => None,
+    }
+}
 
 /// 创建笔记
 pub async fn create(
@@ -26,12 +53,18 @@ pub async fn create(
 }
 
 /// 创建笔记（支持内容加密）
+///
+/// 启用的创建规则（见 [`note_rule`]）产生的变更随笔记一起写入
 pub async fn create_with_key(
     db: &DatabaseConnection,
     note: &Note,
     source: OperateSource,
     encryption_key: Option<&str>,
 ) -> anyhow::Result<Option<Note>> {
+    let effect = note_rule::effect_on_save(db, note, source, RuleTrigger::Create).await?;
+    let ruled = note_rule::apply_to_note(db, note, &effect).await?;
+    let note = ruled.as_ref().unwrap_or(note);
+
     let txn = db.begin().await?;
 
     let now = Local::now().naive_local();
@@ -42,12 +75,16 @@ pub async fn create_with_key(
     let mut active_model: entity::note::ActiveModel = note.into();
 
     active_model.id = NotSet;
-    active_model.is_pinned = Set(0);
-    active_model.is_starred = Set(0);
+    active_model.is_pinned = Set(i32::from(effect.is_pinned.unwrap_or(false)));
+    active_model.is_starred = Set(i32::from(effect.is_starred.unwrap_or(false)));
+    if let Some(mcp_access) = effect.mcp_access {
+        active_model.mcp_access = Set(mcp_access);
+    }
     active_model.content = Set(encrypted_content);
     active_model.create_time = Set(now);
     active_model.update_time = Set(now);
     active_model.deleted_at = Set(None);
+    active_model.version = Set(1);
 
     let entity = active_model.insert(&txn).await?;
 
@@ -90,20 +127,25 @@ pub async fn create_with_key(
         tags: note.tags.clone(),
     })?;
 
+    let encoded = history_storage::encode(&txn, entity.id, "", &note.content).await?;
     entity::note_history::ActiveModel {
         id: NotSet,
         note_id: Set(entity.id),
-        old_content: Set(String::default()),
-        new_content: Set(note.content.clone()),
+        old_content: Set(encoded.old_content),
+        new_content: Set(encoded.new_content),
         extra: Set(extra),
         operate_type: Set(OperationType::Create.as_i32()),
         operate_source: Set(source.as_i32()),
         operate_time: Set(now),
         create_time: Set(now),
+        content_format: Set(encoded.content_format),
+        base_id: Set(encoded.base_id),
     }
     .insert(&txn)
     .await?;
 
+    note_link::sync_content_links(&txn, entity.id, &note.content, note.content_type).await?;
+
     txn.commit().await?;
 
     find_by_id_with_key(db, entity.id, encryption_key).await
@@ -132,6 +174,7 @@ pub async fn create_for_sync(
     active_model.create_time = Set(now);
     active_model.update_time = Set(now);
     active_model.deleted_at = Set(None);
+    active_model.version = Set(1);
 
     let entity = active_model.insert(&txn).await?;
 
@@ -154,6 +197,8 @@ pub async fn create_for_sync(
             .await?;
     }
 
+    note_link::sync_content_links(&txn, entity.id, &note.content, note.content_type).await?;
+
     txn.commit().await?;
 
     find_by_id_with_key(db, entity.id, encryption_key).await
@@ -184,7 +229,9 @@ pub async fn restore_by_id(db: &DatabaseConnection, id: i64) -> anyhow::Result<(
 }
 
 /// 在给定连接上执行单条笔记的永久删除（含历史记录生成）
-async fn permanent_delete_one<C: ConnectionTrait>(
+///
+/// 不处理附件，按保留期清理回收站时由调用方在同一事务中删除附件记录
+pub async fn permanent_delete_one<C: ConnectionTrait>(
     db: &C,
     id: i64,
     source: OperateSource,
@@ -218,16 +265,19 @@ async fn permanent_delete_one<C: ConnectionTrait>(
         // 历史记录统一存明文（与 create/update 保持一致）
         let old_content_plain = decrypt_content(&entity.content, encryption_key);
 
+        let encoded = history_storage::encode(db, id, &old_content_plain, "").await?;
         entity::note_history::ActiveModel {
             id: NotSet,
             note_id: Set(id),
-            old_content: Set(old_content_plain),
-            new_content: Set(String::default()),
+            old_content: Set(encoded.old_content),
+            new_content: Set(encoded.new_content),
             extra: Set(extra),
             operate_type: Set(OperationType::Delete.as_i32()),
             operate_source: Set(source.as_i32()),
             operate_time: Set(now),
             create_time: Set(now),
+            content_format: Set(encoded.content_format),
+            base_id: Set(encoded.base_id),
         }
         .insert(db)
         .await?;
@@ -236,14 +286,7 @@ async fn permanent_delete_one<C: ConnectionTrait>(
             .filter(entity::note_tags::Column::NoteId.eq(id))
             .exec(db)
             .await?;
-        entity::note_link::Entity::delete_many()
-            .filter(
-                Condition::any()
-                    .add(entity::note_link::Column::SourceNoteId.eq(id))
-                    .add(entity::note_link::Column::TargetNoteId.eq(id)),
-            )
-            .exec(db)
-            .await?;
+        note_link::delete_note_links(db, &[id]).await?;
         entity::note::Entity::delete_by_id(id).exec(db).await?;
     }
 
@@ -323,35 +366,251 @@ pub async fn update(
 }
 
 /// 更新笔记（支持内容加密）
+///
+/// 启用的更新规则（见 [`note_rule`]）产生的变更随本次更新一起写入
 pub async fn update_with_key(
     db: &DatabaseConnection,
     note: &Note,
     source: OperateSource,
     encryption_key: Option<&str>,
 ) -> anyhow::Result<Option<Note>> {
-    if let Some(entity) = entity::note::Entity::find_by_id(note.id).one(db).await? {
-        let old_title = entity.title.clone();
+    let effect = note_rule::effect_on_save(db, note, source, RuleTrigger::Update).await?;
+    let ruled = note_rule::apply_to_note(db, note, &effect).await?;
+    let note = ruled.as_ref().unwrap_or(note);
+
+    apply_update(
+        db,
+        note,
+        source,
+        OperationType::Update,
+        encryption_key,
+        &effect,
+    )
+    .await?;
+    find_by_id_with_key(db, note.id, encryption_key).await
+}
+
+/// 更新笔记，并以指定的操作类型记录历史（如恢复历史版本）
+///
+/// `note.version` 大于 0 时与当前版本比较，不一致（笔记已被其他进程修改）时
+/// 返回 `NOTE_VERSION_CONFLICT` 错误，参数为当前版本；有修改时版本号加 1。
+/// 标题变化时同时更新其他笔记内容中引用旧标题的 `[[旧标题]]`。不执行自动规则
+pub async fn update_with_operation(
+    db: &DatabaseConnection,
+    note: &Note,
+    source: OperateSource,
+    operation: OperationType,
+    encryption_key: Option<&str>,
+) -> anyhow::Result<Option<Note>> {
+    let effect = NoteRuleEffect::default();
+    apply_update(db, note, source, operation, encryption_key, &effect).await?;
+    find_by_id_with_key(db, note.id, encryption_key).await
+}
+
+/// 笔记重命名后，改写内容中按旧标题引用该笔记的其他笔记
+///
+/// 与重命名在同一事务中执行，引用方按读取时的版本更新；内容无法解密的笔记会被跳过
+async fn propagate_rename<C: ConnectionTrait>(
+    db: &C,
+    note_id: i64,
+    old_title: &str,
+    new_title: &str,
+    source: OperateSource,
+    encryption_key: Option<&str>,
+) -> anyhow::Result<()> {
+    for source_id in note_link::find_content_sources(db, note_id).await? {
+        let Some(mut referrer) = find_by_id_with_key(db, source_id, encryption_key).await? else {
+            continue;
+        };
+        let Some(content) = rename_wiki_refs(
+            &referrer.content,
+            referrer.content_type,
+            note_id,
+            old_title,
+            new_title,
+        ) else {
+            continue;
+        };
+        referrer.content = content;
+        write_update(
+            db,
+            &referrer,
+            source,
+            OperationType::Update,
+            encryption_key,
+            &NoteRuleEffect::default(),
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+/// 构造历史记录的元信息 JSON（修改前的所属笔记本、内容类型、标题与标签）
+async fn history_extra<C: ConnectionTrait>(
+    db: &C,
+    old_notebook_id: i64,
+    content_type: i32,
+    title: String,
+    tags: Vec<Tag>,
+) -> anyhow::Result<String> {
+    let mut notebook_id = 0_i64;
+    let mut notebook_name = String::default();
+
+    if old_notebook_id > 0
+        && let Some(notebook) = entity::notebook::Entity::find_by_id(old_notebook_id)
+            .one(db)
+            .await?
+    {
+        notebook_id = notebook.id;
+        notebook_name = notebook.name;
+    }
+
+    Ok(serde_json::to_string(&NoteHistoryExtra {
+        notebook_id,
+        notebook_name,
+        content_type,
+        title,
+        tags,
+    })?)
+}
+
+/// 写入一条历史记录，内容按快照加增量编码
+async fn insert_history<C: ConnectionTrait>(
+    db: &C,
+    note_id: i64,
+    old_content: &str,
+    new_content: &str,
+    extra: String,
+    operation: OperationType,
+    source: OperateSource,
+) -> anyhow::Result<()> {
+    let now = Local::now().naive_local();
+    let encoded = history_storage::encode(db, note_id, old_content, new_content).await?;
+    entity::note_history::ActiveModel {
+        id: NotSet,
+        note_id: Set(note_id),
+        old_content: Set(encoded.old_content),
+        new_content: Set(encoded.new_content),
+        extra: Set(extra),
+        operate_type: Set(operation.as_i32()),
+        operate_source: Set(source.as_i32()),
+        operate_time: Set(now),
+        create_time: Set(now),
+        content_format: Set(encoded.content_format),
+        base_id: Set(encoded.base_id),
+    }
+    .insert(db)
+    .await?;
+    Ok(())
+}
+
+/// 记录笔记标签的变更：版本号加 1，并写入一条保存旧标签的历史记录
+///
+/// 用于批量增删标签、合并标签、手动执行规则等不经过 [`update`] 的改写，
+/// 标题与内容不变，不修改更新时间（与仅标签变化的普通更新一致）；
+/// `content` 为笔记的明文内容，同时作为历史记录的新旧内容
+pub async fn record_tags_change<C: ConnectionTrait>(
+    db: &C,
+    note: &entity::note::Model,
+    content: &str,
+    old_tags: Vec<Tag>,
+    source: OperateSource,
+) -> anyhow::Result<()> {
+    entity::note::Entity::update_many()
+        .col_expr(
+            entity::note::Column::Version,
+            Expr::col(entity::note::Column::Version).add(1),
+        )
+        .filter(entity::note::Column::Id.eq(note.id))
+        .exec(db)
+        .await?;
+
+    let extra = history_extra(
+        db,
+        note.notebook_id,
+        note.content_type,
+        note.title.clone(),
+        old_tags,
+    )
+    .await?;
+    insert_history(
+        db,
+        note.id,
+        content,
+        content,
+        extra,
+        OperationType::Update,
+        source,
+    )
+    .await
+}
+
+/// 在一个事务中执行笔记更新，标题发生变化时一并改写引用旧标题的其他笔记
+///
+/// `effect` 中的收藏、置顶与 MCP 权限一并写入（笔记本与标签变更已体现在 `note` 中）
+async fn apply_update(
+    db: &DatabaseConnection,
+    note: &Note,
+    source: OperateSource,
+    operation: OperationType,
+    encryption_key: Option<&str>,
+    effect: &NoteRuleEffect,
+) -> anyhow::Result<()> {
+    let txn = db.begin().await?;
+    if let Some(old_title) =
+        write_update(&txn, note, source, operation, encryption_key, effect).await?
+    {
+        propagate_rename(
+            &txn,
+            note.id,
+            &old_title,
+            &note.title,
+            source,
+            encryption_key,
+        )
+        .await?;
+    }
+    txn.commit().await?;
+    Ok(())
+}
 
-        let txn = db.begin().await?;
+/// 写入笔记更新，标题发生变化时返回旧标题
+async fn write_update<C: ConnectionTrait>(
+    txn: &C,
+    note: &Note,
+    source: OperateSource,
+    operation: OperationType,
+    encryption_key: Option<&str>,
+    effect: &NoteRuleEffect,
+) -> anyhow::Result<Option<String>> {
+    let mut renamed_from = None;
+    if let Some(entity) = entity::note::Entity::find_by_id(note.id).one(txn).await? {
+        if note.version > 0 && note.version != entity.version {
+            return Err(version_conflict(entity.version));
+        }
+        let read_version = entity.version;
+        let old_title = entity.title.clone();
 
         // 获取旧标签关联 ID（用于差集计算）
         let old_tag_ids: Vec<i64> = entity::note_tags::Entity::find()
             .filter(entity::note_tags::Column::NoteId.eq(note.id))
             .order_by_desc(entity::note_tags::Column::SortOrder)
-            .all(&txn)
+            .all(txn)
             .await?
             .into_iter()
             .map(|nt| nt.tag_id)
             .collect();
 
         // 获取旧标签详情（用于历史记录）
-        let old_tags = fetch_note_tags(&txn, note.id).await?;
+        let old_tags = fetch_note_tags(txn, note.id).await?;
 
+        let old_notebook_id = entity.notebook_id;
         let old_content_type = entity.content_type;
         let old_content = entity.content.clone();
 
         // 透明加密：对新内容进行加密
         let encrypted_content = encrypt_content(&note.content, encryption_key)?;
+        let content_encrypted = crypto::is_encrypted(&encrypted_content);
 
         let mut active_model: entity::note::ActiveModel = entity.into_active_model();
 
@@ -361,15 +620,32 @@ pub async fn update_with_key(
         active_model
             .content_type
             .set_if_not_equals(note.content_type);
+        if let Some(starred) = effect.is_starred {
+            active_model
+                .is_starred
+                .set_if_not_equals(i32::from(starred));
+        }
+        if let Some(pinned) = effect.is_pinned {
+            active_model.is_pinned.set_if_not_equals(i32::from(pinned));
+        }
+        if let Some(mcp_access) = effect.mcp_access {
+            active_model.mcp_access.set_if_not_equals(mcp_access);
+        }
 
         let now = Local::now().naive_local();
 
         let note_changed = active_model.is_changed();
+        let title_changed = active_model.title.is_set();
 
         if note_changed {
             active_model.update_time = Set(now);
 
-            active_model.update(&txn).await?;
+            active_model.update(txn).await?;
+            note_link::sync_content_links(txn, note.id, &note.content, note.content_type).await?;
+            // 加密笔记的附件文本不以明文保存
+            if content_encrypted {
+                attachment_text::delete_by_note_id(txn, note.id).await?;
+            }
         }
 
         let new_tag_ids = note.tags.iter().map(|e| e.id).collect::<Vec<i64>>();
@@ -391,7 +667,7 @@ pub async fn update_with_key(
                 entity::note_tags::Entity::delete_many()
                     .filter(entity::note_tags::Column::NoteId.eq(note.id))
                     .filter(entity::note_tags::Column::TagId.is_in(to_delete))
-                    .exec(&txn)
+                    .exec(txn)
                     .await?;
             }
 
@@ -412,51 +688,134 @@ pub async fn update_with_key(
                     .collect::<Vec<entity::note_tags::ActiveModel>>();
 
                 entity::note_tags::Entity::insert_many(new_note_tags)
-                    .exec(&txn)
+                    .exec(txn)
                     .await?;
             }
         }
 
         if note_changed || tags_changed {
-            let mut notebook_id = 0_i64;
-            let mut notebook_name = String::default();
-
-            if note.notebook_id > 0
-                && let Some(notebook) = entity::notebook::Entity::find_by_id(note.notebook_id)
-                    .one(&txn)
+            // 仅在版本未变化时加 1，读取之后被其他进程修改则回滚本次更新
+            let mut bump = entity::note::Entity::update_many()
+                .col_expr(
+                    entity::note::Column::Version,
+                    Expr::col(entity::note::Column::Version).add(1),
+                )
+                .filter(entity::note::Column::Id.eq(note.id));
+            if note.version > 0 {
+                bump = bump.filter(entity::note::Column::Version.eq(read_version));
+            }
+            if bump.exec(txn).await?.rows_affected == 0 {
+                let current = entity::note::Entity::find_by_id(note.id)
+                    .one(txn)
                     .await?
-            {
-                notebook_id = notebook.id;
-                notebook_name = notebook.name;
+                    .map_or(read_version, |n| n.version);
+                return Err(version_conflict(current));
             }
 
-            let extra = serde_json::to_string(&NoteHistoryExtra {
-                notebook_id,
-                notebook_name,
-                content_type: old_content_type,
-                title: old_title,
-                tags: old_tags,
-            })?;
+            // 历史记录保存修改前的元信息（含所属笔记本），用于版本对比与恢复
+            let extra = history_extra(
+                txn,
+                old_notebook_id,
+                old_content_type,
+                old_title.clone(),
+                old_tags,
+            )
+            .await?;
+            insert_history(
+                txn,
+                note.id,
+                &old_content,
+                &note.content,
+                extra,
+                operation,
+                source,
+            )
+            .await?;
+        }
+
+        if title_changed {
+            renamed_from = Some(old_title);
+        }
+    }
+
+    Ok(renamed_from)
+}
+
+/// 将手动执行规则产生的变更写入笔记
+///
+/// 笔记本或标签变化时版本号加 1 并记录一条历史（内容不变）；
+/// 仅收藏、置顶或 MCP 权限变化时与切换收藏/置顶一致，不记录历史；
+/// `content` 为笔记的明文内容，用于历史记录
+pub async fn apply_rule_effect(
+    db: &DatabaseConnection,
+    note: &entity::note::Model,
+    content: &str,
+    effect: &NoteRuleEffect,
+    source: OperateSource,
+) -> anyhow::Result<()> {
+    let txn = db.begin().await?;
+    let old_tags = fetch_note_tags(&txn, note.id).await?;
+    let now = Local::now().naive_local();
 
-            entity::note_history::ActiveModel {
+    let mut active_model: entity::note::ActiveModel = note.clone().into_active_model();
+    if let Some(notebook_id) = effect.notebook_id {
+        active_model.notebook_id.set_if_not_equals(notebook_id);
+    }
+    if let Some(starred) = effect.is_starred {
+        active_model
+            .is_starred
+            .set_if_not_equals(i32::from(starred));
+    }
+    if let Some(pinned) = effect.is_pinned {
+        active_model.is_pinned.set_if_not_equals(i32::from(pinned));
+    }
+    if let Some(mcp_access) = effect.mcp_access {
+        active_model.mcp_access.set_if_not_equals(mcp_access);
+    }
+    if active_model.is_changed() {
+        active_model.update_time = Set(now);
+        active_model.update(&txn).await?;
+    }
+
+    if !effect.remove_tag_ids.is_empty() {
+        entity::note_tags::Entity::delete_many()
+            .filter(entity::note_tags::Column::NoteId.eq(note.id))
+            .filter(entity::note_tags::Column::TagId.is_in(effect.remove_tag_ids.clone()))
+            .exec(&txn)
+            .await?;
+    }
+    if !effect.add_tag_ids.is_empty() {
+        let note_tags = entity::tag::Entity::find()
+            .filter(entity::tag::Column::Id.is_in(effect.add_tag_ids.clone()))
+            .all(&txn)
+            .await?
+            .into_iter()
+            .map(|t| entity::note_tags::ActiveModel {
                 id: NotSet,
                 note_id: Set(note.id),
-                old_content: Set(old_content),
-                new_content: Set(note.content.clone()),
-                extra: Set(extra),
-                operate_type: Set(OperationType::Update.as_i32()),
-                operate_source: Set(source.as_i32()),
-                operate_time: Set(now),
+                tag_id: Set(t.id),
+                sort_order: Set(t.sort_order),
                 create_time: Set(now),
-            }
-            .insert(&txn)
-            .await?;
+                update_time: Set(now),
+            })
+            .collect::<Vec<entity::note_tags::ActiveModel>>();
+        if !note_tags.is_empty() {
+            entity::note_tags::Entity::insert_many(note_tags)
+                .exec(&txn)
+                .await?;
         }
+    }
 
-        txn.commit().await?;
+    if effect.notebook_id.is_some()
+        || !effect.add_tag_ids.is_empty()
+        || !effect.remove_tag_ids.is_empty()
+    {
+        // 历史记录保存修改前的笔记本与标签
+        record_tags_change(&txn, note, content, old_tags, source).await?;
     }
 
-    find_by_id_with_key(db, note.id, encryption_key).await
+    txn.commit().await?;
+    Ok(())
 }
 
 /// 批量移动笔记到指定笔记本
@@ -473,6 +832,10 @@ pub async fn batch_move(
     entity::note::Entity::update_many()
         .col_expr(entity::note::Column::NotebookId, Expr::value(notebook_id))
         .col_expr(entity::note::Column::UpdateTime, Expr::value(now))
+        .col_expr(
+            entity::note::Column::Version,
+            Expr::col(entity::note::Column::Version).add(1),
+        )
         .filter(entity::note::Column::Id.is_in(note_ids.to_vec()))
         .filter(entity::note::Column::DeletedAt.is_null())
         .exec(&txn)
@@ -498,3 +861,231 @@ pub async fn batch_delete(db: &DatabaseConnection, note_ids: &[i64]) -> anyhow::
     txn.commit().await?;
     Ok(())
 }
+
+/// 按请求顺序生成批量操作结果（重复的 ID 只保留一条）
+///
+/// `status` 判断已存在笔记的处理结果，不存在的笔记为 [`BatchNoteStatus::NotFound`]
+fn batch_results(
+    note_ids: &[i64],
+    notes: &[entity::note::Model],
+    status: impl Fn(&entity::note::Model) -> BatchNoteStatus,
+) -> Vec<BatchNoteResult> {
+    let by_id: HashMap<i64, &entity::note::Model> = notes.iter().map(|n| (n.id, n)).collect();
+    let mut seen = HashSet::new();
+    note_ids
+        .iter()
+        .filter(|id| seen.insert(**id))
+        .map(|id| BatchNoteResult {
+            note_id: *id,
+            status: by_id
+                .get(id)
+                .map_or(BatchNoteStatus::NotFound, |n| status(n)),
+        })
+        .collect()
+}
+
+/// 批量结果中已修改的笔记 ID
+pub fn changed_ids(results: &[BatchNoteResult]) -> Vec<i64> {
+    results
+        .iter()
+        .filter(|r| r.status == BatchNoteStatus::Changed)
+        .map(|r| r.note_id)
+        .collect()
+}
+
+/// 批量设置未删除笔记的收藏或置顶标记
+async fn batch_set_flag(
+    db: &DatabaseConnection,
+    note_ids: &[i64],
+    column: entity::note::Column,
+    value: bool,
+    current: fn(&entity::note::Model) -> i32,
+) -> anyhow::Result<Vec<BatchNoteResult>> {
+    let value = i32::from(value);
+    let txn = db.begin().await?;
+    let notes = entity::note::Entity::find()
+        .filter(entity::note::Column::Id.is_in(note_ids.to_vec()))
+        .all(&txn)
+        .await?;
+    let results = batch_results(note_ids, &notes, |n| {
+        if n.deleted_at.is_some() {
+            BatchNoteStatus::Skipped
+        } else if current(n) == value {
+            BatchNoteStatus::Unchanged
+        } else {
+            BatchNoteStatus::Changed
+        }
+    });
+
+    let changed = changed_ids(&results);
+    if !changed.is_empty() {
+        entity::note::Entity::update_many()
+            .col_expr(column, Expr::value(value))
+            .col_expr(
+                entity::note::Column::UpdateTime,
+                Expr::value(Local::now().naive_local()),
+            )
+            .filter(entity::note::Column::Id.is_in(changed))
+            .exec(&txn)
+            .await?;
+    }
+    txn.commit().await?;
+    Ok(results)
+}
+
+/// 批量设置笔记收藏状态
+///
+/// 回收站中的笔记跳过；返回每条笔记的处理结果（按请求顺序）
+pub async fn batch_set_star(
+    db: &DatabaseConnection,
+    note_ids: &[i64],
+    starred: bool,
+) -> anyhow::Result<Vec<BatchNoteResult>> {
+    batch_set_flag(
+        db,
+        note_ids,
+        entity::note::Column::IsStarred,
+        starred,
+        |n| n.is_starred,
+    )
+    .await
+}
+
+/// 批量设置笔记置顶状态
+///
+/// 回收站中的笔记跳过；返回每条笔记的处理结果（按请求顺序）
+pub async fn batch_set_pin(
+    db: &DatabaseConnection,
+    note_ids: &[i64],
+    pinned: bool,
+) -> anyhow::Result<Vec<BatchNoteResult>> {
+    batch_set_flag(db, note_ids, entity::note::Column::IsPinned, pinned, |n| {
+        n.is_pinned
+    })
+    .await
+}
+
+/// 批量从回收站恢复笔记
+///
+/// 未在回收站中的笔记为 [`BatchNoteStatus::Unchanged`]；返回每条笔记的处理结果（按请求顺序）
+pub async fn batch_restore(
+    db: &DatabaseConnection,
+    note_ids: &[i64],
+) -> anyhow::Result<Vec<BatchNoteResult>> {
+    let txn = db.begin().await?;
+    let notes = entity::note::Entity::find()
+        .filter(entity::note::Column::Id.is_in(note_ids.to_vec()))
+        .all(&txn)
+        .await?;
+    let results = batch_results(note_ids, &notes, |n| {
+        if n.deleted_at.is_some() {
+            BatchNoteStatus::Changed
+        } else {
+            BatchNoteStatus::Unchanged
+        }
+    });
+
+    let changed = changed_ids(&results);
+    if !changed.is_empty() {
+        entity::note::Entity::update_many()
+            .col_expr(
+                entity::note::Column::DeletedAt,
+                Expr::value(Option::<chrono::NaiveDateTime>::None),
+            )
+            .col_expr(
+                entity::note::Column::UpdateTime,
+                Expr::value(Local::now().naive_local()),
+            )
+            .filter(entity::note::Column::Id.is_in(changed))
+            .exec(&txn)
+            .await?;
+    }
+    txn.commit().await?;
+    Ok(results)
+}
+
+/// 批量永久删除回收站中的笔记（每条笔记记录删除历史）
+///
+/// 未在回收站中的笔记跳过；返回每条笔记的处理结果（按请求顺序）
+pub async fn batch_permanent_delete(
+    db: &DatabaseConnection,
+    note_ids: &[i64],
+    source: OperateSource,
+    encryption_key: Option<&str>,
+) -> anyhow::Result<Vec<BatchNoteResult>> {
+    let txn = db.begin().await?;
+    let notes = entity::note::Entity::find()
+        .filter(entity::note::Column::Id.is_in(note_ids.to_vec()))
+        .all(&txn)
+        .await?;
+    let results = batch_results(note_ids, &notes, |n| {
+        if n.deleted_at.is_some() {
+            BatchNoteStatus::Changed
+        } else {
+            BatchNoteStatus::Skipped
+        }
+    });
+
+    for id in changed_ids(&results) {
+        permanent_delete_one(&txn, id, source, encryption_key).await?;
+    }
+    txn.commit().await?;
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(id: i64, deleted: bool) -> entity::note::Model {
+        let now = Local::now().naive_local();
+        entity::note::Model {
+            id,
+            notebook_id: 1,
+            title: format!("note {}", id),
+            content: String::new(),
+            content_type: 0,
+            is_pinned: 0,
+            is_starred: 0,
+            mcp_access: 0,
+            create_time: now,
+            update_time: now,
+            deleted_at: deleted.then_some(now),
+            version: 1,
+        }
+    }
+
+    fn result(note_id: i64, status: BatchNoteStatus) -> BatchNoteResult {
+        BatchNoteResult { note_id, status }
+    }
+
+    #[test]
+    fn batch_results_keep_request_order_and_dedup() {
+        // 查询结果顺序与请求顺序无关
+        let notes = vec![note(1, false), note(3, true), note(2, false)];
+        let results = batch_results(&[3, 1, 4, 3, 2, 1], &notes, |n| {
+            if n.deleted_at.is_some() {
+                BatchNoteStatus::Skipped
+            } else {
+                BatchNoteStatus::Changed
+            }
+        });
+        assert_eq!(
+            results,
+            vec![
+                result(3, BatchNoteStatus::Skipped),
+                result(1, BatchNoteStatus::Changed),
+                result(4, BatchNoteStatus::NotFound),
+                result(2, BatchNoteStatus::Changed),
+            ]
+        );
+        assert_eq!(changed_ids(&results), vec![1, 2]);
+    }
+
+    #[test]
+    fn batch_results_empty_request() {
+        let results = batch_results(&[], &[note(1, false)], |_| BatchNoteStatus::Changed);
+        assert!(results.is_empty());
+        assert!(changed_ids(&results).is_empty());
+    }
+}
@@ -0,0 +1,257 @@
+//! 笔记搜索游标分页
+//!
+//! 游标记录上一页最后一条笔记的排序键（置顶、排序列、ID），下一页按
+//! `(is_pinned, sort_col, id)` 的字典序直接定位，不再使用 OFFSET，总数统计也改为可选。
+//!
+//! 相关度得分是每次查询时实时计算的浮点值，不适合作为定位键，
+//! 按 relevance 排序时游标中记录的是下一页的偏移量，行为与普通分页一致。
+//!
+//! 游标是 base64url 编码的 JSON，对调用方不透明；排序字段或方向变化后旧游标失效。
+
+use anyhow::anyhow;
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::NaiveDateTime;
+use sea_orm::{ColumnTrait, Condition, DatabaseConnection, EntityTrait, QueryFilter, Value};
+use serde::{Deserialize, Serialize};
+
+use crate::entity;
+use crate::i18n::t_simple;
+use crate::model::{CursorPageResult, Note, NoteCursorSearchParam, NoteSearchPageParam};
+
+use super::query::SearchQuery;
+use super::search::{
+    SearchBackend, apply_search_filters, by_relevance, count_notes, fill_search_results, load_page,
+    sort_column,
+};
+
+/// 游标中时间值的格式（保留小数秒，保证定位精确）
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.f";
+
+/// 游标内容
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct SearchCursor {
+    /// 生成游标时的排序（`排序字段:方向`）
+    sort: String,
+    /// 按相关度排序时下一页的起始偏移量
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    offset: Option<u64>,
+    /// 上一页最后一条笔记的置顶状态
+    #[serde(default)]
+    pinned: i32,
+    /// 上一页最后一条笔记的排序列值（时间按 `TIME_FORMAT` 格式化）
+    #[serde(default)]
+    value: String,
+    /// 上一页最后一条笔记的 ID
+    #[serde(default)]
+    id: i64,
+}
+
+impl SearchCursor {
+    fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    fn decode(cursor: &str) -> Option<Self> {
+        let bytes = URL_SAFE_NO_PAD.decode(cursor).ok()?;
+        serde_json::from_slice(&bytes).ok()
+    }
+}
+
+/// 当前搜索实际使用的排序（`排序字段:方向`），用于校验游标是否仍然适用
+fn sort_key(search_param: &NoteSearchPageParam, query: &SearchQuery) -> String {
+    let field = if by_relevance(search_param, query) {
+        "relevance"
+    } else {
+        match sort_column(search_param) {
+            entity::note::Column::Title => "title",
+            entity::note::Column::CreateTime => "create_time",
+            _ => "update_time",
+        }
+    };
+    let order = if search_param.sort_order == "asc" {
+        "asc"
+    } else {
+        "desc"
+    };
+    format!("{}:{}", field, order)
+}
+
+/// 记录笔记排序键的游标
+fn cursor_after(note: &Note, column: entity::note::Column, sort: String) -> SearchCursor {
+    let time = |t: Option<NaiveDateTime>| {
+        t.map(|t| t.format(TIME_FORMAT).to_string())
+            .unwrap_or_default()
+    };
+    let value = match column {
+        entity::note::Column::Title => note.title.clone(),
+        entity::note::Column::CreateTime => time(note.create_time),
+        _ => time(note.update_time),
+    };
+    SearchCursor {
+        sort,
+        offset: None,
+        pinned: note.is_pinned,
+        value,
+        id: note.id,
+    }
+}
+
+/// 位于游标之后的笔记条件
+///
+/// 与 `load_page` 的排序一致：置顶降序、排序列按方向、ID 降序。
+/// 游标中的时间值无法解析时返回 None
+fn keyset_condition(
+    column: entity::note::Column,
+    ascending: bool,
+    cursor: &SearchCursor,
+) -> Option<Condition> {
+    let value: Value = match column {
+        entity::note::Column::Title => cursor.value.clone().into(),
+        _ => NaiveDateTime::parse_from_str(&cursor.value, TIME_FORMAT)
+            .ok()?
+            .into(),
+    };
+    let after = if ascending {
+        column.gt(value.clone())
+    } else {
+        column.lt(value.clone())
+    };
+
+    Some(
+        Condition::any()
+            .add(entity::note::Column::IsPinned.lt(cursor.pinned))
+            .add(
+                Condition::all()
+                    .add(entity::note::Column::IsPinned.eq(cursor.pinned))
+                    .add(after),
+            )
+            .add(
+                Condition::all()
+                    .add(entity::note::Column::IsPinned.eq(cursor.pinned))
+                    .add(column.eq(value))
+                    .add(entity::note::Column::Id.lt(cursor.id)),
+            ),
+    )
+}
+
+/// 游标分页搜索笔记
+pub async fn search_cursor(
+    db: &DatabaseConnection,
+    cursor_param: &NoteCursorSearchParam,
+) -> anyhow::Result<CursorPageResult<Note>> {
+    search_cursor_with_key(db, cursor_param, None).await
+}
+
+/// 游标分页搜索笔记（支持解密）
+pub async fn search_cursor_with_key(
+    db: &DatabaseConnection,
+    cursor_param: &NoteCursorSearchParam,
+    encryption_key: Option<&str>,
+) -> anyhow::Result<CursorPageResult<Note>> {
+    cursor_param.validate()?;
+
+    let search_param = &cursor_param.search_param;
+    let backend = SearchBackend::detect(db).await?;
+    let query = SearchQuery::parse(&search_param.keyword);
+    let relevance = by_relevance(search_param, &query);
+    let column = sort_column(search_param);
+    let sort = sort_key(search_param, &query);
+    let invalid_cursor = || anyhow!("{}", t_simple("validation.invalidCursor"));
+
+    let mut query_builder =
+        apply_search_filters(entity::note::Entity::find(), search_param, backend);
+    let mut start = 0;
+
+    if !cursor_param.cursor.is_empty() {
+        let cursor = SearchCursor::decode(&cursor_param.cursor)
+            .filter(|c| c.sort == sort && c.offset.is_some() == relevance)
+            .ok_or_else(invalid_cursor)?;
+        match cursor.offset {
+            Some(offset) => start = offset,
+            None => {
+                let condition = keyset_condition(column, search_param.sort_order == "asc", &cursor)
+                    .ok_or_else(invalid_cursor)?;
+                query_builder = query_builder.filter(condition);
+            }
+        }
+    }
+
+    // 多取一条用于判断是否还有下一页
+    let limit = search_param.page_param.page_size as u64;
+    let mut notes = load_page(
+        db,
+        query_builder,
+        search_param,
+        &query,
+        start,
+        limit + 1,
+        backend,
+    )
+    .await?;
+    let has_more = notes.len() as u64 > limit;
+    notes.truncate(limit as usize);
+
+    // 游标需在解密前生成（排序列取自数据库原值）
+    let next_cursor = match notes.last() {
+        Some(_) if has_more && relevance => Some(SearchCursor {
+            sort,
+            offset: Some(start + limit),
+            ..Default::default()
+        }),
+        Some(last) if has_more => Some(cursor_after(last, column, sort)),
+        _ => None,
+    }
+    .map(|c| c.encode());
+
+    let total = if cursor_param.with_total {
+        Some(count_notes(db, search_param, backend).await?)
+    } else {
+        None
+    };
+
+    fill_search_results(db, &mut notes, &query, encryption_key).await?;
+
+    Ok(CursorPageResult {
+        data: notes,
+        next_cursor,
+        total,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_round_trip() {
+        let note = Note {
+            id: 42,
+            is_pinned: 1,
+            title: "会议纪要".to_string(),
+            update_time: NaiveDateTime::parse_from_str("2026-03-01 08:30:15.250", TIME_FORMAT).ok(),
+            ..Default::default()
+        };
+        let cursor = cursor_after(
+            &note,
+            entity::note::Column::UpdateTime,
+            "update_time:desc".to_string(),
+        );
+        assert_eq!(cursor.value, "2026-03-01 08:30:15.250");
+
+        let encoded = cursor.encode();
+        assert!(!encoded.contains(['+', '/', '=']));
+        assert_eq!(SearchCursor::decode(&encoded), Some(cursor.clone()));
+        assert!(keyset_condition(entity::note::Column::UpdateTime, false, &cursor).is_some());
+    }
+
+    #[test]
+    fn malformed_cursor_is_rejected() {
+        assert_eq!(SearchCursor::decode("not a cursor"), None);
+        let cursor = SearchCursor {
+            sort: "update_time:desc".to_string(),
+            value: "yesterday".to_string(),
+            ..Default::default()
+        };
+        assert!(keyset_condition(entity::note::Column::UpdateTime, false, &cursor).is_none());
+    }
+}
@@ -6,7 +6,12 @@
 //! - 笔记的 CRUD 操作
 //! - 笔记与标签的关联管理
 //! - 笔记历史记录的自动生成
-//! - 分页搜索功能（支持多条件过滤）
+//! - 分页搜索功能（支持多条件过滤；SQLite FTS5，MySQL FULLTEXT，PostgreSQL tsvector）
+//! - 游标分页搜索（按排序键定位下一页，总数统计可选）
+//! - 搜索命中片段与高亮区间提取
+//! - 结构化搜索语法解析（`tag:`、`notebook:`、`is:`、`before:`/`after:`、短语与排除）
+//! - 内容中的 Wiki 链接解析（`[[标题]]`、`[[id:123]]`），保存时同步内容链接，
+//!   重命名时更新引用该笔记的内容
 //!
 //! # 事务处理
 //! 创建、更新、删除操作都在事务中执行，确保数据一致性：
@@ -17,10 +22,19 @@
 //! - OperationType::Create (1): 创建
 //! - OperationType::Update (2): 更新
 //! - OperationType::Delete (3): 删除
+//! - OperationType::Restore (4): 恢复历史版本
 
 mod crud;
 mod crypto_helper;
+mod cursor;
+mod query;
 mod search;
+mod snippet;
+mod wiki_link;
 
 pub use crud::*;
+pub use cursor::*;
+pub use query::*;
 pub use search::*;
+pub use snippet::*;
+pub use wiki_link::*;
@@ -0,0 +1,360 @@
+//! 结构化搜索语法解析
+//!
+//! 将一条搜索字符串解析为类型化的查询 AST，供 SQLite FTS5 与 MySQL/PG 全文索引
+//! 两条检索路径共用。支持的语法：
+//!
+//! - `word`、`"exact phrase"`：全文关键词 / 短语
+//! - `tag:rust`、`tag:"Deep Learning"`：按标签名称过滤
+//! - `notebook:Work`、`notebook:"Work Notes"`：按笔记本名称过滤
+//! - `is:starred`、`is:pinned`：按收藏 / 置顶状态过滤
+//! - `before:2026-01-01`、`after:2025-06-01`：按更新时间过滤（before 不含当天，after 含当天）
+//! - 任意项前加 `-` 表示排除，如 `-draft`、`-tag:archived`、`-is:pinned`
+//!
+//! 无法识别的限定符（如 `foo:bar`）或非法日期按普通关键词处理。
+
+use chrono::{NaiveDate, NaiveDateTime};
+
+/// `is:` 限定符支持的状态标记
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QueryFlag {
+    /// 收藏/星标
+    Starred,
+    /// 置顶
+    Pinned,
+}
+
+/// 查询 AST 节点
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum QueryNode {
+    /// 全文关键词或短语（`phrase` 为 true 表示来自引号）
+    Text {
+        value: String,
+        phrase: bool,
+        negated: bool,
+    },
+    /// 标签名称过滤
+    Tag { name: String, negated: bool },
+    /// 笔记本名称过滤
+    Notebook { name: String, negated: bool },
+    /// 状态标记过滤
+    Is { flag: QueryFlag, negated: bool },
+    /// 更新时间早于指定日期（不含当天）
+    Before(NaiveDate),
+    /// 更新时间不早于指定日期（含当天）
+    After(NaiveDate),
+}
+
+/// 解析后的搜索查询
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SearchQuery {
+    pub nodes: Vec<QueryNode>,
+}
+
+impl SearchQuery {
+    /// 解析搜索字符串
+    pub fn parse(input: &str) -> Self {
+        let nodes = tokenize(input)
+            .into_iter()
+            .filter_map(Token::into_node)
+            .collect();
+        Self { nodes }
+    }
+
+    /// 是否没有任何条件
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// 需要匹配的全文关键词
+    pub fn text_terms(&self) -> impl Iterator<Item = &str> {
+        self.nodes.iter().filter_map(|node| match node {
+            QueryNode::Text {
+                value,
+                negated: false,
+                ..
+            } => Some(value.as_str()),
+            _ => None,
+        })
+    }
+
+    /// 需要排除的全文关键词
+    pub fn excluded_terms(&self) -> impl Iterator<Item = &str> {
+        self.nodes.iter().filter_map(|node| match node {
+            QueryNode::Text {
+                value,
+                negated: true,
+                ..
+            } => Some(value.as_str()),
+            _ => None,
+        })
+    }
+}
+
+/// 将日期转换为当天零点
+pub fn start_of_day(date: NaiveDate) -> NaiveDateTime {
+    date.and_hms_opt(0, 0, 0).unwrap_or_default()
+}
+
+/// 词法单元：`[-][key:]value`
+#[derive(Debug)]
+struct Token {
+    negated: bool,
+    key: Option<String>,
+    value: String,
+    quoted: bool,
+}
+
+impl Token {
+    fn into_node(self) -> Option<QueryNode> {
+        if self.value.is_empty() {
+            return None;
+        }
+
+        let negated = self.negated;
+
+        if let Some(key) = self.key.as_deref() {
+            let node = match key.to_ascii_lowercase().as_str() {
+                "tag" => Some(QueryNode::Tag {
+                    name: self.value.clone(),
+                    negated,
+                }),
+                "notebook" => Some(QueryNode::Notebook {
+                    name: self.value.clone(),
+                    negated,
+                }),
+                "is" => match self.value.to_ascii_lowercase().as_str() {
+                    "starred" => Some(QueryNode::Is {
+                        flag: QueryFlag::Starred,
+                        negated,
+                    }),
+                    "pinned" => Some(QueryNode::Is {
+                        flag: QueryFlag::Pinned,
+                        negated,
+                    }),
+                    _ => None,
+                },
+                "before" if !negated => parse_date(&self.value).map(QueryNode::Before),
+                "after" if !negated => parse_date(&self.value).map(QueryNode::After),
+                _ => None,
+            };
+
+            if node.is_some() {
+                return node;
+            }
+
+            // 未识别的限定符：还原为普通关键词
+            let value = if self.quoted {
+                format!("{}:\"{}\"", key, self.value)
+            } else {
+                format!("{}:{}", key, self.value)
+            };
+            return Some(QueryNode::Text {
+                value,
+                phrase: false,
+                negated,
+            });
+        }
+
+        Some(QueryNode::Text {
+            value: self.value,
+            phrase: self.quoted,
+            negated,
+        })
+    }
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()
+}
+
+/// 读取引号内的内容（调用时已越过起始引号），未闭合时读到末尾
+fn read_quoted(chars: &mut std::iter::Peekable<std::str::Chars<'_>>) -> String {
+    let mut value = String::new();
+    for ch in chars.by_ref() {
+        if ch == '"' {
+            break;
+        }
+        value.push(ch);
+    }
+    value
+}
+
+fn tokenize(input: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&ch) = chars.peek() {
+        if ch.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        let mut negated = false;
+        if ch == '-' {
+            chars.next();
+            match chars.peek() {
+                Some(next) if !next.is_whitespace() => negated = true,
+                // 孤立的 `-` 忽略
+                _ => continue,
+            }
+        }
+
+        if chars.peek() == Some(&'"') {
+            chars.next();
+            let value = read_quoted(&mut chars).trim().to_string();
+            tokens.push(Token {
+                negated,
+                key: None,
+                value,
+                quoted: true,
+            });
+            continue;
+        }
+
+        let mut word = String::new();
+        let mut key = None;
+        let mut quoted = false;
+
+        while let Some(&c) = chars.peek() {
+            if c.is_whitespace() {
+                break;
+            }
+            chars.next();
+            if c == ':' && key.is_none() && !word.is_empty() {
+                key = Some(std::mem::take(&mut word));
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    word = read_quoted(&mut chars).trim().to_string();
+                    quoted = true;
+                    break;
+                }
+                continue;
+            }
+            word.push(c);
+        }
+
+        // `key:` 后无值：按普通关键词处理
+        if let Some(k) = key.as_ref()
+            && word.is_empty()
+            && !quoted
+        {
+            word = format!("{}:", k);
+            key = None;
+        }
+
+        tokens.push(Token {
+            negated,
+            key,
+            value: word,
+            quoted,
+        });
+    }
+
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(value: &str, phrase: bool, negated: bool) -> QueryNode {
+        QueryNode::Text {
+            value: value.to_string(),
+            phrase,
+            negated,
+        }
+    }
+
+    #[test]
+    fn parse_plain_keywords() {
+        let q = SearchQuery::parse("  rust  async ");
+        assert_eq!(
+            q.nodes,
+            vec![text("rust", false, false), text("async", false, false)]
+        );
+    }
+
+    #[test]
+    fn parse_full_example() {
+        let q = SearchQuery::parse(
+            r#"tag:rust notebook:"Work" is:starred before:2026-01-01 "exact phrase" -draft"#,
+        );
+        assert_eq!(
+            q.nodes,
+            vec![
+                QueryNode::Tag {
+                    name: "rust".to_string(),
+                    negated: false
+                },
+                QueryNode::Notebook {
+                    name: "Work".to_string(),
+                    negated: false
+                },
+                QueryNode::Is {
+                    flag: QueryFlag::Starred,
+                    negated: false
+                },
+                QueryNode::Before(NaiveDate::from_ymd_opt(2026, 1, 1).unwrap()),
+                text("exact phrase", true, false),
+                text("draft", false, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_negated_qualifiers() {
+        let q = SearchQuery::parse(r#"-tag:"old stuff" -is:pinned"#);
+        assert_eq!(
+            q.nodes,
+            vec![
+                QueryNode::Tag {
+                    name: "old stuff".to_string(),
+                    negated: true
+                },
+                QueryNode::Is {
+                    flag: QueryFlag::Pinned,
+                    negated: true
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_qualifier_falls_back_to_text() {
+        let q = SearchQuery::parse("http://example.com is:unknown before:tomorrow");
+        assert_eq!(
+            q.nodes,
+            vec![
+                text("http://example.com", false, false),
+                text("is:unknown", false, false),
+                text("before:tomorrow", false, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn dangling_tokens_are_tolerated() {
+        let q = SearchQuery::parse(r#"- tag: "unterminated"#);
+        assert_eq!(
+            q.nodes,
+            vec![
+                text("tag:", false, false),
+                text("unterminated", true, false)
+            ]
+        );
+        assert!(SearchQuery::parse(r#"  ""  - "#).is_empty());
+    }
+
+    #[test]
+    fn cjk_keywords() {
+        let q = SearchQuery::parse("会议纪要 tag:项目");
+        assert_eq!(q.text_terms().collect::<Vec<_>>(), vec!["会议纪要"]);
+        assert_eq!(
+            q.nodes[1],
+            QueryNode::Tag {
+                name: "项目".to_string(),
+                negated: false
+            }
+        );
+    }
+}
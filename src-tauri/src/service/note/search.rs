@@ -3,88 +3,659 @@ use std::collections::{HashMap, HashSet};
 use crate::{
     entity::{self, notebook},
     model::{Note, NoteSearchPageParam, NoteStatsResult, PageResult, Tag},
+    service::{attachment_text, saved_search, settings},
 };
 use sea_orm::{
     ColumnTrait, Condition, ConnectionTrait, DatabaseBackend, DatabaseConnection, EntityTrait,
-    ExprTrait, Order, QueryFilter, QueryOrder, QuerySelect, Select,
+    ExprTrait, JoinType, Order, QueryFilter, QueryOrder, QuerySelect, QueryTrait, Select,
+    Statement,
     prelude::Expr,
-    sea_query::{Asterisk, Query},
+    sea_query::{Alias, Asterisk, Query, SelectStatement},
 };
 
 use super::crypto_helper::decrypt_note;
+use super::query::{QueryFlag, QueryNode, SearchQuery, start_of_day};
+use super::snippet::{build_snippets, content_snippet};
 
-/// Build keyword filter condition based on database backend
+/// 构建 FTS5 MATCH 表达式中的单个短语（双引号包裹，内部引号转义）
+fn fts_phrase(term: &str) -> String {
+    format!("\"{}\"", term.replace('"', "\"\""))
+}
+
+/// 按标签名称查找笔记 ID 的子查询
+fn tag_name_subquery(name: &str) -> SelectStatement {
+    Query::select()
+        .column(entity::note_tags::Column::NoteId)
+        .distinct()
+        .from(entity::note_tags::Entity)
+        .inner_join(
+            entity::tag::Entity,
+            Expr::col((entity::tag::Entity, entity::tag::Column::Id))
+                .equals((entity::note_tags::Entity, entity::note_tags::Column::TagId)),
+        )
+        .and_where(entity::tag::Column::Name.eq(name))
+        .to_owned()
+}
+
+/// 按标签 ID 查找笔记 ID 的子查询（命中任一标签）
+fn tag_ids_subquery(tag_ids: &[i64]) -> SelectStatement {
+    Query::select()
+        .column(entity::note_tags::Column::NoteId)
+        .distinct()
+        .from(entity::note_tags::Entity)
+        .and_where(entity::note_tags::Column::TagId.is_in(tag_ids.to_vec()))
+        .to_owned()
+}
+
+/// 笔记带有指定标签之一的过滤条件，`negated` 时为不带任何一个
 ///
-/// SQLite: 使用 FTS5 全文搜索（title + content + tags）
-/// MySQL/PG: 使用 LIKE 搜索 title、content 和 tag name
-pub(super) fn apply_keyword_filter<E: EntityTrait>(
-    builder: Select<E>,
-    keyword: &str,
-    is_sqlite: bool,
-) -> Select<E> {
-    if is_sqlite {
-        let fts_keyword = keyword
-            .replace('"', "\"\"")
-            .replace(['*', '(', ')', '{', '}', '^', ':'], "");
-        let fts_query = format!("\"{}\"", fts_keyword);
-        // 使用 BM25 排序：标题权重 10，内容权重 1，标签权重 5
-        let fts_condition = Expr::cust_with_values(
-            "id IN (SELECT rowid FROM note_fts WHERE note_fts MATCH ? ORDER BY bm25(note_fts, 10.0, 1.0, 5.0))",
-            [fts_query],
-        );
-        builder.filter(fts_condition)
+/// `include_descendants` 时使用递归 CTE 展开标签树，子孙标签同样命中；
+/// UNION 去重保证父子关系成环时也能结束
+fn tag_condition(tag_ids: &[i64], include_descendants: bool, negated: bool) -> Condition {
+    if !include_descendants {
+        let sub_query = tag_ids_subquery(tag_ids);
+        return Condition::all().add(if negated {
+            entity::note::Column::Id.not_in_subquery(sub_query)
+        } else {
+            entity::note::Column::Id.in_subquery(sub_query)
+        });
+    }
+    let ids = tag_ids
+        .iter()
+        .map(i64::to_string)
+        .collect::<Vec<_>>()
+        .join(", ");
+    Condition::all().add(Expr::cust(format!(
+        "note.id {}IN (SELECT note_id FROM note_tags WHERE tag_id IN (WITH RECURSIVE tag_tree(id) AS (SELECT id FROM tag WHERE id IN ({}) UNION SELECT tag.id FROM tag INNER JOIN tag_tree ON tag.parent_id = tag_tree.id) SELECT id FROM tag_tree))",
+        if negated { "NOT " } else { "" },
+        ids
+    )))
+}
+
+/// 笔记属于指定笔记本或其子孙笔记本的过滤条件
+///
+/// 使用递归 CTE 展开笔记本树，UNION 去重保证父子关系成环时也能结束
+pub fn notebook_tree_condition(root_ids: &[i64]) -> Condition {
+    let ids = root_ids
+        .iter()
+        .map(i64::to_string)
+        .collect::<Vec<_>>()
+        .join(", ");
+    Condition::all().add(Expr::cust(format!(
+        "note.notebook_id IN (WITH RECURSIVE notebook_tree(id) AS (SELECT id FROM notebook WHERE id IN ({}) UNION SELECT notebook.id FROM notebook INNER JOIN notebook_tree ON notebook.parent_id = notebook_tree.id) SELECT id FROM notebook_tree)",
+        ids
+    )))
+}
+
+/// 按笔记本名称查找笔记本 ID 的子查询
+fn notebook_name_subquery(name: &str) -> SelectStatement {
+    Query::select()
+        .column(notebook::Column::Id)
+        .from(notebook::Entity)
+        .and_where(notebook::Column::Name.eq(name))
+        .to_owned()
+}
+
+/// 按标签名称模糊匹配笔记 ID 的子查询
+fn tag_like_subquery(term: &str) -> SelectStatement {
+    Query::select()
+        .column(entity::note_tags::Column::NoteId)
+        .distinct()
+        .from(entity::note_tags::Entity)
+        .inner_join(
+            entity::tag::Entity,
+            Expr::col((entity::tag::Entity, entity::tag::Column::Id))
+                .equals((entity::note_tags::Entity, entity::note_tags::Column::TagId)),
+        )
+        .and_where(entity::tag::Column::Name.contains(term))
+        .to_owned()
+}
+
+/// 按附件文本模糊匹配笔记 ID 的子查询
+fn attachment_like_subquery(term: &str) -> SelectStatement {
+    Query::select()
+        .column(entity::note_attachment_text::Column::NoteId)
+        .distinct()
+        .from(entity::note_attachment_text::Entity)
+        .and_where(entity::note_attachment_text::Column::Content.contains(term))
+        .to_owned()
+}
+
+/// 加密内容的 LIKE 模式（密文不参与关键词匹配）
+const ENCRYPTED_CONTENT_PATTERN: &str = "ENOTE_ENC_%";
+
+/// 未加密内容包含关键词
+fn plain_content_contains(term: &str) -> Condition {
+    Condition::all()
+        .add(entity::note::Column::Content.contains(term))
+        .add(entity::note::Column::Content.not_like(ENCRYPTED_CONTENT_PATTERN))
+}
+
+/// 单个关键词的 LIKE 条件（title、未加密 content 或 tag name 任一命中）
+fn like_term_condition(term: &str) -> Condition {
+    Condition::any()
+        .add(entity::note::Column::Title.contains(term))
+        .add(plain_content_contains(term))
+        .add(entity::note::Column::Id.in_subquery(tag_like_subquery(term)))
+}
+
+/// 关键词是否包含中日韩字符
+fn has_cjk(term: &str) -> bool {
+    term.chars().any(|c| {
+        matches!(c as u32,
+            0x3040..=0x30FF // 平假名、片假名
+            | 0x3400..=0x4DBF // CJK 扩展 A
+            | 0x4E00..=0x9FFF // CJK 基本区
+            | 0xAC00..=0xD7AF // 韩文音节
+            | 0xF900..=0xFAFF) // CJK 兼容表意文字
+    })
+}
+
+/// PostgreSQL: 将关键词转换为 `to_tsquery('simple', ?)` 的参数
+///
+/// 运算符字符替换为空白，按空白切分后每个词加引号并追加 `:*`（前缀匹配），多个词用 `<->` 连接（短语匹配），
+/// 词内再由 PG 解析器切分，保证与 `to_tsvector` 的分词一致。
+/// `simple` 配置无法切分中日韩文本，含中日韩字符或无有效词时返回 None，由调用方回退到 LIKE
+fn pg_tsquery(term: &str) -> Option<String> {
+    if has_cjk(term) {
+        return None;
+    }
+
+    // tsquery 运算符替换为空白，避免破坏查询语法
+    let cleaned: String = term
+        .chars()
+        .map(|c| match c {
+            '&' | '|' | '!' | '(' | ')' | ':' | '*' | '<' | '>' | '\'' | '\\' => ' ',
+            _ => c,
+        })
+        .collect();
+
+    let words: Vec<String> = cleaned
+        .split_whitespace()
+        .filter(|w| w.chars().any(char::is_alphanumeric))
+        .map(|w| format!("'{}':*", w))
+        .collect();
+
+    if words.is_empty() {
+        None
     } else {
-        // MySQL/PG: LIKE 搜索 title、content，加上 tag name 子查询
-        let tag_subquery = Query::select()
-            .column(entity::note_tags::Column::NoteId)
-            .distinct()
-            .from(entity::note_tags::Entity)
-            .inner_join(
-                entity::tag::Entity,
-                Expr::col((entity::tag::Entity, entity::tag::Column::Id))
-                    .equals((entity::note_tags::Entity, entity::note_tags::Column::TagId)),
+        Some(words.join(" <-> "))
+    }
+}
+
+/// MySQL: 将关键词转换为 `AGAINST(? IN BOOLEAN MODE)` 的短语参数
+///
+/// ngram 解析器的最小词元为 2 个字符，更短的关键词返回 None，由调用方回退到 LIKE
+fn mysql_against_phrase(term: &str) -> Option<String> {
+    let cleaned = term.replace('"', " ");
+    let cleaned = cleaned.trim();
+    if cleaned.chars().count() < 2 {
+        None
+    } else {
+        Some(format!("\"{}\"", cleaned))
+    }
+}
+
+/// MySQL 默认全文解析器的最短词元长度（`innodb_ft_min_token_size` 默认值）
+const MYSQL_DEFAULT_MIN_CHARS: usize = 3;
+
+/// 关键词检索所用的后端：数据库类型，以及 MySQL 全文索引实际使用的解析器
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) struct SearchBackend {
+    pub db: DatabaseBackend,
+    /// MySQL 全文索引使用 ngram 解析器；服务端不支持 ngram 时迁移退化为默认解析器
+    pub mysql_ngram: bool,
+}
+
+impl From<DatabaseBackend> for SearchBackend {
+    fn from(db: DatabaseBackend) -> Self {
+        Self {
+            db,
+            mysql_ngram: true,
+        }
+    }
+}
+
+impl SearchBackend {
+    /// 检测当前连接的检索后端
+    ///
+    /// MySQL 从 note 表的建表语句中读取 `ft_note_title_content` 索引记录的解析器
+    pub(super) async fn detect(db: &DatabaseConnection) -> anyhow::Result<Self> {
+        let backend = db.get_database_backend();
+        if backend != DatabaseBackend::MySql {
+            return Ok(backend.into());
+        }
+        let create_table = db
+            .query_one_raw(Statement::from_string(backend, "SHOW CREATE TABLE note"))
+            .await?
+            .map(|row| row.try_get::<String>("", "Create Table"))
+            .transpose()?
+            .unwrap_or_default();
+        Ok(Self {
+            db: backend,
+            mysql_ngram: mysql_uses_ngram(&create_table),
+        })
+    }
+}
+
+/// MySQL: 建表语句中的 `ft_note_title_content` 索引是否使用 ngram 解析器
+fn mysql_uses_ngram(create_table: &str) -> bool {
+    create_table
+        .lines()
+        .find(|line| line.contains("`ft_note_title_content`"))
+        .is_some_and(|line| line.contains("WITH PARSER `ngram`"))
+}
+
+/// MySQL: 按标签名称全文匹配笔记 ID 的子查询（使用 `ft_tag_name` 索引）
+fn tag_fulltext_subquery(phrase: &str) -> SelectStatement {
+    Query::select()
+        .column(entity::note_tags::Column::NoteId)
+        .distinct()
+        .from(entity::note_tags::Entity)
+        .inner_join(
+            entity::tag::Entity,
+            Expr::col((entity::tag::Entity, entity::tag::Column::Id))
+                .equals((entity::note_tags::Entity, entity::note_tags::Column::TagId)),
+        )
+        .and_where(Expr::cust_with_values(
+            "MATCH(tag.name) AGAINST(? IN BOOLEAN MODE)",
+            [phrase.to_string()],
+        ))
+        .to_owned()
+}
+
+/// 相关度排序的列权重
+///
+/// 对应 FTS5 `bm25()` 的列权重（title、content、tags），
+/// 可通过设置项 `searchWeightTitle` / `searchWeightContent` / `searchWeightTags` 调整
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SearchWeights {
+    pub title: f64,
+    pub content: f64,
+    pub tags: f64,
+}
+
+impl Default for SearchWeights {
+    fn default() -> Self {
+        Self {
+            title: 10.0,
+            content: 1.0,
+            tags: 5.0,
+        }
+    }
+}
+
+impl SearchWeights {
+    /// 从设置中读取权重，缺失或非法（非有限值、负数）时使用默认值
+    pub fn from_settings(settings: &HashMap<String, String>) -> Self {
+        let defaults = Self::default();
+        let read = |key: &str, default: f64| {
+            settings
+                .get(key)
+                .and_then(|v| v.trim().parse::<f64>().ok())
+                .filter(|v| v.is_finite() && *v >= 0.0)
+                .unwrap_or(default)
+        };
+        Self {
+            title: read("searchWeightTitle", defaults.title),
+            content: read("searchWeightContent", defaults.content),
+            tags: read("searchWeightTags", defaults.tags),
+        }
+    }
+}
+
+/// 单个关键词按 LIKE 命中情况加权的得分
+fn like_term_score(term: &str, weights: &SearchWeights) -> Expr {
+    Expr::case(entity::note::Column::Title.contains(term), weights.title)
+        .finally(0.0_f64)
+        .add(Expr::case(plain_content_contains(term), weights.content).finally(0.0_f64))
+        .add(
+            Expr::case(
+                entity::note::Column::Id.in_subquery(tag_like_subquery(term)),
+                weights.tags,
             )
-            .and_where(entity::tag::Column::Name.contains(keyword))
-            .to_owned();
-
-        builder.filter(
-            Condition::all().add(
-                Condition::any()
-                    .add(entity::note::Column::Title.contains(keyword))
-                    .add(entity::note::Column::Content.contains(keyword))
-                    .add(entity::note::Column::Id.in_subquery(tag_subquery)),
+            .finally(0.0_f64),
+        )
+}
+
+/// trigram 分词器能够匹配的最短关键词长度（字符数）
+const TRIGRAM_MIN_CHARS: usize = 3;
+
+/// 单个关键词的检索路径
+///
+/// 由 `TermPlan::new` 按后端与关键词特征选择：索引无法表达的关键词
+/// （SQLite 不足 3 个字符、PG 含中日韩字符、MySQL 单字符）统一回退到 LIKE，
+/// 每个关键词各自生成一个条件并按 AND 组合，调用方无需关心具体走哪条路径
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum TermPlan {
+    /// SQLite FTS5 trigram MATCH（已转义的短语）
+    Fts(String),
+    /// PostgreSQL `search_vector @@ to_tsquery`（tsquery 文本）
+    TsQuery(String),
+    /// MySQL FULLTEXT 布尔模式短语，同时保留原始关键词用于标题加分
+    FullText { phrase: String, term: String },
+    /// LIKE 回退（原始关键词）
+    Like(String),
+}
+
+impl TermPlan {
+    fn new(term: &str, backend: SearchBackend) -> Self {
+        let like = || TermPlan::Like(term.to_string());
+        match backend.db {
+            DatabaseBackend::Sqlite if term.chars().count() >= TRIGRAM_MIN_CHARS => {
+                TermPlan::Fts(fts_phrase(term))
+            }
+            DatabaseBackend::Postgres => {
+                pg_tsquery(term).map(TermPlan::TsQuery).unwrap_or_else(like)
+            }
+            // 默认解析器按空白与标点分词，无法匹配短关键词或中日韩文本中的片段
+            DatabaseBackend::MySql
+                if !backend.mysql_ngram
+                    && (has_cjk(term) || term.chars().count() < MYSQL_DEFAULT_MIN_CHARS) =>
+            {
+                like()
+            }
+            DatabaseBackend::MySql => mysql_against_phrase(term)
+                .map(|phrase| TermPlan::FullText {
+                    phrase,
+                    term: term.to_string(),
+                })
+                .unwrap_or_else(like),
+            _ => like(),
+        }
+    }
+
+    /// 该关键词命中的过滤条件（笔记本身或其附件文本任一命中）
+    fn condition(&self) -> Condition {
+        Condition::any()
+            .add(self.note_condition())
+            .add(self.attachment_condition())
+    }
+
+    /// 附件文本命中的过滤条件，与笔记本身使用相同的检索路径
+    fn attachment_condition(&self) -> Condition {
+        let expr = match self {
+            TermPlan::Fts(phrase) => Expr::cust_with_values(
+                "note.id IN (SELECT note_id FROM note_attachment_text WHERE id IN (SELECT rowid FROM attachment_fts WHERE attachment_fts MATCH ?))",
+                [phrase.clone()],
             ),
+            TermPlan::TsQuery(tsquery) => Expr::cust_with_values(
+                "note.id IN (SELECT note_id FROM note_attachment_text WHERE to_tsvector('simple', content) @@ to_tsquery('simple', ?))",
+                [tsquery.clone()],
+            ),
+            TermPlan::FullText { phrase, .. } => Expr::cust_with_values(
+                "note.id IN (SELECT note_id FROM note_attachment_text WHERE MATCH(content) AGAINST(? IN BOOLEAN MODE))",
+                [phrase.clone()],
+            ),
+            TermPlan::Like(term) => {
+                entity::note::Column::Id.in_subquery(attachment_like_subquery(term))
+            }
+        };
+        Condition::all().add(expr)
+    }
+
+    /// 笔记本身（标题、内容、标签）命中的过滤条件
+    fn note_condition(&self) -> Condition {
+        match self {
+            TermPlan::Fts(phrase) => Condition::all().add(Expr::cust_with_values(
+                "id IN (SELECT rowid FROM note_fts WHERE note_fts MATCH ?)",
+                [phrase.clone()],
+            )),
+            TermPlan::TsQuery(tsquery) => Condition::all().add(Expr::cust_with_values(
+                "note.search_vector @@ to_tsquery('simple', ?)",
+                [tsquery.clone()],
+            )),
+            TermPlan::FullText { phrase, .. } => Condition::any()
+                .add(Expr::cust_with_values(
+                    "MATCH(note.title, note.content) AGAINST(? IN BOOLEAN MODE)",
+                    [phrase.clone()],
+                ))
+                .add(entity::note::Column::Id.in_subquery(tag_fulltext_subquery(phrase))),
+            TermPlan::Like(term) => like_term_condition(term),
+        }
+    }
+}
+
+/// SQLite: 整个查询的 BM25 得分子查询（note_id, bm25_score）
+///
+/// 所有 FTS5 关键词以 OR 合并为一个 MATCH 表达式，`bm25()` 对整个查询计分；
+/// 只执行一次 MATCH，再按笔记 ID 左连接到主查询
+fn fts_rank_subquery(expr: &str, weights: &SearchWeights) -> SelectStatement {
+    Query::select()
+        .expr_as(Expr::cust("note_fts.rowid"), Alias::new("note_id"))
+        // bm25 越小越相关，取负值
+        .expr_as(
+            Expr::cust(format!(
+                "-bm25(note_fts, {:.4}, {:.4}, {:.4})",
+                weights.title, weights.content, weights.tags
+            )),
+            Alias::new("bm25_score"),
         )
+        .from(Alias::new("note_fts"))
+        .and_where(Expr::cust_with_values(
+            "note_fts MATCH ?",
+            [expr.to_string()],
+        ))
+        .to_owned()
+}
+
+/// PostgreSQL: 整个查询（各关键词的 tsquery 以 `|` 合并）的 `ts_rank` 得分
+fn pg_rank_expr(tsquery: &str, weights: &SearchWeights) -> Expr {
+    // ts_rank 的权重数组按 {D, C, B, A} 排列且必须位于 [0, 1]，先归一化再乘回最大值
+    let max = weights.title.max(weights.content).max(weights.tags);
+    if max <= 0.0 {
+        return Expr::val(0.0_f64);
     }
+    Expr::cust_with_values(
+        format!(
+            "CAST(ts_rank('{{{:.4}, 0, {:.4}, {:.4}}}', note.search_vector, to_tsquery('simple', ?)) * {:.4} AS DOUBLE PRECISION)",
+            weights.content / max,
+            weights.tags / max,
+            weights.title / max,
+            max
+        ),
+        [tsquery.to_string()],
+    )
+}
+
+/// MySQL: 标题、标签命中单个关键词的加分
+fn mysql_term_bonus(phrase: &str, term: &str, weights: &SearchWeights) -> Expr {
+    Expr::case(entity::note::Column::Title.contains(term), weights.title)
+        .finally(0.0_f64)
+        .add(
+            Expr::case(
+                entity::note::Column::Id.in_subquery(tag_fulltext_subquery(phrase)),
+                weights.tags,
+            )
+            .finally(0.0_f64),
+        )
+}
+
+/// 选择笔记 ID 与相关度得分（`score`，越高越相关）
+///
+/// 全文索引的关键词合并为一个检索表达式，对整个查询计分：
+/// SQLite FTS5 取 BM25（左连接一次 MATCH 子查询），PostgreSQL 取 `ts_rank`
+/// （title A / tags B / content D），MySQL 取全文匹配得分并叠加各关键词的标题、标签加分；
+/// 回退到 LIKE 的关键词按各列命中加权后累加
+fn select_relevance(
+    builder: Select<entity::note::Entity>,
+    query: &SearchQuery,
+    weights: &SearchWeights,
+    backend: SearchBackend,
+) -> Select<entity::note::Entity> {
+    let mut builder = builder.select_only().column(entity::note::Column::Id);
+    let mut score = Expr::val(0.0_f64);
+    let mut fts_phrases = Vec::new();
+    let mut tsqueries = Vec::new();
+    let mut against_phrases = Vec::new();
+
+    for term in query.text_terms() {
+        match TermPlan::new(term, backend) {
+            TermPlan::Fts(phrase) => fts_phrases.push(phrase),
+            TermPlan::TsQuery(tsquery) => tsqueries.push(format!("({})", tsquery)),
+            TermPlan::FullText { phrase, term } => {
+                score = score.add(mysql_term_bonus(&phrase, &term, weights));
+                against_phrases.push(phrase);
+            }
+            TermPlan::Like(term) => score = score.add(like_term_score(&term, weights)),
+        }
+    }
+
+    if !fts_phrases.is_empty() {
+        let fts_rank = Alias::new("fts_rank");
+        QueryTrait::query(&mut builder).join_subquery(
+            JoinType::LeftJoin,
+            fts_rank_subquery(&fts_phrases.join(" OR "), weights),
+            fts_rank.clone(),
+            Expr::col((fts_rank, Alias::new("note_id")))
+                .equals((entity::note::Entity, entity::note::Column::Id)),
+        );
+        score = score.add(Expr::cust("COALESCE(fts_rank.bm25_score, 0.0)"));
+    }
+    if !tsqueries.is_empty() {
+        score = score.add(pg_rank_expr(&tsqueries.join(" | "), weights));
+    }
+    if !against_phrases.is_empty() {
+        score = score.add(Expr::cust_with_values(
+            format!(
+                "(MATCH(note.title, note.content) AGAINST(? IN BOOLEAN MODE) * {:.4})",
+                weights.content
+            ),
+            [against_phrases.join(" ")],
+        ));
+    }
+
+    builder.expr_as(score, "score")
+}
+
+/// Build keyword filter condition based on database backend
+///
+/// 每个关键词按 `TermPlan` 选择检索路径后以 AND 组合，排除关键词取反。
+/// 单个关键词可以命中笔记本身或其附件文本，因此各关键词分别生成条件，
+/// 不再将 SQLite 的 FTS5 关键词合并为一个 MATCH
+pub(super) fn apply_keyword_filter<E: EntityTrait>(
+    builder: Select<E>,
+    query: &SearchQuery,
+    backend: SearchBackend,
+) -> Select<E> {
+    let mut b = builder;
+
+    for term in query.text_terms() {
+        b = b.filter(TermPlan::new(term, backend).condition());
+    }
+
+    for term in query.excluded_terms() {
+        b = b.filter(TermPlan::new(term, backend).condition().not());
+    }
+
+    b
+}
+
+/// 将结构化查询 AST 应用到查询构建器
+///
+/// 限定符（标签、笔记本、状态、日期）在两种后端上使用相同的 SQL 条件，
+/// 全文关键词交给 `apply_keyword_filter` 按后端分别处理
+pub(super) fn apply_query_filter<E: EntityTrait>(
+    builder: Select<E>,
+    query: &SearchQuery,
+    backend: SearchBackend,
+) -> Select<E> {
+    let mut b = builder;
+
+    for node in &query.nodes {
+        b = match node {
+            QueryNode::Tag { name, negated } => {
+                let sub_query = tag_name_subquery(name);
+                if *negated {
+                    b.filter(entity::note::Column::Id.not_in_subquery(sub_query))
+                } else {
+                    b.filter(entity::note::Column::Id.in_subquery(sub_query))
+                }
+            }
+            QueryNode::Notebook { name, negated } => {
+                let sub_query = notebook_name_subquery(name);
+                if *negated {
+                    b.filter(entity::note::Column::NotebookId.not_in_subquery(sub_query))
+                } else {
+                    b.filter(entity::note::Column::NotebookId.in_subquery(sub_query))
+                }
+            }
+            QueryNode::Is { flag, negated } => {
+                let value = if *negated { 0 } else { 1 };
+                match flag {
+                    QueryFlag::Starred => b.filter(entity::note::Column::IsStarred.eq(value)),
+                    QueryFlag::Pinned => b.filter(entity::note::Column::IsPinned.eq(value)),
+                }
+            }
+            QueryNode::Before(date) => {
+                b.filter(entity::note::Column::UpdateTime.lt(start_of_day(*date)))
+            }
+            QueryNode::After(date) => {
+                b.filter(entity::note::Column::UpdateTime.gte(start_of_day(*date)))
+            }
+            QueryNode::Text { .. } => b,
+        };
+    }
+
+    apply_keyword_filter(b, query, backend)
 }
 
 /// Apply common search filters (notebook, tag, keyword) to a query builder
-/// 默认排除已软删除的笔记
+/// 多个笔记本按 OR 组合；标签支持 all / any / none 三种组合，可同时使用，
+/// 笔记本与标签筛选均可选择包含子孙节点
+/// 默认排除已软删除的笔记，关键词按结构化搜索语法解析
 pub(super) fn apply_search_filters<E: EntityTrait>(
     builder: Select<E>,
     search_param: &NoteSearchPageParam,
-    is_sqlite: bool,
+    backend: SearchBackend,
 ) -> Select<E> {
     let mut b = builder;
     // 排除已软删除的笔记
     b = b.filter(entity::note::Column::DeletedAt.is_null());
     if search_param.notebook_id > 0 {
-        b = b.filter(entity::note::Column::NotebookId.eq(search_param.notebook_id));
+        b = if search_param.include_descendants {
+            b.filter(notebook_tree_condition(&[search_param.notebook_id]))
+        } else {
+            b.filter(entity::note::Column::NotebookId.eq(search_param.notebook_id))
+        };
+    }
+    if !search_param.notebook_ids.is_empty() {
+        b = if search_param.include_descendants {
+            b.filter(notebook_tree_condition(&search_param.notebook_ids))
+        } else {
+            b.filter(entity::note::Column::NotebookId.is_in(search_param.notebook_ids.clone()))
+        };
     }
+    let with_children = search_param.include_tag_descendants;
     if search_param.tag_id > 0 {
-        let sub_query = Query::select()
-            .column(entity::note_tags::Column::NoteId)
-            .distinct()
-            .and_where(Expr::col(entity::note_tags::Column::TagId).eq(Expr::val(search_param.tag_id)))
-            .from(entity::note_tags::Entity)
-            .to_owned();
-        b = b.filter(Condition::any().add(entity::note::Column::Id.in_subquery(sub_query)));
+        b = b.filter(tag_condition(&[search_param.tag_id], with_children, false));
+    }
+    // all: 每个标签各自一个子查询，全部命中
+    for tag_id in &search_param.all_tag_ids {
+        b = b.filter(tag_condition(&[*tag_id], with_children, false));
+    }
+    // any: 命中任一标签
+    if !search_param.any_tag_ids.is_empty() {
+        b = b.filter(tag_condition(
+            &search_param.any_tag_ids,
+            with_children,
+            false,
+        ));
+    }
+    // none: 不包含任何一个标签
+    if !search_param.exclude_tag_ids.is_empty() {
+        b = b.filter(tag_condition(
+            &search_param.exclude_tag_ids,
+            with_children,
+            true,
+        ));
     }
     if search_param.is_starred {
         b = b.filter(entity::note::Column::IsStarred.eq(1));
     }
     if !search_param.keyword.is_empty() {
-        b = apply_keyword_filter(b, search_param.keyword.as_str(), is_sqlite);
+        let query = SearchQuery::parse(&search_param.keyword);
+        b = apply_query_filter(b, &query, backend);
     }
     b
 }
@@ -111,8 +682,8 @@ pub async fn find_by_id(db: &DatabaseConnection, id: i64) -> anyhow::Result<Opti
 }
 
 /// 根据 ID 查询笔记（支持解密）
-pub async fn find_by_id_with_key(
-    db: &DatabaseConnection,
+pub async fn find_by_id_with_key<C: ConnectionTrait>(
+    db: &C,
     id: i64,
     encryption_key: Option<&str>,
 ) -> anyhow::Result<Option<Note>> {
@@ -200,128 +771,240 @@ pub async fn search_page(
     search_page_with_key(db, search_param, None).await
 }
 
-/// 分页搜索笔记（支持解密）
-pub async fn search_page_with_key(
+/// 搜索结果的排序方向
+fn sort_order(search_param: &NoteSearchPageParam) -> Order {
+    if search_param.sort_order == "asc" {
+        Order::Asc
+    } else {
+        Order::Desc
+    }
+}
+
+/// 搜索结果的排序列（relevance 以外的排序字段）
+pub(super) fn sort_column(search_param: &NoteSearchPageParam) -> entity::note::Column {
+    match search_param.sort_field.as_str() {
+        "title" => entity::note::Column::Title,
+        "create_time" => entity::note::Column::CreateTime,
+        _ => entity::note::Column::UpdateTime,
+    }
+}
+
+/// 是否按相关度排序（需要关键词中包含全文检索词）
+pub(super) fn by_relevance(search_param: &NoteSearchPageParam, query: &SearchQuery) -> bool {
+    search_param.sort_field == "relevance" && query.text_terms().next().is_some()
+}
+
+/// 统计满足条件的笔记总数
+pub(super) async fn count_notes(
     db: &DatabaseConnection,
     search_param: &NoteSearchPageParam,
-    encryption_key: Option<&str>,
-) -> anyhow::Result<PageResult<Note>> {
-    search_param.validate()?;
+    backend: SearchBackend,
+) -> anyhow::Result<i64> {
+    Ok(
+        apply_search_filters(entity::note::Entity::find(), search_param, backend)
+            .select_only()
+            .column_as(Expr::col(Asterisk).count(), "count")
+            .into_tuple::<i64>()
+            .one(db)
+            .await?
+            .unwrap_or_default(),
+    )
+}
+
+/// 加载一页笔记（未填充笔记本、标签，未解密）
+///
+/// 按相关度排序时先取当前页的 (id, score)，再批量加载笔记并保持顺序；
+/// 否则按置顶、排序列、ID 排序
+pub(super) async fn load_page(
+    db: &DatabaseConnection,
+    query_builder: Select<entity::note::Entity>,
+    search_param: &NoteSearchPageParam,
+    query: &SearchQuery,
+    start: u64,
+    limit: u64,
+    backend: SearchBackend,
+) -> anyhow::Result<Vec<Note>> {
+    let order = sort_order(search_param);
 
-    let is_sqlite = db.get_database_backend() == DatabaseBackend::Sqlite;
+    if by_relevance(search_param, query) {
+        let weights = SearchWeights::from_settings(&settings::get_all(db).await?);
+        let ranked = select_relevance(query_builder, query, &weights, backend)
+            .order_by(Expr::cust("score"), order)
+            .order_by_desc(entity::note::Column::Id)
+            .offset(start)
+            .limit(limit)
+            .into_tuple::<(i64, f64)>()
+            .all(db)
+            .await?;
 
-    let count_builder = apply_search_filters(entity::note::Entity::find(), search_param, is_sqlite);
-    let query_builder = apply_search_filters(entity::note::Entity::find(), search_param, is_sqlite);
+        let mut model_map = entity::note::Entity::find()
+            .filter(entity::note::Column::Id.is_in(ranked.iter().map(|(id, _)| *id)))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|m| (m.id, m))
+            .collect::<HashMap<i64, entity::note::Model>>();
 
-    let total = count_builder
-        .select_only()
-        .column_as(Expr::col(Asterisk).count(), "count")
-        .into_tuple::<i64>()
-        .one(db)
+        return Ok(ranked
+            .into_iter()
+            .filter_map(|(id, score)| {
+                model_map.remove(&id).map(|m| {
+                    let mut note = Note::from(m);
+                    note.score = Some(score);
+                    note
+                })
+            })
+            .collect());
+    }
+
+    Ok(query_builder
+        .offset(start)
+        .limit(limit)
+        .order_by_desc(entity::note::Column::IsPinned)
+        .order_by(sort_column(search_param), order)
+        .order_by_desc(entity::note::Column::Id)
+        .all(db)
         .await?
-        .unwrap_or_default();
+        .into_iter()
+        .map(Note::from)
+        .collect())
+}
 
-    if total > 0 {
-        let start = search_param.page_param.start() as u64;
-        let page_size = search_param.page_param.page_size as u64;
+/// 为搜索结果填充笔记本名称、标签，解密内容并生成命中片段
+pub(super) async fn fill_search_results(
+    db: &DatabaseConnection,
+    notes: &mut [Note],
+    query: &SearchQuery,
+    encryption_key: Option<&str>,
+) -> anyhow::Result<()> {
+    let mut note_ids = Vec::<i64>::with_capacity(notes.len());
+    let mut notebook_ids = HashSet::<i64>::new();
 
-        let order = if search_param.sort_order == "asc" {
-            Order::Asc
-        } else {
-            Order::Desc
-        };
-        let sort_col = match search_param.sort_field.as_str() {
-            "title" => entity::note::Column::Title,
-            "create_time" => entity::note::Column::CreateTime,
-            _ => entity::note::Column::UpdateTime,
-        };
+    for note in notes.iter() {
+        note_ids.push(note.id);
 
-        let mut notes: Vec<Note> = query_builder
-            .offset(start)
-            .limit(page_size)
-            .order_by_desc(entity::note::Column::IsPinned)
-            .order_by(sort_col, order.clone())
-            .order_by_desc(entity::note::Column::Id)
+        if note.notebook_id > 0 {
+            notebook_ids.insert(note.notebook_id);
+        }
+    }
+
+    if note_ids.is_empty() {
+        return Ok(());
+    }
+
+    if !notebook_ids.is_empty() {
+        let notebook_map = notebook::Entity::find()
+            .filter(notebook::Column::Id.is_in(notebook_ids))
             .all(db)
             .await?
             .into_iter()
-            .map(Note::from)
-            .collect();
+            .map(|e| (e.id, e.name))
+            .collect::<HashMap<i64, String>>();
+
+        for note in notes.iter_mut() {
+            if let Some(notebook_name) = notebook_map.get(&note.notebook_id) {
+                note.notebook_name = notebook_name.clone();
+            }
+        }
+    }
 
-        let mut note_ids = Vec::<i64>::with_capacity(notes.len());
-        let mut notebook_ids = HashSet::<i64>::new();
+    let note_tags = entity::note_tags::Entity::find()
+        .filter(entity::note_tags::Column::NoteId.is_in(note_ids.clone()))
+        .order_by_asc(entity::note_tags::Column::SortOrder)
+        .order_by_asc(entity::note_tags::Column::Id)
+        .all(db)
+        .await?;
 
-        for note in notes.iter() {
-            note_ids.push(note.id);
+    if !note_tags.is_empty() {
+        let mut note_tags_map = HashMap::<i64, Vec<i64>>::new();
 
-            if note.notebook_id > 0 {
-                notebook_ids.insert(note.notebook_id);
-            }
+        for note_tag in note_tags.iter() {
+            note_tags_map
+                .entry(note_tag.note_id)
+                .or_default()
+                .push(note_tag.tag_id);
         }
 
-        if !note_ids.is_empty() {
-            let mut notebook_map = HashMap::<i64, String>::with_capacity(notebook_ids.len());
-
-            if !notebook_ids.is_empty() {
-                notebook_map = notebook::Entity::find()
-                    .filter(notebook::Column::Id.is_in(notebook_ids))
-                    .all(db)
-                    .await?
-                    .into_iter()
-                    .map(|e| (e.id, e.name))
-                    .collect::<HashMap<i64, String>>();
-            }
+        let tag_ids = note_tags.iter().map(|e| e.tag_id).collect::<HashSet<i64>>();
+        let tag_map = entity::tag::Entity::find()
+            .filter(entity::tag::Column::Id.is_in(tag_ids))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|e| {
+                let id = e.id;
+                (id, Tag::from(e))
+            })
+            .collect::<HashMap<i64, Tag>>();
 
-            for note in notes.iter_mut() {
-                if let Some(notebook_name) = notebook_map.get(&note.notebook_id) {
-                    note.notebook_name = notebook_name.clone();
-                }
+        for note in notes.iter_mut() {
+            if let Some(tag_ids) = note_tags_map.get(&note.id) {
+                note.tags = tag_ids
+                    .iter()
+                    .filter_map(|id| tag_map.get(id).cloned())
+                    .collect();
             }
+        }
+    }
 
-            let note_tags = entity::note_tags::Entity::find()
-                .filter(entity::note_tags::Column::NoteId.is_in(note_ids))
-                .order_by_asc(entity::note_tags::Column::SortOrder)
-                .order_by_asc(entity::note_tags::Column::Id)
-                .all(db)
-                .await?;
-
-            if !note_tags.is_empty() {
-                let mut note_tags_map = HashMap::<i64, Vec<i64>>::new();
-
-                for note_tag in note_tags.iter() {
-                    note_tags_map
-                        .entry(note_tag.note_id)
-                        .or_default()
-                        .push(note_tag.tag_id);
-                }
+    // 透明解密
+    for note in notes.iter_mut() {
+        decrypt_note(note, encryption_key);
+    }
 
-                let tag_ids = note_tags.iter().map(|e| e.tag_id).collect::<HashSet<i64>>();
-                let tag_map = entity::tag::Entity::find()
-                    .filter(entity::tag::Column::Id.is_in(tag_ids))
-                    .all(db)
-                    .await?
-                    .into_iter()
-                    .map(|e| {
-                        let id = e.id;
-                        (id, Tag::from(e))
-                    })
-                    .collect::<HashMap<i64, Tag>>();
-
-                for note in notes.iter_mut() {
-                    if let Some(tag_ids) = note_tags_map.get(&note.id) {
-                        note.tags = tag_ids
-                            .iter()
-                            .filter_map(|id| tag_map.get(id).cloned())
-                            .collect();
-                    }
-                }
-            }
+    // 生成命中片段（需在解密之后进行）
+    if !query.is_empty() {
+        for note in notes.iter_mut() {
+            note.snippets = build_snippets(note, query.text_terms());
         }
+    }
 
-        // 透明解密
+    // 附件文本命中：补充 attachment 片段并标记
+    if query.text_terms().next().is_some() {
+        let attachment_texts = attachment_text::find_by_note_ids(db, &note_ids).await?;
         for note in notes.iter_mut() {
-            decrypt_note(note, encryption_key);
+            let snippet = attachment_texts.get(&note.id).and_then(|texts| {
+                texts
+                    .iter()
+                    .find_map(|text| content_snippet("attachment", text, 1, query.text_terms()))
+            });
+            if let Some(snippet) = snippet {
+                note.attachment_match = true;
+                note.snippets.push(snippet);
+            }
         }
+    }
+
+    Ok(())
+}
+
+/// 分页搜索笔记（支持解密）
+pub async fn search_page_with_key(
+    db: &DatabaseConnection,
+    search_param: &NoteSearchPageParam,
+    encryption_key: Option<&str>,
+) -> anyhow::Result<PageResult<Note>> {
+    search_param.validate()?;
+
+    let backend = SearchBackend::detect(db).await?;
+    let total = count_notes(db, search_param, backend).await?;
+
+    if total > 0 {
+        let query = SearchQuery::parse(&search_param.keyword);
+        let query_builder =
+            apply_search_filters(entity::note::Entity::find(), search_param, backend);
+
+        let mut notes = load_page(
+            db,
+            query_builder,
+            search_param,
+            &query,
+            search_param.page_param.start() as u64,
+            search_param.page_param.page_size as u64,
+            backend,
+        )
+        .await?;
+        fill_search_results(db, &mut notes, &query, encryption_key).await?;
 
         let mut page_result = PageResult::<Note>::with_data(total, notes);
 
@@ -333,22 +1016,23 @@ pub async fn search_page_with_key(
     Ok(PageResult::default())
 }
 
+/// 统计满足条件的笔记数量（总数与各笔记本的数量），并附带各保存的搜索的命中数量
 pub async fn stats(
     db: &DatabaseConnection,
     search_param: &NoteSearchPageParam,
 ) -> anyhow::Result<NoteStatsResult> {
     search_param.validate()?;
 
-    let is_sqlite = db.get_database_backend() == DatabaseBackend::Sqlite;
+    let backend = SearchBackend::detect(db).await?;
 
-    let count_builder = apply_search_filters(entity::note::Entity::find(), search_param, is_sqlite);
+    let count_builder = apply_search_filters(entity::note::Entity::find(), search_param, backend);
     let count_map_builder = apply_search_filters(
         entity::note::Entity::find()
             .select_only()
             .column(entity::note::Column::NotebookId)
             .column_as(entity::note::Column::Id.count(), "n"),
         search_param,
-        is_sqlite,
+        backend,
     );
 
     let total = count_builder
@@ -359,20 +1043,289 @@ pub async fn stats(
         .await?
         .unwrap_or_default();
 
-    if total > 0 {
-        let notebook_counts = count_map_builder
+    let notebook_counts = if total > 0 {
+        count_map_builder
             .group_by(entity::note::Column::NotebookId)
             .into_tuple::<(i64, i64)>()
             .all(db)
             .await?
             .into_iter()
-            .collect::<HashMap<i64, i64>>();
+            .collect::<HashMap<i64, i64>>()
+    } else {
+        HashMap::new()
+    };
+
+    Ok(NoteStatsResult {
+        total,
+        notebook_counts,
+        saved_search_counts: saved_search::counts(db).await?,
+    })
+}
+
+/// 多组搜索条件合并为一条 SELECT：每组条件一个 `COUNT(*)` 标量子查询，列名为 `c{序号}`
+fn count_many_statement(
+    search_params: &[NoteSearchPageParam],
+    backend: SearchBackend,
+) -> SelectStatement {
+    let mut statement = Query::select();
+    for (i, search_param) in search_params.iter().enumerate() {
+        let count = apply_search_filters(
+            entity::note::Entity::find()
+                .select_only()
+                .column_as(Expr::col(Asterisk).count(), "count"),
+            search_param,
+            backend,
+        )
+        .into_query();
+        statement.expr_as(
+            Expr::SubQuery(None, Box::new(count.into())),
+            Alias::new(format!("c{}", i)),
+        );
+    }
+    statement
+}
+
+/// 一次查询统计多组搜索条件各自命中的笔记数量
+///
+/// # 返回
+/// - `Ok(Vec<i64>)`: 命中数量，与 `search_params` 顺序一致
+pub async fn count_many(
+    db: &DatabaseConnection,
+    search_params: &[NoteSearchPageParam],
+) -> anyhow::Result<Vec<i64>> {
+    if search_params.is_empty() {
+        return Ok(Vec::new());
+    }
+    for search_param in search_params {
+        search_param.validate()?;
+    }
+
+    let statement = count_many_statement(search_params, SearchBackend::detect(db).await?);
+    let Some(row) = db.query_one(&statement).await? else {
+        return Ok(vec![0; search_params.len()]);
+    };
+    (0..search_params.len())
+        .map(|i| Ok(row.try_get::<i64>("", &format!("c{}", i))?))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 按搜索参数生成的 SQLite SQL
+    fn filter_sql(param: &NoteSearchPageParam) -> String {
+        apply_search_filters(
+            entity::note::Entity::find(),
+            param,
+            DatabaseBackend::Sqlite.into(),
+        )
+        .build(DatabaseBackend::Sqlite)
+        .to_string()
+    }
+
+    #[test]
+    fn filters_by_multiple_notebooks() {
+        let mut param = NoteSearchPageParam {
+            notebook_ids: vec![1, 2],
+            ..Default::default()
+        };
+        let sql = filter_sql(&param);
+        assert!(sql.contains(r#""note"."notebook_id" IN (1, 2)"#), "{}", sql);
+        assert!(!sql.contains("notebook_tree"), "{}", sql);
+
+        param.include_descendants = true;
+        let sql = filter_sql(&param);
+        assert!(
+            sql.contains("SELECT id FROM notebook WHERE id IN (1, 2) UNION"),
+            "{}",
+            sql
+        );
+        assert!(!sql.contains(r#""note"."notebook_id" IN"#), "{}", sql);
+    }
 
-        return Ok(NoteStatsResult {
-            total,
-            notebook_counts,
+    #[test]
+    fn filters_by_all_any_none_tags() {
+        let sql = filter_sql(&NoteSearchPageParam {
+            all_tag_ids: vec![1, 2],
+            any_tag_ids: vec![3, 4],
+            exclude_tag_ids: vec![5],
+            ..Default::default()
         });
+        let tag_in = |ids: &str| format!(r#"WHERE "note_tags"."tag_id" IN ({}))"#, ids);
+        // all: 每个标签各自一个子查询
+        assert!(
+            sql.contains(&format!(
+                r#""note"."id" IN (SELECT DISTINCT "note_id" FROM "note_tags" {}"#,
+                tag_in("1")
+            )),
+            "{}",
+            sql
+        );
+        assert!(
+            sql.contains(&format!(
+                r#""note"."id" IN (SELECT DISTINCT "note_id" FROM "note_tags" {}"#,
+                tag_in("2")
+            )),
+            "{}",
+            sql
+        );
+        // any: 一个子查询命中任一标签
+        assert!(sql.contains(&tag_in("3, 4")), "{}", sql);
+        // none: 排除任一标签
+        assert!(
+            sql.contains(&format!(
+                r#""note"."id" NOT IN (SELECT DISTINCT "note_id" FROM "note_tags" {}"#,
+                tag_in("5")
+            )),
+            "{}",
+            sql
+        );
+    }
+
+    #[test]
+    fn counts_many_filters_in_one_statement() {
+        let params = [
+            NoteSearchPageParam {
+                notebook_id: 1,
+                ..Default::default()
+            },
+            NoteSearchPageParam {
+                is_starred: true,
+                ..Default::default()
+            },
+        ];
+        let sql = count_many_statement(&params, DatabaseBackend::Sqlite.into())
+            .to_string(sea_orm::sea_query::SqliteQueryBuilder);
+        assert!(sql.starts_with("SELECT (SELECT COUNT(*)"), "{}", sql);
+        assert!(
+            sql.contains(r#""note"."notebook_id" = 1) AS "c0""#),
+            "{}",
+            sql
+        );
+        assert!(
+            sql.contains(r#""note"."is_starred" = 1) AS "c1""#),
+            "{}",
+            sql
+        );
     }
 
-    Ok(NoteStatsResult::default())
+    #[test]
+    fn tag_filters_expand_descendants() {
+        let sql = filter_sql(&NoteSearchPageParam {
+            any_tag_ids: vec![3, 4],
+            exclude_tag_ids: vec![5],
+            include_tag_descendants: true,
+            ..Default::default()
+        });
+        assert!(
+            sql.contains("note.id IN (SELECT note_id FROM note_tags WHERE tag_id IN (WITH RECURSIVE tag_tree(id) AS (SELECT id FROM tag WHERE id IN (3, 4) UNION"),
+            "{}",
+            sql
+        );
+        assert!(
+            sql.contains("note.id NOT IN (SELECT note_id FROM note_tags WHERE tag_id IN (WITH RECURSIVE tag_tree(id) AS (SELECT id FROM tag WHERE id IN (5) UNION"),
+            "{}",
+            sql
+        );
+    }
+
+    #[test]
+    fn sqlite_short_terms_fall_back_to_like() {
+        let backend = DatabaseBackend::Sqlite.into();
+        assert_eq!(
+            TermPlan::new("会议", backend),
+            TermPlan::Like("会议".to_string())
+        );
+        assert_eq!(
+            TermPlan::new("AI", backend),
+            TermPlan::Like("AI".to_string())
+        );
+        assert_eq!(
+            TermPlan::new("会议纪要", backend),
+            TermPlan::Fts("\"会议纪要\"".to_string())
+        );
+        assert_eq!(
+            TermPlan::new("AI会", backend),
+            TermPlan::Fts("\"AI会\"".to_string())
+        );
+    }
+
+    #[test]
+    fn relevance_runs_one_match_for_whole_query() {
+        let query = SearchQuery::parse("会议纪要 项目计划 AI");
+        let sql = select_relevance(
+            entity::note::Entity::find(),
+            &query,
+            &SearchWeights::default(),
+            DatabaseBackend::Sqlite.into(),
+        )
+        .build(DatabaseBackend::Sqlite)
+        .to_string();
+        assert_eq!(sql.matches("MATCH").count(), 1, "{}", sql);
+        assert!(
+            sql.contains(r#"note_fts MATCH '"会议纪要" OR "项目计划"'"#),
+            "{}",
+            sql
+        );
+        assert!(sql.contains(r#"LEFT JOIN (SELECT"#), "{}", sql);
+        // 短关键词仍按 LIKE 加分
+        assert!(sql.contains("%AI%"), "{}", sql);
+    }
+
+    #[test]
+    fn native_backends_choose_index_or_like() {
+        assert_eq!(
+            TermPlan::new("Rust's async", DatabaseBackend::Postgres.into()),
+            TermPlan::TsQuery("'Rust':* <-> 's':* <-> 'async':*".to_string())
+        );
+        assert_eq!(
+            TermPlan::new("会议", DatabaseBackend::Postgres.into()),
+            TermPlan::Like("会议".to_string())
+        );
+        assert_eq!(
+            TermPlan::new("会议", DatabaseBackend::MySql.into()),
+            TermPlan::FullText {
+                phrase: "\"会议\"".to_string(),
+                term: "会议".to_string()
+            }
+        );
+        assert_eq!(
+            TermPlan::new("C", DatabaseBackend::MySql.into()),
+            TermPlan::Like("C".to_string())
+        );
+    }
+
+    #[test]
+    fn mysql_default_parser_falls_back_to_like() {
+        let ngram = "  FULLTEXT KEY `ft_note_title_content` (`title`,`content`) /*!50100 WITH PARSER `ngram` */ ,";
+        let default = "  FULLTEXT KEY `ft_note_title_content` (`title`,`content`),";
+        assert!(mysql_uses_ngram(ngram));
+        assert!(!mysql_uses_ngram(default));
+        assert!(!mysql_uses_ngram(""));
+
+        let backend = SearchBackend {
+            db: DatabaseBackend::MySql,
+            mysql_ngram: false,
+        };
+        assert_eq!(
+            TermPlan::new("会议", backend),
+            TermPlan::Like("会议".to_string())
+        );
+        assert_eq!(
+            TermPlan::new("会议纪要", backend),
+            TermPlan::Like("会议纪要".to_string())
+        );
+        assert_eq!(
+            TermPlan::new("AI", backend),
+            TermPlan::Like("AI".to_string())
+        );
+        assert_eq!(
+            TermPlan::new("rust", backend),
+            TermPlan::FullText {
+                phrase: "\"rust\"".to_string(),
+                term: "rust".to_string()
+            }
+        );
+    }
 }
@@ -0,0 +1,306 @@
+//! 搜索命中片段提取
+//!
+//! 为关键词搜索结果生成带高亮区间的上下文片段，说明笔记为何命中。
+//!
+//! `note_fts` 是以 `note` 为外部内容表的 FTS5 索引，其中 `tags` 列在 `note` 表中
+//! 并不存在，且 `content` 列保存的是原始 HTML / 加密密文，因此 FTS5 的
+//! `snippet()` / `highlight()` 无法直接使用。片段统一在取回当前页、完成解密后
+//! 于内存中提取，SQLite 与 MySQL/PG 行为一致。
+
+use crate::model::{HighlightRange, Note, SearchSnippet};
+use crate::service::crypto;
+
+/// 内容片段中命中位置之前保留的字符数
+const CONTEXT_BEFORE: usize = 40;
+/// 内容片段的最大字符数
+const SNIPPET_LENGTH: usize = 160;
+/// 截断标记
+const ELLIPSIS: &str = "…";
+
+/// 去除 HTML 标签并解码常见实体
+///
+/// `line_breaks` 为 true 时块级元素与换行标签替换为换行，其余标签替换为空格；
+/// 为 false 时所有标签都替换为空格
+pub fn strip_html(content: &str, line_breaks: bool) -> String {
+    let mut text = String::with_capacity(content.len());
+    let mut tag = String::new();
+    let mut in_tag = false;
+    for ch in content.chars() {
+        match ch {
+            '<' => {
+                in_tag = true;
+                tag.clear();
+            }
+            '>' if in_tag => {
+                in_tag = false;
+                text.push(if line_breaks && is_block_tag(&tag) {
+                    '\n'
+                } else {
+                    ' '
+                });
+            }
+            _ if in_tag => tag.push(ch),
+            _ => text.push(ch),
+        }
+    }
+    decode_entities(&text)
+}
+
+/// 解码常见 HTML 实体（`&amp;` 最后解码，避免重复解码）
+pub(super) fn decode_entities(text: &str) -> String {
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// 标签（不含尖括号）是否为块级元素或换行标签
+fn is_block_tag(tag: &str) -> bool {
+    let name = tag
+        .trim_start_matches('/')
+        .split(|c: char| c.is_whitespace() || c == '/')
+        .next()
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    matches!(
+        name.as_str(),
+        "p" | "div"
+            | "br"
+            | "li"
+            | "tr"
+            | "h1"
+            | "h2"
+            | "h3"
+            | "h4"
+            | "h5"
+            | "h6"
+            | "pre"
+            | "blockquote"
+            | "hr"
+    )
+}
+
+/// 将笔记内容转换为纯文本
+///
+/// HTML 去除标签并解码常见实体；Markdown 保留原文。两者都会合并连续空白。
+pub fn content_to_text(content: &str, content_type: i32) -> String {
+    let text = if content_type == 1 {
+        content.to_string()
+    } else {
+        strip_html(content, false)
+    };
+
+    text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+/// 小写化单个字符（多字符展开时取首字符，保证偏移一一对应）
+fn fold(ch: char) -> char {
+    ch.to_lowercase().next().unwrap_or(ch)
+}
+
+/// 查找所有关键词在文本中的命中区间（字符偏移，已排序且不重叠）
+fn find_ranges(text: &[char], terms: &[Vec<char>]) -> Vec<HighlightRange> {
+    let mut ranges: Vec<HighlightRange> = Vec::new();
+
+    for term in terms {
+        if term.is_empty() || term.len() > text.len() {
+            continue;
+        }
+        let mut i = 0;
+        while i + term.len() <= text.len() {
+            if text[i..i + term.len()]
+                .iter()
+                .zip(term.iter())
+                .all(|(a, b)| fold(*a) == *b)
+            {
+                ranges.push(HighlightRange {
+                    start: i,
+                    end: i + term.len(),
+                });
+                i += term.len();
+            } else {
+                i += 1;
+            }
+        }
+    }
+
+    ranges.sort_by_key(|r| (r.start, r.end));
+
+    // 合并重叠区间
+    let mut merged: Vec<HighlightRange> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// 从纯文本中截取以首个命中为中心的片段
+fn extract_window(field: &str, text: &str, terms: &[Vec<char>]) -> Option<SearchSnippet> {
+    let chars: Vec<char> = text.chars().collect();
+    let ranges = find_ranges(&chars, terms);
+    let first = ranges.first()?;
+
+    let start = first.start.saturating_sub(CONTEXT_BEFORE);
+    let end = (start + SNIPPET_LENGTH).min(chars.len());
+
+    let prefix = if start > 0 { ELLIPSIS } else { "" };
+    let suffix = if end < chars.len() { ELLIPSIS } else { "" };
+    let offset = prefix.chars().count();
+
+    let text = format!(
+        "{}{}{}",
+        prefix,
+        chars[start..end].iter().collect::<String>(),
+        suffix
+    );
+    let ranges = ranges
+        .into_iter()
+        .filter(|r| r.start >= start && r.end <= end)
+        .map(|r| HighlightRange {
+            start: r.start - start + offset,
+            end: r.end - start + offset,
+        })
+        .collect();
+
+    Some(SearchSnippet {
+        field: field.to_string(),
+        text,
+        ranges,
+    })
+}
+
+/// 将关键词转换为小写字符序列
+fn fold_terms<'a>(terms: impl IntoIterator<Item = &'a str>) -> Vec<Vec<char>> {
+    terms
+        .into_iter()
+        .map(|t| t.chars().map(fold).collect())
+        .collect()
+}
+
+/// 为单个内容字段生成命中片段
+///
+/// 用于笔记以外的内容（如历史版本）；加密内容或未命中时返回 None。
+pub fn content_snippet<'a>(
+    field: &str,
+    content: &str,
+    content_type: i32,
+    terms: impl IntoIterator<Item = &'a str>,
+) -> Option<SearchSnippet> {
+    if crypto::is_encrypted(content) {
+        return None;
+    }
+    let terms = fold_terms(terms);
+    extract_window(field, &content_to_text(content, content_type), &terms)
+}
+
+/// 为一条笔记生成命中片段
+///
+/// 依次检查标题、内容和标签；加密且未解密的内容不参与匹配。
+pub fn build_snippets<'a>(
+    note: &Note,
+    terms: impl IntoIterator<Item = &'a str>,
+) -> Vec<SearchSnippet> {
+    let terms = fold_terms(terms);
+
+    if terms.is_empty() {
+        return Vec::new();
+    }
+
+    let mut snippets = Vec::new();
+
+    let title: Vec<char> = note.title.chars().collect();
+    let title_ranges = find_ranges(&title, &terms);
+    if !title_ranges.is_empty() {
+        snippets.push(SearchSnippet {
+            field: "title".to_string(),
+            text: note.title.clone(),
+            ranges: title_ranges,
+        });
+    }
+
+    if !crypto::is_encrypted(&note.content) {
+        let text = content_to_text(&note.content, note.content_type);
+        if let Some(snippet) = extract_window("content", &text, &terms) {
+            snippets.push(snippet);
+        }
+    }
+
+    for tag in &note.tags {
+        let name: Vec<char> = tag.name.chars().collect();
+        let ranges = find_ranges(&name, &terms);
+        if !ranges.is_empty() {
+            snippets.push(SearchSnippet {
+                field: "tags".to_string(),
+                text: tag.name.clone(),
+                ranges,
+            });
+        }
+    }
+
+    snippets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Tag;
+
+    #[test]
+    fn html_is_stripped() {
+        let text = content_to_text("<p>Hello&nbsp;<b>world</b></p><p>a &amp; b</p>", 0);
+        assert_eq!(text, "Hello world a & b");
+    }
+
+    #[test]
+    fn title_and_tag_hits() {
+        let note = Note {
+            title: "Rust async guide".to_string(),
+            content: "<p>nothing here</p>".to_string(),
+            tags: vec![Tag {
+                name: "rust-lang".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let snippets = build_snippets(&note, ["RUST"]);
+        assert_eq!(snippets.len(), 2);
+        assert_eq!(snippets[0].field, "title");
+        assert_eq!(
+            snippets[0].ranges,
+            vec![HighlightRange { start: 0, end: 4 }]
+        );
+        assert_eq!(snippets[1].field, "tags");
+    }
+
+    #[test]
+    fn content_window_is_centered_on_first_hit() {
+        let content = format!("{} 会议纪要 {}", "前".repeat(100), "后".repeat(300));
+        let note = Note {
+            content,
+            content_type: 1,
+            ..Default::default()
+        };
+        let snippets = build_snippets(&note, ["会议"]);
+        assert_eq!(snippets.len(), 1);
+        let s = &snippets[0];
+        assert!(s.text.starts_with(ELLIPSIS) && s.text.ends_with(ELLIPSIS));
+        let chars: Vec<char> = s.text.chars().collect();
+        let r = &s.ranges[0];
+        assert_eq!(chars[r.start..r.end].iter().collect::<String>(), "会议");
+    }
+
+    #[test]
+    fn overlapping_terms_are_merged() {
+        let chars: Vec<char> = "database".chars().collect();
+        let terms = vec!["data".chars().collect(), "abase".chars().collect()];
+        assert_eq!(
+            find_ranges(&chars, &terms),
+            vec![HighlightRange { start: 0, end: 8 }]
+        );
+    }
+}
@@ -0,0 +1,196 @@
+//! 笔记内容中的 Wiki 链接解析
+//!
+//! 支持以下引用形式：
+//! - `[[笔记标题]]`：按标题引用（HTML 内容中的实体会先解码）
+//! - `[[id:123]]`：按笔记 ID 引用
+//! - `enote://note/123` 链接：HTML 中的 `<a href="enote://note/123">` 或 Markdown 中的
+//!   `[文字](enote://note/123)`
+//!
+//! 方括号内不允许换行、嵌套方括号或 HTML 标签，避免误识别普通文本。
+
+use super::snippet::decode_entities;
+
+/// 笔记 ID 引用前缀
+const ID_PREFIX: &str = "id:";
+/// 笔记链接 URL 前缀
+const NOTE_URL_PREFIX: &str = "enote://note/";
+
+/// 内容中的笔记引用
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum WikiRef {
+    /// 按 ID 引用
+    Id(i64),
+    /// 按标题引用
+    Title(String),
+}
+
+/// 内容中的一处 `[[...]]` 引用
+struct BracketRef {
+    /// 方括号内文本的字节区间（不含 `[[` 与 `]]`）
+    inner: std::ops::Range<usize>,
+    target: WikiRef,
+}
+
+/// 将标题转义为可写入 HTML 内容的文本
+fn encode_entities(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// 解析 `[[...]]` 内的文本
+fn parse_inner(inner: &str, html: bool) -> Option<WikiRef> {
+    if inner.contains(['\n', '[', ']', '<']) {
+        return None;
+    }
+    let text = if html {
+        decode_entities(inner)
+    } else {
+        inner.to_string()
+    };
+    let text = text.trim();
+    let id = text
+        .strip_prefix(ID_PREFIX)
+        .and_then(|id| id.trim().parse::<i64>().ok())
+        .filter(|id| *id > 0);
+    match id {
+        Some(id) => Some(WikiRef::Id(id)),
+        None => (!text.is_empty()).then(|| WikiRef::Title(text.to_string())),
+    }
+}
+
+/// 扫描所有 `[[...]]` 引用
+fn bracket_refs(content: &str, html: bool) -> Vec<BracketRef> {
+    let mut refs = Vec::new();
+    let mut pos = 0;
+    while let Some(offset) = content[pos..].find("[[") {
+        let start = pos + offset + 2;
+        let Some(len) = content[start..].find("]]") else {
+            break;
+        };
+        let inner = start..start + len;
+        match parse_inner(&content[inner.clone()], html) {
+            Some(target) => {
+                pos = inner.end + 2;
+                refs.push(BracketRef { inner, target });
+            }
+            // 形如 `[[[x]]` 时从下一个字符重新查找
+            None => pos = start - 1,
+        }
+    }
+    refs
+}
+
+/// 扫描所有 `enote://note/<id>` 链接
+fn url_refs(content: &str) -> Vec<i64> {
+    content
+        .match_indices(NOTE_URL_PREFIX)
+        .filter_map(|(idx, _)| {
+            let digits: String = content[idx + NOTE_URL_PREFIX.len()..]
+                .chars()
+                .take_while(char::is_ascii_digit)
+                .collect();
+            digits.parse().ok().filter(|id| *id > 0)
+        })
+        .collect()
+}
+
+/// 解析内容中的全部笔记引用（去重）
+///
+/// `content_type` 为 0（HTML）时方括号内的实体会先解码
+pub fn parse_wiki_refs(content: &str, content_type: i32) -> Vec<WikiRef> {
+    let html = content_type == 0;
+    let mut refs: Vec<WikiRef> = Vec::new();
+    let found = bracket_refs(content, html)
+        .into_iter()
+        .map(|r| r.target)
+        .chain(url_refs(content).into_iter().map(WikiRef::Id));
+    for target in found {
+        if !refs.contains(&target) {
+            refs.push(target);
+        }
+    }
+    refs
+}
+
+/// 将内容中按旧标题引用的 `[[旧标题]]` 替换为新标题
+///
+/// 新标题包含方括号或换行、无法写成 `[[...]]` 时改写为 `[[id:<note_id>]]`。
+/// 内容未发生变化时返回 None
+pub fn rename_wiki_refs(
+    content: &str,
+    content_type: i32,
+    note_id: i64,
+    old_title: &str,
+    new_title: &str,
+) -> Option<String> {
+    let html = content_type == 0;
+    let old_title = old_title.trim();
+    let new_title = new_title.trim();
+    let replacement = if new_title.is_empty() || new_title.contains(['\n', '[', ']']) {
+        format!("{}{}", ID_PREFIX, note_id)
+    } else if html {
+        encode_entities(new_title)
+    } else {
+        new_title.to_string()
+    };
+
+    let mut result = String::with_capacity(content.len());
+    let mut last = 0;
+    for r in bracket_refs(content, html) {
+        if r.target == WikiRef::Title(old_title.to_string()) {
+            result.push_str(&content[last..r.inner.start]);
+            result.push_str(&replacement);
+            last = r.inner.end;
+        }
+    }
+    if last == 0 {
+        return None;
+    }
+    result.push_str(&content[last..]);
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_titles_ids_and_anchors() {
+        let content = r#"<p>见 [[会议 &amp; 纪要]] 与 [[ id:12 ]]，
+            <a href="enote://note/34">周报</a>，[[会议 & 纪要]]，[[]]、[[a<b>c</b>]]</p>"#;
+        assert_eq!(
+            parse_wiki_refs(content, 0),
+            vec![
+                WikiRef::Title("会议 & 纪要".to_string()),
+                WikiRef::Id(12),
+                WikiRef::Id(34),
+            ]
+        );
+        assert_eq!(
+            parse_wiki_refs("[[[Todo]]] [x](enote://note/7) [[id:abc]]", 1),
+            vec![
+                WikiRef::Title("Todo".to_string()),
+                WikiRef::Title("id:abc".to_string()),
+                WikiRef::Id(7),
+            ]
+        );
+    }
+
+    #[test]
+    fn renames_title_refs() {
+        assert_eq!(
+            rename_wiki_refs("[[Old]] 与 [[ Old ]]，[[Other]]", 1, 5, "Old", "New").as_deref(),
+            Some("[[New]] 与 [[New]]，[[Other]]")
+        );
+        assert_eq!(
+            rename_wiki_refs("<p>[[Old]]</p>", 0, 5, "Old", "A & B").as_deref(),
+            Some("<p>[[A &amp; B]]</p>")
+        );
+        assert_eq!(
+            rename_wiki_refs("[[Old]]", 1, 5, "Old", "[draft]").as_deref(),
+            Some("[[id:5]]")
+        );
+        assert_eq!(rename_wiki_refs("[[id:5]]", 1, 5, "Old", "New"), None);
+    }
+}
@@ -0,0 +1,437 @@
+//! 笔记关系图服务
+//!
+//! 基于 note_link 构建笔记关系图：节点为未删除的笔记（附笔记本与标签），
+//! 边为笔记间的有向链接。支持：
+//! - 全部笔记或按笔记本、标签限定的子图
+//! - 以某篇笔记为中心的 N 跳邻域
+//! - 孤立笔记检测（没有任何链接的笔记）
+//! - 导出为 GraphML、DOT 与 JSON，供外部工具加载
+//!
+//! 链接的任一端笔记已删除时不计入关系图。
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Result;
+use sea_orm::sea_query::{Query, SelectStatement};
+use sea_orm::*;
+
+use crate::entity::{note, note_link, note_tags, notebook, tag};
+use crate::error::AppError;
+use crate::model::{GraphEdge, GraphNode, LinkOrigin, NoteGraph, NoteGraphQuery};
+use crate::service::note::notebook_tree_condition;
+
+/// 邻域查询的最大跳数
+pub const MAX_HOPS: u32 = 5;
+
+/// 节点条件：未删除的笔记，按笔记本（含子孙笔记本）与标签限定
+fn node_condition(query: &NoteGraphQuery) -> Condition {
+    let mut condition = Condition::all().add(note::Column::DeletedAt.is_null());
+    if query.notebook_id > 0 {
+        condition = condition.add(notebook_tree_condition(&[query.notebook_id]));
+    }
+    if query.tag_id > 0 {
+        condition = condition.add(
+            note::Column::Id.in_subquery(
+                Query::select()
+                    .column(note_tags::Column::NoteId)
+                    .from(note_tags::Entity)
+                    .and_where(note_tags::Column::TagId.eq(query.tag_id))
+                    .to_owned(),
+            ),
+        );
+    }
+    condition
+}
+
+/// 满足条件的笔记 ID 子查询
+fn note_ids_subquery(condition: Condition) -> SelectStatement {
+    Query::select()
+        .column(note::Column::Id)
+        .from(note::Entity)
+        .cond_where(condition)
+        .to_owned()
+}
+
+/// 两端都满足条件的链接
+fn both_ends_in(condition: Condition) -> Condition {
+    let note_ids = note_ids_subquery(condition);
+    Condition::all()
+        .add(note_link::Column::SourceNoteId.in_subquery(note_ids.clone()))
+        .add(note_link::Column::TargetNoteId.in_subquery(note_ids))
+}
+
+/// 加载满足条件的节点（不加载笔记内容）
+async fn load_nodes(db: &DatabaseConnection, condition: Condition) -> Result<Vec<GraphNode>> {
+    let notes = note::Entity::find()
+        .select_only()
+        .column(note::Column::Id)
+        .column(note::Column::Title)
+        .column(note::Column::NotebookId)
+        .filter(condition.clone())
+        .order_by_asc(note::Column::Id)
+        .into_tuple::<(i64, String, i64)>()
+        .all(db)
+        .await?;
+    if notes.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let notebook_ids: HashSet<i64> = notes.iter().map(|(_, _, id)| *id).collect();
+    let notebook_names: HashMap<i64, String> = notebook::Entity::find()
+        .select_only()
+        .column(notebook::Column::Id)
+        .column(notebook::Column::Name)
+        .filter(notebook::Column::Id.is_in(notebook_ids))
+        .into_tuple::<(i64, String)>()
+        .all(db)
+        .await?
+        .into_iter()
+        .collect();
+
+    // 标签顺序与笔记详情一致（按 sort_order 倒序）
+    let note_tag_ids = note_tags::Entity::find()
+        .select_only()
+        .column(note_tags::Column::NoteId)
+        .column(note_tags::Column::TagId)
+        .filter(note_tags::Column::NoteId.in_subquery(note_ids_subquery(condition)))
+        .order_by_desc(note_tags::Column::SortOrder)
+        .into_tuple::<(i64, i64)>()
+        .all(db)
+        .await?;
+    let tag_ids: HashSet<i64> = note_tag_ids.iter().map(|(_, id)| *id).collect();
+    let tag_names: HashMap<i64, String> = if tag_ids.is_empty() {
+        HashMap::new()
+    } else {
+        tag::Entity::find()
+            .select_only()
+            .column(tag::Column::Id)
+            .column(tag::Column::Name)
+            .filter(tag::Column::Id.is_in(tag_ids))
+            .into_tuple::<(i64, String)>()
+            .all(db)
+            .await?
+            .into_iter()
+            .collect()
+    };
+    let mut note_tag_names: HashMap<i64, Vec<String>> = HashMap::new();
+    for (note_id, tag_id) in note_tag_ids {
+        if let Some(name) = tag_names.get(&tag_id) {
+            note_tag_names
+                .entry(note_id)
+                .or_default()
+                .push(name.clone());
+        }
+    }
+
+    Ok(notes
+        .into_iter()
+        .map(|(id, title, notebook_id)| GraphNode {
+            id,
+            title,
+            notebook_id,
+            notebook_name: notebook_names
+                .get(&notebook_id)
+                .cloned()
+                .unwrap_or_default(),
+            tags: note_tag_names.remove(&id).unwrap_or_default(),
+        })
+        .collect())
+}
+
+/// 加载满足条件的边
+///
+/// 同一方向、同一关系同时存在手动与内容链接时只保留手动链接
+async fn load_edges(db: &DatabaseConnection, condition: Condition) -> Result<Vec<GraphEdge>> {
+    let links = note_link::Entity::find()
+        .filter(condition)
+        .order_by_asc(note_link::Column::Origin)
+        .order_by_asc(note_link::Column::Id)
+        .all(db)
+        .await?;
+
+    let mut seen = HashSet::new();
+    let mut edges: Vec<GraphEdge> = links
+        .into_iter()
+        .filter(|link| {
+            seen.insert((
+                link.source_note_id,
+                link.target_note_id,
+                link.relation.clone(),
+            ))
+        })
+        .map(|link| GraphEdge {
+            id: link.id,
+            source: link.source_note_id,
+            target: link.target_note_id,
+            relation: link.relation,
+            origin: link.origin,
+        })
+        .collect();
+    edges.sort_by_key(|edge| edge.id);
+    Ok(edges)
+}
+
+/// 构建关系图（全部笔记，或按笔记本、标签限定的子图）
+///
+/// 按笔记本限定时包含子孙笔记本中的笔记；子图只包含两端都在子集中的边
+pub async fn build_graph(db: &DatabaseConnection, query: &NoteGraphQuery) -> Result<NoteGraph> {
+    let condition = node_condition(query);
+    let nodes = load_nodes(db, condition.clone()).await?;
+    if nodes.is_empty() {
+        return Ok(NoteGraph::default());
+    }
+    let edges = load_edges(db, both_ends_in(condition)).await?;
+    Ok(NoteGraph { nodes, edges })
+}
+
+/// 将链接两端中尚未到达的笔记加入 `reached`，返回新到达的笔记（忽略方向）
+fn next_frontier(pairs: &[(i64, i64)], reached: &mut HashSet<i64>) -> Vec<i64> {
+    pairs
+        .iter()
+        .flat_map(|(source, target)| [*source, *target])
+        .filter(|id| reached.insert(*id))
+        .collect()
+}
+
+/// 以指定笔记为中心的 N 跳邻域（出链与反向链接都计入）
+///
+/// 每跳只查询与上一跳笔记相连的链接；`hops` 限制在 1 到 [`MAX_HOPS`] 之间；
+/// 笔记不存在或已删除时返回空图
+pub async fn neighbourhood(db: &DatabaseConnection, note_id: i64, hops: u32) -> Result<NoteGraph> {
+    let live = node_condition(&NoteGraphQuery::default());
+    let exists = note::Entity::find()
+        .filter(live.clone())
+        .filter(note::Column::Id.eq(note_id))
+        .count(db)
+        .await?
+        > 0;
+    if !exists {
+        return Ok(NoteGraph::default());
+    }
+
+    let mut reached = HashSet::from([note_id]);
+    let mut frontier = vec![note_id];
+    for _ in 0..hops.clamp(1, MAX_HOPS) {
+        if frontier.is_empty() {
+            break;
+        }
+        let pairs = note_link::Entity::find()
+            .select_only()
+            .column(note_link::Column::SourceNoteId)
+            .column(note_link::Column::TargetNoteId)
+            .filter(both_ends_in(live.clone()))
+            .filter(
+                Condition::any()
+                    .add(note_link::Column::SourceNoteId.is_in(frontier.clone()))
+                    .add(note_link::Column::TargetNoteId.is_in(frontier)),
+            )
+            .into_tuple::<(i64, i64)>()
+            .all(db)
+            .await?;
+        frontier = next_frontier(&pairs, &mut reached);
+    }
+
+    let reached: Vec<i64> = reached.into_iter().collect();
+    let condition = live.add(note::Column::Id.is_in(reached));
+    let nodes = load_nodes(db, condition.clone()).await?;
+    let edges = load_edges(db, both_ends_in(condition)).await?;
+    Ok(NoteGraph { nodes, edges })
+}
+
+/// 查找孤立笔记：与任何未删除笔记之间都没有链接（出链或反向链接）
+///
+/// `query` 限定检测范围；范围外的链接同样计入
+pub async fn find_orphans(
+    db: &DatabaseConnection,
+    query: &NoteGraphQuery,
+) -> Result<Vec<GraphNode>> {
+    let condition = node_condition(query);
+    let mut nodes = load_nodes(db, condition.clone()).await?;
+    if nodes.is_empty() {
+        return Ok(nodes);
+    }
+
+    let scope = note_ids_subquery(condition);
+    let live = note_ids_subquery(node_condition(&NoteGraphQuery::default()));
+    let linked: HashSet<i64> = note_link::Entity::find()
+        .select_only()
+        .column(note_link::Column::SourceNoteId)
+        .column(note_link::Column::TargetNoteId)
+        .filter(
+            Condition::any()
+                .add(
+                    Condition::all()
+                        .add(note_link::Column::SourceNoteId.in_subquery(scope.clone()))
+                        .add(note_link::Column::TargetNoteId.in_subquery(live.clone())),
+                )
+                .add(
+                    Condition::all()
+                        .add(note_link::Column::TargetNoteId.in_subquery(scope))
+                        .add(note_link::Column::SourceNoteId.in_subquery(live)),
+                ),
+        )
+        .into_tuple::<(i64, i64)>()
+        .all(db)
+        .await?
+        .into_iter()
+        .flat_map(|(source, target)| [source, target])
+        .collect();
+
+    nodes.retain(|node| !linked.contains(&node.id));
+    Ok(nodes)
+}
+
+/// 链接来源的导出名称
+fn origin_name(origin: i32) -> &'static str {
+    if origin == LinkOrigin::Content.as_i32() {
+        "content"
+    } else {
+        "manual"
+    }
+}
+
+/// 转义 XML 文本与属性值
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// 转义 DOT 双引号字符串
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// 渲染为 GraphML
+fn render_graphml(graph: &NoteGraph) -> String {
+    let mut out = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+  <key id="title" for="node" attr.name="title" attr.type="string"/>
+  <key id="notebook" for="node" attr.name="notebook" attr.type="string"/>
+  <key id="tags" for="node" attr.name="tags" attr.type="string"/>
+  <key id="relation" for="edge" attr.name="relation" attr.type="string"/>
+  <key id="origin" for="edge" attr.name="origin" attr.type="string"/>
+  <graph id="enote" edgedefault="directed">
+"#,
+    );
+    for node in &graph.nodes {
+        out.push_str(&format!(
+            "    <node id=\"n{}\">\n      <data key=\"title\">{}</data>\n      <data key=\"notebook\">{}</data>\n      <data key=\"tags\">{}</data>\n    </node>\n",
+            node.id,
+            xml_escape(&node.title),
+            xml_escape(&node.notebook_name),
+            xml_escape(&node.tags.join(", ")),
+        ));
+    }
+    for edge in &graph.edges {
+        out.push_str(&format!(
+            "    <edge id=\"e{}\" source=\"n{}\" target=\"n{}\">\n      <data key=\"relation\">{}</data>\n      <data key=\"origin\">{}</data>\n    </edge>\n",
+            edge.id,
+            edge.source,
+            edge.target,
+            xml_escape(&edge.relation),
+            origin_name(edge.origin),
+        ));
+    }
+    out.push_str("  </graph>\n</graphml>\n");
+    out
+}
+
+/// 渲染为 Graphviz DOT
+fn render_dot(graph: &NoteGraph) -> String {
+    let mut out = String::from("digraph enote {\n");
+    for node in &graph.nodes {
+        out.push_str(&format!(
+            "  n{} [label=\"{}\", notebook=\"{}\", tags=\"{}\"];\n",
+            node.id,
+            dot_escape(&node.title),
+            dot_escape(&node.notebook_name),
+            dot_escape(&node.tags.join(", ")),
+        ));
+    }
+    for edge in &graph.edges {
+        out.push_str(&format!(
+            "  n{} -> n{} [label=\"{}\", origin=\"{}\"];\n",
+            edge.source,
+            edge.target,
+            dot_escape(&edge.relation),
+            origin_name(edge.origin),
+        ));
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// 将关系图渲染为指定格式的文本
+///
+/// `format` 支持 graphml、dot 与 json
+pub fn render(graph: &NoteGraph, format: &str) -> Result<String> {
+    match format {
+        "graphml" => Ok(render_graphml(graph)),
+        "dot" => Ok(render_dot(graph)),
+        "json" => Ok(serde_json::to_string_pretty(graph)?),
+        _ => Err(AppError::code("UNSUPPORTED_EXPORT_FORMAT").into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn next_frontier_ignores_direction_and_skips_reached() {
+        let mut reached = HashSet::from([1]);
+        let frontier = next_frontier(&[(1, 2), (3, 1)], &mut reached);
+        assert_eq!(frontier, vec![2, 3]);
+
+        let frontier = next_frontier(&[(1, 2), (2, 3), (4, 2)], &mut reached);
+        assert_eq!(frontier, vec![4]);
+        assert_eq!(reached, HashSet::from([1, 2, 3, 4]));
+
+        assert!(next_frontier(&[(2, 4)], &mut reached).is_empty());
+    }
+
+    #[test]
+    fn renders_escaped_graphml_and_dot() {
+        let graph = NoteGraph {
+            nodes: vec![
+                GraphNode {
+                    id: 1,
+                    title: "A & \"B\"".to_string(),
+                    notebook_name: "Work".to_string(),
+                    tags: vec!["x".to_string(), "y".to_string()],
+                    ..Default::default()
+                },
+                GraphNode {
+                    id: 2,
+                    title: "C".to_string(),
+                    ..Default::default()
+                },
+            ],
+            edges: vec![GraphEdge {
+                id: 9,
+                source: 1,
+                target: 2,
+                relation: "depends-on".to_string(),
+                origin: LinkOrigin::Content.as_i32(),
+            }],
+        };
+
+        let graphml = render(&graph, "graphml").unwrap();
+        assert!(graphml.contains("<data key=\"title\">A &amp; &quot;B&quot;</data>"));
+        assert!(graphml.contains("<data key=\"tags\">x, y</data>"));
+        assert!(graphml.contains("<edge id=\"e9\" source=\"n1\" target=\"n2\">"));
+        assert!(graphml.contains("<data key=\"origin\">content</data>"));
+
+        let dot = render(&graph, "dot").unwrap();
+        assert!(dot.contains("n1 [label=\"A & \\\"B\\\"\", notebook=\"Work\", tags=\"x, y\"];"));
+        assert!(dot.contains("n1 -> n2 [label=\"depends-on\", origin=\"content\"];"));
+
+        assert!(render(&graph, "svg").is_err());
+    }
+}
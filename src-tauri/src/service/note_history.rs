@@ -4,28 +4,46 @@
 //!
 //! # 功能概述
 //! - 分页查询指定笔记的历史记录
+//! - 跨笔记搜索历史版本内容（可按日期、操作来源过滤）
 //!
 //! # 历史记录说明
 //! 历史记录由笔记服务在以下操作时自动创建：
 //! - 创建笔记（操作类型 1）
 //! - 更新笔记（操作类型 2）
 //! - 删除笔记（操作类型 3）
+//! - 恢复历史版本（操作类型 4）
 //!
 //! 每条历史记录包含：
 //! - 操作前后的内容差异
 //! - 操作时的笔记元信息（标题、笔记本、标签）
+//!
+//! 内容按快照加增量存储（见 `history_storage`），查询结果均已还原为完整文本。
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
 
+use chrono::{Duration, NaiveDateTime};
 use sea_orm::{
-    ColumnTrait, DatabaseConnection, EntityTrait, ExprTrait, QueryFilter, QueryOrder, QuerySelect,
-    prelude::Expr, sea_query::Asterisk,
+    ColumnTrait, Condition, DatabaseConnection, EntityTrait, ExprTrait, PaginatorTrait,
+    QueryFilter, QueryOrder, QuerySelect,
+    prelude::Expr,
+    sea_query::{Asterisk, BinOper, Func, Query},
 };
 
 use crate::{
     entity::{self},
     i18n::t_simple,
-    model::{NoteHistory, NoteHistorySearchPageParam, PageResult},
+    model::{
+        NoteHistory, NoteHistoryContentSearchParam, NoteHistoryHit, NoteHistorySearchPageParam,
+        PageResult,
+    },
+    service::history_storage::{self, HistoryDecoder},
+    service::note::{content_snippet, content_to_text, start_of_day},
 };
 
+/// 内容搜索时每批扫描的记录数
+const SCAN_BATCH_SIZE: u64 = 200;
+
 /// 分页搜索笔记历史记录
 ///
 /// # 参数
@@ -66,12 +84,14 @@ pub async fn search_page(
     if total > 0 {
         let start = search_param.page_param.start() as u64;
         let page_size = search_param.page_param.page_size as u64;
-        let histories: Vec<NoteHistory> = entity::note_history::Entity::find()
+        let models = entity::note_history::Entity::find()
             .filter(note_filter)
             .offset(start)
             .limit(page_size)
             .order_by_desc(entity::note_history::Column::Id)
             .all(db)
+            .await?;
+        let histories: Vec<NoteHistory> = history_storage::decode_all(db, models)
             .await?
             .into_iter()
             .map(NoteHistory::from)
@@ -87,3 +107,315 @@ pub async fn search_page(
     // 无数据时返回空结果
     Ok(PageResult::<NoteHistory>::default())
 }
+
+/// 原始内容是否包含关键词（忽略 ASCII 大小写）
+fn raw_contains(content: &str, keyword: &str) -> bool {
+    content
+        .to_ascii_lowercase()
+        .contains(&keyword.to_ascii_lowercase())
+}
+
+/// 纯文本是否包含关键词（忽略 ASCII 大小写）
+fn text_contains(content: &str, content_type: i32, keyword: &str) -> bool {
+    raw_contains(&content_to_text(content, content_type), keyword)
+}
+
+/// 存储内容（新旧内容任一）包含文本，两侧均转为小写后比较
+///
+/// 各后端 `LIKE` 的大小写规则不同，统一比较 `LOWER()` 后的结果；
+/// SQLite 的 `LOWER()` 只转换 ASCII 字母，其余后端转换范围更大，都不会漏掉 `raw_contains` 命中的记录
+fn stored_contains(text: &str) -> Condition {
+    let pattern = format!("%{}%", text);
+    let lower_like = |column: entity::note_history::Column| {
+        Expr::expr(Func::lower(Expr::col((
+            entity::note_history::Entity,
+            column,
+        ))))
+        .binary(BinOper::Like, Func::lower(Expr::val(pattern.clone())))
+    };
+    Condition::any()
+        .add(lower_like(entity::note_history::Column::OldContent))
+        .add(lower_like(entity::note_history::Column::NewContent))
+}
+
+/// 内容搜索的候选记录条件
+///
+/// 完整文本存储的记录直接按关键词匹配；增量存储的记录需解码后才能匹配，
+/// 只解码存储内容中出现过关键词片段（见 `history_storage::keyword_stored_fragments`）的笔记的记录。
+/// 候选记录解码后统一按 `raw_contains` 判断是否命中
+fn content_search_condition(search_param: &NoteHistoryContentSearchParam) -> Condition {
+    let keyword = search_param.keyword.as_str();
+    let full_rows = Condition::all()
+        .add(entity::note_history::Column::ContentFormat.eq(0))
+        .add(stored_contains(keyword));
+    let mut fragment_match = Condition::any();
+    for fragment in history_storage::keyword_stored_fragments(keyword) {
+        fragment_match = fragment_match.add(stored_contains(&fragment));
+    }
+    let delta_rows = Condition::all()
+        .add(entity::note_history::Column::ContentFormat.ne(0))
+        .add(
+            entity::note_history::Column::NoteId.in_subquery(
+                Query::select()
+                    .column(entity::note_history::Column::NoteId)
+                    .from(entity::note_history::Entity)
+                    .cond_where(fragment_match)
+                    .to_owned(),
+            ),
+        );
+    let mut condition = Condition::all().add(Condition::any().add(full_rows).add(delta_rows));
+    if search_param.note_id > 0 {
+        condition = condition.add(entity::note_history::Column::NoteId.eq(search_param.note_id));
+    }
+    if let Some(source) = search_param.operate_source {
+        condition = condition.add(entity::note_history::Column::OperateSource.eq(source));
+    }
+    if let Some(date) = search_param.start_date {
+        condition =
+            condition.add(entity::note_history::Column::OperateTime.gte(start_of_day(date)));
+    }
+    if let Some(date) = search_param.end_date {
+        condition = condition.add(
+            entity::note_history::Column::OperateTime.lt(start_of_day(date + Duration::days(1))),
+        );
+    }
+
+    condition
+}
+
+/// 跨笔记搜索历史版本内容
+///
+/// # 参数
+/// - `db`: 数据库连接
+/// - `search_param`: 搜索参数，包含：
+///   - `keyword`: 关键词（在 `old_content` / `new_content` 中按子串匹配）
+///   - `note_id`: 限定笔记（0 表示所有笔记）
+///   - `operate_source`: 操作来源过滤
+///   - `start_date` / `end_date`: 操作日期范围（均含当天）
+///
+/// # 返回
+/// - `Ok(PageResult<NoteHistoryHit>)`: 命中的历史版本，附带笔记标题与命中片段
+/// - `Err`: 查询失败
+///
+/// # 说明
+/// 加密存储的历史内容无法在数据库中匹配，不会出现在结果中。
+/// 完整文本存储的记录在数据库中筛选，增量存储的记录只解码可能命中的笔记，
+/// 再统一按忽略 ASCII 大小写的子串匹配判断是否命中。
+/// 按操作时间降序排列（最新的版本在前）
+pub async fn search_content(
+    db: &DatabaseConnection,
+    search_param: &NoteHistoryContentSearchParam,
+) -> anyhow::Result<PageResult<NoteHistoryHit>> {
+    search_param.validate()?;
+
+    let keyword = search_param.keyword.as_str();
+    let condition = content_search_condition(search_param);
+
+    // 按笔记与 ID 顺序扫描候选记录，解码时可复用上一条记录；
+    // 只统计命中数量，并保留排序最靠前的 `page_end` 条命中记录的排序键（小顶堆）
+    let page_end =
+        search_param.page_param.start() as usize + search_param.page_param.page_size as usize;
+    let mut total: i64 = 0;
+    let mut keys: BinaryHeap<Reverse<(NaiveDateTime, i64)>> = BinaryHeap::new();
+    let mut decoder = HistoryDecoder::default();
+    let mut paginator = entity::note_history::Entity::find()
+        .filter(condition)
+        .order_by_asc(entity::note_history::Column::NoteId)
+        .order_by_asc(entity::note_history::Column::Id)
+        .paginate(db, SCAN_BATCH_SIZE);
+    while let Some(batch) = paginator.fetch_and_next().await? {
+        for model in batch {
+            let decoded = decoder.decode(db, model).await?;
+            if raw_contains(&decoded.old_content, keyword)
+                || raw_contains(&decoded.new_content, keyword)
+            {
+                total += 1;
+                keys.push(Reverse((decoded.operate_time, decoded.id)));
+                if keys.len() > page_end {
+                    keys.pop();
+                }
+            }
+        }
+    }
+
+    if total == 0 {
+        return Ok(PageResult::<NoteHistoryHit>::default());
+    }
+
+    // into_sorted_vec 按 Reverse 升序，即排序键降序
+    let page_ids = keys
+        .into_sorted_vec()
+        .into_iter()
+        .skip(search_param.page_param.start() as usize)
+        .map(|Reverse((_, id))| id)
+        .collect::<Vec<i64>>();
+
+    let models = entity::note_history::Entity::find()
+        .filter(entity::note_history::Column::Id.is_in(page_ids))
+        .order_by_desc(entity::note_history::Column::OperateTime)
+        .order_by_desc(entity::note_history::Column::Id)
+        .all(db)
+        .await?;
+    let histories: Vec<NoteHistory> = history_storage::decode_all(db, models)
+        .await?
+        .into_iter()
+        .map(NoteHistory::from)
+        .collect();
+
+    // 批量获取笔记当前标题与删除状态
+    let note_ids = histories.iter().map(|h| h.note_id).collect::<Vec<i64>>();
+    let note_map = entity::note::Entity::find()
+        .filter(entity::note::Column::Id.is_in(note_ids))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|n| (n.id, (n.title, n.deleted_at.is_some())))
+        .collect::<HashMap<i64, (String, bool)>>();
+
+    let hits = histories
+        .into_iter()
+        .map(|history| {
+            let (note_title, note_deleted) = note_map
+                .get(&history.note_id)
+                .cloned()
+                .unwrap_or_else(|| (history.extra.title.clone(), true));
+            // 历史记录的 content_type 为修改前的类型，新旧内容共用
+            let content_type = history.extra.content_type;
+
+            let snippets = [
+                ("oldContent", history.old_content.as_str()),
+                ("newContent", history.new_content.as_str()),
+            ]
+            .into_iter()
+            .filter_map(|(field, content)| content_snippet(field, content, content_type, [keyword]))
+            .collect::<Vec<_>>();
+
+            let removed = text_contains(&history.old_content, content_type, keyword)
+                && !text_contains(&history.new_content, content_type, keyword);
+
+            NoteHistoryHit {
+                history_id: history.id,
+                note_id: history.note_id,
+                note_title,
+                note_deleted,
+                removed,
+                snippets,
+                operate_type: history.operate_type,
+                operate_source: history.operate_source,
+                operate_time: history.operate_time,
+            }
+        })
+        .collect::<Vec<NoteHistoryHit>>();
+
+    let mut page_result = PageResult::<NoteHistoryHit>::with_data(total, hits);
+
+    page_result.total_pages(search_param.page_param.page_size);
+
+    Ok(page_result)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use sea_orm::{DatabaseBackend, QueryTrait};
+
+    use super::*;
+
+    /// 按搜索参数生成的候选记录 SQLite WHERE 子句
+    fn condition_sql(search_param: &NoteHistoryContentSearchParam) -> String {
+        let sql = entity::note_history::Entity::find()
+            .filter(content_search_condition(search_param))
+            .build(DatabaseBackend::Sqlite)
+            .to_string();
+        sql.split_once(" WHERE ")
+            .map(|(_, condition)| condition.to_string())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn condition_matches_keyword_or_delta_rows() {
+        let sql = condition_sql(&NoteHistoryContentSearchParam {
+            keyword: "rust".to_string(),
+            ..Default::default()
+        });
+        assert!(
+            sql.contains(r#"LOWER("note_history"."old_content") LIKE LOWER('%rust%')"#),
+            "{}",
+            sql
+        );
+        assert!(
+            sql.contains(r#"LOWER("note_history"."new_content") LIKE LOWER('%rust%')"#),
+            "{}",
+            sql
+        );
+        assert!(
+            sql.contains(r#""note_history"."content_format" <> 0"#),
+            "{}",
+            sql
+        );
+        // 增量记录只取存储内容中出现过关键词的笔记
+        assert!(
+            sql.contains(
+                r#""note_history"."note_id" IN (SELECT "note_id" FROM "note_history" WHERE"#
+            ),
+            "{}",
+            sql
+        );
+        assert!(!sql.contains(r#""note_history"."note_id" ="#), "{}", sql);
+        assert!(!sql.contains(r#""note_history"."operate_time""#), "{}", sql);
+    }
+
+    #[test]
+    fn condition_prunes_delta_rows_by_keyword_fragment() {
+        let sql = condition_sql(&NoteHistoryContentSearchParam {
+            keyword: "</p>\n<p>Rust".to_string(),
+            ..Default::default()
+        });
+        // 完整文本记录按整个关键词匹配，增量记录按不含切分符的最长片段筛选笔记
+        assert!(sql.contains("LIKE LOWER('%</p>\n<p>Rust%')"), "{}", sql);
+        assert!(
+            sql.contains(r#"IN (SELECT "note_id" FROM "note_history" WHERE LOWER("note_history"."old_content") LIKE LOWER('%Rust%')"#),
+            "{}",
+            sql
+        );
+    }
+
+    #[test]
+    fn condition_filters_note_source_and_inclusive_dates() {
+        let sql = condition_sql(&NoteHistoryContentSearchParam {
+            keyword: "rust".to_string(),
+            note_id: 7,
+            operate_source: Some(1),
+            start_date: NaiveDate::from_ymd_opt(2026, 3, 1),
+            end_date: NaiveDate::from_ymd_opt(2026, 3, 31),
+            ..Default::default()
+        });
+        assert!(sql.contains(r#""note_history"."note_id" = 7"#), "{}", sql);
+        assert!(
+            sql.contains(r#""note_history"."operate_source" = 1"#),
+            "{}",
+            sql
+        );
+        // 截止日期含当天：小于次日零点
+        assert!(
+            sql.contains(r#""note_history"."operate_time" >= '2026-03-01 00:00:00"#),
+            "{}",
+            sql
+        );
+        assert!(
+            sql.contains(r#""note_history"."operate_time" < '2026-04-01 00:00:00"#),
+            "{}",
+            sql
+        );
+    }
+
+    #[test]
+    fn keyword_matching_ignores_case() {
+        assert!(raw_contains("<p>Hello Rust</p>", "rust"));
+        assert!(raw_contains("<p>HELLO</p>", "Hello"));
+        assert!(!raw_contains("<p>Hello</p>", "rust"));
+        // 纯文本匹配不命中 HTML 标签
+        assert!(text_contains("<p>Hello Rust</p>", 0, "RUST"));
+        assert!(!text_contains("<p>Hello</p>", 0, "p>"));
+    }
+}
@@ -1,65 +1,90 @@
 //! 笔记链接服务
 //!
-//! 实现笔记间的双向链接功能
+//! 链接是有方向的：source 笔记引用 target 笔记，可附带关系类型（如 references、
+//! depends-on、supersedes 或自定义标签）。可分别查询笔记的出链与反向链接。
+//!
+//! 链接有两种来源：
+//! - 手动创建：通过 `create_link` 建立
+//! - 内容解析：创建或更新笔记时从内容中的 `[[标题]]`、`[[id:123]]` 与
+//!   `enote://note/123` 链接解析，source 为包含引用的笔记，随内容变化自动增删；
+//!   已有笔记的内容链接由 `backfill_content_links` 在启动后回填一次
+//!
+//! 笔记移入回收站时其链接保留但不再返回，恢复后重新可见；笔记被彻底删除时
+//! 相关链接一并删除。`check_integrity` 用于检查和修复遗留的悬空链接。
+
+use std::collections::{HashMap, HashSet};
 
 use anyhow::Result;
 use chrono::Local;
+use sea_orm::prelude::Expr;
 use sea_orm::*;
+use tracing::{info, warn};
 
 use crate::entity::{note, note_link};
-use crate::model::NoteLink;
+use crate::i18n::t;
+use crate::model::{BrokenReference, DanglingLink, LinkIntegrityReport, LinkOrigin, NoteLink};
+use crate::service::note::{WikiRef, parse_wiki_refs};
+use crate::service::{crypto, settings};
 
-/// 获取笔记的所有链接（双向）
-///
-/// 返回与指定笔记关联的所有笔记（包括 source 和 target 方向）
-pub async fn find_links(db: &DatabaseConnection, note_id: i64) -> Result<Vec<NoteLink>> {
-    let links = note_link::Entity::find()
-        .filter(
-            Condition::any()
-                .add(note_link::Column::SourceNoteId.eq(note_id))
-                .add(note_link::Column::TargetNoteId.eq(note_id)),
-        )
-        .all(db)
-        .await?;
+/// 关系类型的最大字符数
+const MAX_RELATION_LENGTH: usize = 64;
+
+/// 重新同步内容链接时每批读取的笔记数
+const RESYNC_BATCH_SIZE: usize = 200;
 
+/// 内容链接回填完成标记的设置键
+pub const BACKFILL_DONE_KEY: &str = "noteLinkBackfilled";
+
+/// 将链接记录转换为 DTO，`note_id` 为当前笔记
+///
+/// 同一笔记、同一方向、同一关系同时存在手动与内容链接时只返回手动链接；
+/// 关联笔记在回收站中或已不存在的链接被忽略
+async fn to_note_links(
+    db: &DatabaseConnection,
+    note_id: i64,
+    mut links: Vec<note_link::Model>,
+) -> Result<Vec<NoteLink>> {
     if links.is_empty() {
         return Ok(Vec::new());
     }
+    links.sort_by_key(|link| (link.origin, link.id));
 
-    // 批量收集所有关联笔记 ID
-    let linked_note_ids: Vec<i64> = links
-        .iter()
-        .map(|link| {
-            if link.source_note_id == note_id {
-                link.target_note_id
-            } else {
-                link.source_note_id
-            }
-        })
-        .collect();
+    let linked_note_id = |link: &note_link::Model| {
+        if link.source_note_id == note_id {
+            link.target_note_id
+        } else {
+            link.source_note_id
+        }
+    };
 
     // 一次查询获取所有关联笔记标题
-    let notes_map: std::collections::HashMap<i64, String> = note::Entity::find()
-        .filter(note::Column::Id.is_in(linked_note_ids))
+    let notes_map: HashMap<i64, String> = note::Entity::find()
+        .select_only()
+        .column(note::Column::Id)
+        .column(note::Column::Title)
+        .filter(note::Column::Id.is_in(links.iter().map(linked_note_id).collect::<Vec<_>>()))
+        .filter(note::Column::DeletedAt.is_null())
+        .into_tuple::<(i64, String)>()
         .all(db)
         .await?
         .into_iter()
-        .map(|n| (n.id, n.title))
         .collect();
 
+    let mut seen = HashSet::new();
     let mut result = Vec::new();
     for link in &links {
-        let linked_note_id = if link.source_note_id == note_id {
-            link.target_note_id
-        } else {
-            link.source_note_id
-        };
-
-        if let Some(title) = notes_map.get(&linked_note_id) {
+        let linked = linked_note_id(link);
+        let outgoing = link.source_note_id == note_id;
+        if let Some(title) = notes_map.get(&linked)
+            && seen.insert((linked, outgoing, link.relation.as_str()))
+        {
             result.push(NoteLink {
                 id: link.id,
-                note_id: linked_note_id,
+                note_id: linked,
                 note_title: title.clone(),
+                origin: link.origin,
+                relation: link.relation.clone(),
+                outgoing,
                 create_time: Some(link.create_time),
             });
         }
@@ -68,29 +93,68 @@ pub async fn find_links(db: &DatabaseConnection, note_id: i64) -> Result<Vec<Not
     Ok(result)
 }
 
+/// 获取笔记的所有链接（出链与反向链接）
+pub async fn find_links(db: &DatabaseConnection, note_id: i64) -> Result<Vec<NoteLink>> {
+    let links = note_link::Entity::find()
+        .filter(
+            Condition::any()
+                .add(note_link::Column::SourceNoteId.eq(note_id))
+                .add(note_link::Column::TargetNoteId.eq(note_id)),
+        )
+        .all(db)
+        .await?;
+    to_note_links(db, note_id, links).await
+}
+
+/// 获取笔记的出链（该笔记引用的笔记）
+pub async fn find_outgoing(db: &DatabaseConnection, note_id: i64) -> Result<Vec<NoteLink>> {
+    let links = note_link::Entity::find()
+        .filter(note_link::Column::SourceNoteId.eq(note_id))
+        .all(db)
+        .await?;
+    to_note_links(db, note_id, links).await
+}
+
+/// 获取笔记的反向链接（引用该笔记的笔记）
+pub async fn find_backlinks(db: &DatabaseConnection, note_id: i64) -> Result<Vec<NoteLink>> {
+    let links = note_link::Entity::find()
+        .filter(note_link::Column::TargetNoteId.eq(note_id))
+        .all(db)
+        .await?;
+    to_note_links(db, note_id, links).await
+}
+
 /// 创建笔记链接
 ///
-/// 在两个笔记之间建立双向链接，使用较小 ID 作为 source 确保唯一性
+/// 建立 source → target 的有向链接，`relation` 为关系类型（去除首尾空白，可为空）。
+/// 相同方向、相同关系的链接已存在时不重复创建
 pub async fn create_link(
     db: &DatabaseConnection,
     source_note_id: i64,
     target_note_id: i64,
+    relation: &str,
 ) -> Result<()> {
     if source_note_id == target_note_id {
         return Err(crate::error::AppError::code("CANNOT_LINK_SELF").into());
     }
 
-    // 标准化方向：较小 ID 作为 source
-    let (src, tgt) = if source_note_id < target_note_id {
-        (source_note_id, target_note_id)
-    } else {
-        (target_note_id, source_note_id)
-    };
+    let relation = relation.trim();
+    if relation.chars().count() > MAX_RELATION_LENGTH {
+        anyhow::bail!(
+            "{}",
+            t(
+                "validation.relationTooLong",
+                &[&MAX_RELATION_LENGTH.to_string()]
+            )
+        );
+    }
 
     // 检查是否已存在
     let existing = note_link::Entity::find()
-        .filter(note_link::Column::SourceNoteId.eq(src))
-        .filter(note_link::Column::TargetNoteId.eq(tgt))
+        .filter(note_link::Column::SourceNoteId.eq(source_note_id))
+        .filter(note_link::Column::TargetNoteId.eq(target_note_id))
+        .filter(note_link::Column::Origin.eq(LinkOrigin::Manual.as_i32()))
+        .filter(note_link::Column::Relation.eq(relation))
         .one(db)
         .await?;
 
@@ -101,9 +165,11 @@ pub async fn create_link(
     let now = Local::now().naive_local();
     let model = note_link::ActiveModel {
         id: NotSet,
-        source_note_id: Set(src),
-        target_note_id: Set(tgt),
+        source_note_id: Set(source_note_id),
+        target_note_id: Set(target_note_id),
         create_time: Set(now),
+        origin: Set(LinkOrigin::Manual.as_i32()),
+        relation: Set(relation.to_string()),
     };
 
     note_link::Entity::insert(model).exec(db).await?;
@@ -111,8 +177,14 @@ pub async fn create_link(
 }
 
 /// 删除链接
+///
+/// 只删除手动链接；内容链接随笔记内容同步，需修改内容中的引用
 pub async fn delete_link(db: &DatabaseConnection, link_id: i64) -> Result<()> {
-    note_link::Entity::delete_by_id(link_id).exec(db).await?;
+    note_link::Entity::delete_many()
+        .filter(note_link::Column::Id.eq(link_id))
+        .filter(note_link::Column::Origin.eq(LinkOrigin::Manual.as_i32()))
+        .exec(db)
+        .await?;
     Ok(())
 }
 
@@ -165,7 +237,357 @@ pub async fn search_linkable_notes(
             id: 0,
             note_id: n.id,
             note_title: n.title,
+            origin: LinkOrigin::Manual.as_i32(),
+            outgoing: true,
             create_time: None,
+            ..Default::default()
         })
         .collect())
 }
+
+/// 将内容中的引用解析为笔记 ID（排除自身与不存在的笔记）
+///
+/// 按标题引用时匹配同名笔记，优先未删除的笔记，存在多篇时取最近更新的一篇；
+/// 引用回收站中的笔记时链接保留，恢复后重新可见
+async fn resolve_refs<C: ConnectionTrait>(
+    db: &C,
+    note_id: i64,
+    refs: Vec<WikiRef>,
+) -> Result<HashSet<i64>> {
+    let mut ids = Vec::new();
+    let mut titles = Vec::new();
+    for r in refs {
+        match r {
+            WikiRef::Id(id) => ids.push(id),
+            WikiRef::Title(title) => titles.push(title),
+        }
+    }
+
+    let mut targets = HashSet::new();
+    if !ids.is_empty() {
+        targets.extend(
+            note::Entity::find()
+                .select_only()
+                .column(note::Column::Id)
+                .filter(note::Column::Id.is_in(ids))
+                .into_tuple::<i64>()
+                .all(db)
+                .await?,
+        );
+    }
+    if !titles.is_empty() {
+        let mut resolved = HashSet::new();
+        for (id, title) in note::Entity::find()
+            .select_only()
+            .column(note::Column::Id)
+            .column(note::Column::Title)
+            .filter(note::Column::Title.is_in(titles))
+            .order_by(Expr::col(note::Column::DeletedAt).is_null(), Order::Desc)
+            .order_by_desc(note::Column::UpdateTime)
+            .order_by_desc(note::Column::Id)
+            .into_tuple::<(i64, String)>()
+            .all(db)
+            .await?
+        {
+            if resolved.insert(title) {
+                targets.insert(id);
+            }
+        }
+    }
+    targets.remove(&note_id);
+    Ok(targets)
+}
+
+/// 计算笔记内容链接的变化，返回（需删除的 target，需新增的 target）
+async fn content_link_changes<C: ConnectionTrait>(
+    db: &C,
+    note_id: i64,
+    content: &str,
+    content_type: i32,
+) -> Result<(Vec<i64>, Vec<i64>)> {
+    let targets = resolve_refs(db, note_id, parse_wiki_refs(content, content_type)).await?;
+
+    let existing: HashSet<i64> = note_link::Entity::find()
+        .select_only()
+        .column(note_link::Column::TargetNoteId)
+        .filter(note_link::Column::SourceNoteId.eq(note_id))
+        .filter(note_link::Column::Origin.eq(LinkOrigin::Content.as_i32()))
+        .into_tuple::<i64>()
+        .all(db)
+        .await?
+        .into_iter()
+        .collect();
+
+    let removed = existing.difference(&targets).copied().collect();
+    let added = targets.difference(&existing).copied().collect();
+    Ok((removed, added))
+}
+
+/// 按笔记内容同步该笔记的内容链接
+///
+/// `content` 为明文内容；新增内容中出现的引用，删除已不再引用的内容链接
+pub async fn sync_content_links<C: ConnectionTrait>(
+    db: &C,
+    note_id: i64,
+    content: &str,
+    content_type: i32,
+) -> Result<()> {
+    let (removed, added) = content_link_changes(db, note_id, content, content_type).await?;
+    apply_content_link_changes(db, note_id, removed, added).await
+}
+
+/// 笔记的明文内容，加密且无法解密时返回 None
+fn plain_content(n: &note::Model, encryption_key: Option<&str>) -> Option<String> {
+    if crypto::is_encrypted(&n.content) {
+        encryption_key.and_then(|key| crypto::decrypt(&n.content, key).ok())
+    } else {
+        Some(n.content.clone())
+    }
+}
+
+/// 按当前内容重新同步指定笔记的内容链接，返回因无法解密而跳过的笔记数
+///
+/// 用于批量写入笔记之后：引用可能指向之后才写入的笔记，需全部写入后再解析
+pub async fn resync_content_links(
+    db: &DatabaseConnection,
+    note_ids: &[i64],
+    encryption_key: Option<&str>,
+) -> Result<u64> {
+    let mut skipped = 0;
+    for chunk in note_ids.chunks(RESYNC_BATCH_SIZE) {
+        let notes = note::Entity::find()
+            .filter(note::Column::Id.is_in(chunk.to_vec()))
+            .all(db)
+            .await?;
+        let txn = db.begin().await?;
+        for n in &notes {
+            match plain_content(n, encryption_key) {
+                Some(content) => sync_content_links(&txn, n.id, &content, n.content_type).await?,
+                None => skipped += 1,
+            }
+        }
+        txn.commit().await?;
+    }
+    Ok(skipped)
+}
+
+/// 为内容链接功能之前已有的笔记回填内容链接
+///
+/// 只执行一次；存在无法解密的笔记时不记录完成，下次提供密钥后再执行
+pub async fn backfill_content_links(
+    db: &DatabaseConnection,
+    encryption_key: Option<&str>,
+) -> Result<()> {
+    if settings::get_all(db)
+        .await?
+        .get(BACKFILL_DONE_KEY)
+        .is_some_and(|v| v == "1")
+    {
+        return Ok(());
+    }
+
+    let note_ids: Vec<i64> = note::Entity::find()
+        .select_only()
+        .column(note::Column::Id)
+        .order_by_asc(note::Column::Id)
+        .into_tuple::<i64>()
+        .all(db)
+        .await?;
+    let skipped = resync_content_links(db, &note_ids, encryption_key).await?;
+    if skipped == 0 {
+        settings::save(
+            db,
+            HashMap::from([(BACKFILL_DONE_KEY.to_string(), "1".to_string())]),
+        )
+        .await?;
+    }
+
+    info!(
+        "Note content links backfilled: notes={}, skipped={}",
+        note_ids.len(),
+        skipped
+    );
+    Ok(())
+}
+
+/// 在后台执行 `backfill_content_links`，失败时只记录日志，下次启动时重试
+pub fn spawn_backfill_content_links(db: DatabaseConnection, encryption_key: Option<String>) {
+    tokio::spawn(async move {
+        if let Err(e) = backfill_content_links(&db, encryption_key.as_deref()).await {
+            warn!("Note content link backfill failed: {}", e);
+        }
+    });
+}
+
+/// 删除与新增指定笔记的内容链接
+async fn apply_content_link_changes<C: ConnectionTrait>(
+    db: &C,
+    note_id: i64,
+    removed: Vec<i64>,
+    added: Vec<i64>,
+) -> Result<()> {
+    if !removed.is_empty() {
+        note_link::Entity::delete_many()
+            .filter(note_link::Column::SourceNoteId.eq(note_id))
+            .filter(note_link::Column::TargetNoteId.is_in(removed))
+            .filter(note_link::Column::Origin.eq(LinkOrigin::Content.as_i32()))
+            .exec(db)
+            .await?;
+    }
+
+    let now = Local::now().naive_local();
+    let added: Vec<note_link::ActiveModel> = added
+        .into_iter()
+        .map(|target| note_link::ActiveModel {
+            id: NotSet,
+            source_note_id: Set(note_id),
+            target_note_id: Set(target),
+            create_time: Set(now),
+            origin: Set(LinkOrigin::Content.as_i32()),
+            relation: Set(String::new()),
+        })
+        .collect();
+    if !added.is_empty() {
+        note_link::Entity::insert_many(added).exec(db).await?;
+    }
+    Ok(())
+}
+
+/// 删除与指定笔记相关的全部链接（出链与反向链接），用于笔记被彻底删除时
+pub async fn delete_note_links<C: ConnectionTrait>(db: &C, note_ids: &[i64]) -> Result<()> {
+    if note_ids.is_empty() {
+        return Ok(());
+    }
+    note_link::Entity::delete_many()
+        .filter(
+            Condition::any()
+                .add(note_link::Column::SourceNoteId.is_in(note_ids.to_vec()))
+                .add(note_link::Column::TargetNoteId.is_in(note_ids.to_vec())),
+        )
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+/// 检查链接完整性，`repair` 为 true 时同时修复
+///
+/// - 悬空链接：source 或 target 笔记已不存在的链接，修复时删除
+/// - 过期的内容链接：与笔记当前内容不一致的内容链接，修复时重新同步
+/// - 无法解析的内容引用：内容中引用了不存在的笔记 ID 或标题，只报告不修改内容
+///
+/// 回收站中的笔记视为存在；加密且无法解密的笔记跳过内容检查
+pub async fn check_integrity(
+    db: &DatabaseConnection,
+    encryption_key: Option<&str>,
+    repair: bool,
+) -> Result<LinkIntegrityReport> {
+    let notes = note::Entity::find()
+        .order_by_asc(note::Column::Id)
+        .all(db)
+        .await?;
+    let note_ids: HashSet<i64> = notes.iter().map(|n| n.id).collect();
+
+    let mut report = LinkIntegrityReport {
+        repaired: repair,
+        ..Default::default()
+    };
+
+    report.dangling_links = note_link::Entity::find()
+        .order_by_asc(note_link::Column::Id)
+        .all(db)
+        .await?
+        .into_iter()
+        .filter(|link| {
+            !note_ids.contains(&link.source_note_id) || !note_ids.contains(&link.target_note_id)
+        })
+        .map(|link| DanglingLink {
+            id: link.id,
+            source_note_id: link.source_note_id,
+            target_note_id: link.target_note_id,
+            origin: link.origin,
+            relation: link.relation,
+        })
+        .collect();
+
+    // 只检查时不开启写事务
+    if repair {
+        let txn = db.begin().await?;
+        if !report.dangling_links.is_empty() {
+            let ids: Vec<i64> = report.dangling_links.iter().map(|l| l.id).collect();
+            note_link::Entity::delete_many()
+                .filter(note_link::Column::Id.is_in(ids))
+                .exec(&txn)
+                .await?;
+        }
+        check_notes(&txn, &notes, &note_ids, encryption_key, repair, &mut report).await?;
+        txn.commit().await?;
+    } else {
+        check_notes(db, &notes, &note_ids, encryption_key, repair, &mut report).await?;
+    }
+
+    Ok(report)
+}
+
+/// 检查每篇笔记的引用与内容链接，`repair` 为 true 时同步内容链接
+async fn check_notes<C: ConnectionTrait>(
+    db: &C,
+    notes: &[note::Model],
+    note_ids: &HashSet<i64>,
+    encryption_key: Option<&str>,
+    repair: bool,
+    report: &mut LinkIntegrityReport,
+) -> Result<()> {
+    let titles: HashSet<&str> = notes.iter().map(|n| n.title.as_str()).collect();
+
+    for n in notes {
+        let Some(content) = plain_content(n, encryption_key) else {
+            report.skipped_notes += 1;
+            continue;
+        };
+
+        for r in parse_wiki_refs(&content, n.content_type) {
+            let resolved = match &r {
+                WikiRef::Id(id) => note_ids.contains(id),
+                WikiRef::Title(title) => titles.contains(title.as_str()),
+            };
+            if !resolved {
+                let (target_id, target_title) = match r {
+                    WikiRef::Id(id) => (id, String::new()),
+                    WikiRef::Title(title) => (0, title),
+                };
+                report.broken_references.push(BrokenReference {
+                    note_id: n.id,
+                    note_title: n.title.clone(),
+                    target_id,
+                    target_title,
+                });
+            }
+        }
+
+        let (removed, added) = content_link_changes(db, n.id, &content, n.content_type).await?;
+        if removed.is_empty() && added.is_empty() {
+            continue;
+        }
+        report.stale_content_links += (removed.len() + added.len()) as u64;
+        if repair {
+            apply_content_link_changes(db, n.id, removed, added).await?;
+        }
+    }
+    Ok(())
+}
+
+/// 内容中引用了指定笔记的笔记 ID
+pub async fn find_content_sources<C: ConnectionTrait>(
+    db: &C,
+    target_note_id: i64,
+) -> Result<Vec<i64>> {
+    Ok(note_link::Entity::find()
+        .select_only()
+        .column(note_link::Column::SourceNoteId)
+        .filter(note_link::Column::TargetNoteId.eq(target_note_id))
+        .filter(note_link::Column::Origin.eq(LinkOrigin::Content.as_i32()))
+        .order_by_asc(note_link::Column::SourceNoteId)
+        .into_tuple::<i64>()
+        .all(db)
+        .await?)
+}
@@ -0,0 +1,382 @@
+//! 笔记版本对比服务
+//!
+//! 每条历史记录保存的是该次操作之前的版本：标题、标签、笔记本、内容类型来自 `extra`，
+//! 内容来自 `old_content`；创建记录没有“之前的版本”，取创建时的内容 `new_content`。
+//! 早期版本写入的更新记录中 `extra` 的笔记本是修改后的值（标题、标签仍是修改前的值），
+//! 这类记录的版本显示修改后的笔记本，恢复时也会回到该笔记本。
+//! 版本可以与另一条历史记录或笔记的当前版本对比。
+//!
+//! 内容对比前统一转换为按行的纯文本：HTML 按块级元素分行并去除标签，Markdown 保持原文。
+//! 加密的内容使用当前 Profile 的密钥解密，无法解密时按空文本参与对比并标记。
+//!
+//! 恢复历史版本时按快照重建标题、内容、内容类型、笔记本与标签，经由笔记更新流程写入，
+//! 恢复操作本身也会生成一条 `OperationType::Restore` 历史记录。
+
+use std::collections::HashSet;
+
+use anyhow::Result;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+use similar::{ChangeTag, DiffTag, TextDiff};
+use tracing::{info, warn};
+
+use crate::entity;
+use crate::i18n::t_simple;
+use crate::model::{
+    DiffKind, DiffLine, DiffSegment, Note, NoteHistory, NoteRevision, NoteRevisionDiff,
+    NoteRevisionDiffParam, OperateSource, OperationType, Tag,
+};
+use crate::service::{crypto, history_storage, note};
+
+/// 某个版本的完整快照
+pub struct RevisionSnapshot {
+    pub revision: NoteRevision,
+    /// 明文内容（无法解密时为空）
+    pub content: String,
+}
+
+/// 解密内容，无法解密时返回 None
+fn plain_content(content: &str, encryption_key: Option<&str>) -> Option<String> {
+    if !crypto::is_encrypted(content) {
+        return Some(content.to_string());
+    }
+    let key = encryption_key.filter(|k| !k.is_empty())?;
+    crypto::decrypt(content, key)
+        .map_err(|e| warn!("Failed to decrypt revision content: {}", e))
+        .ok()
+}
+
+/// 读取历史记录保存的版本
+pub async fn history_snapshot(
+    db: &DatabaseConnection,
+    history_id: i64,
+    encryption_key: Option<&str>,
+) -> Result<Option<RevisionSnapshot>> {
+    let Some(model) = entity::note_history::Entity::find_by_id(history_id)
+        .one(db)
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    let history = NoteHistory::from(history_storage::decode(db, model).await?);
+    let raw = if history.operate_type == OperationType::Create.as_i32() {
+        &history.new_content
+    } else {
+        &history.old_content
+    };
+    let content = plain_content(raw, encryption_key);
+
+    Ok(Some(RevisionSnapshot {
+        revision: NoteRevision {
+            history_id: history.id,
+            note_id: history.note_id,
+            title: history.extra.title,
+            notebook_id: history.extra.notebook_id,
+            notebook_name: history.extra.notebook_name,
+            content_type: history.extra.content_type,
+            tags: history.extra.tags,
+            content_encrypted: content.is_none(),
+            time: Some(history.operate_time),
+        },
+        content: content.unwrap_or_default(),
+    }))
+}
+
+/// 读取笔记的当前版本
+pub async fn current_snapshot(
+    db: &DatabaseConnection,
+    note_id: i64,
+    encryption_key: Option<&str>,
+) -> Result<Option<RevisionSnapshot>> {
+    let Some(current) = note::find_by_id(db, note_id).await? else {
+        return Ok(None);
+    };
+
+    let content = plain_content(&current.content, encryption_key);
+
+    Ok(Some(RevisionSnapshot {
+        revision: NoteRevision {
+            history_id: 0,
+            note_id: current.id,
+            title: current.title,
+            notebook_id: current.notebook_id,
+            notebook_name: current.notebook_name,
+            content_type: current.content_type,
+            tags: current.tags,
+            content_encrypted: content.is_none(),
+            time: current.update_time,
+        },
+        content: content.unwrap_or_default(),
+    }))
+}
+
+/// 将笔记内容转换为按行的纯文本
+///
+/// HTML 在块级元素与换行标签处分行，去除标签并解码常见实体；Markdown 保留原文。
+/// 每行合并连续空白，空行被忽略
+pub fn content_to_lines(content: &str, content_type: i32) -> String {
+    let text = if content_type == 1 {
+        content.to_string()
+    } else {
+        note::strip_html(content, true)
+    };
+
+    text.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<&str>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn diff_kind(tag: ChangeTag) -> DiffKind {
+    match tag {
+        ChangeTag::Equal => DiffKind::Equal,
+        ChangeTag::Insert => DiffKind::Insert,
+        ChangeTag::Delete => DiffKind::Delete,
+    }
+}
+
+/// 追加片段，与上一个同类型片段合并
+fn push_segment(segments: &mut Vec<DiffSegment>, kind: DiffKind, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    match segments.last_mut() {
+        Some(last) if last.kind == kind => last.text.push_str(text),
+        _ => segments.push(DiffSegment {
+            kind,
+            text: text.to_string(),
+        }),
+    }
+}
+
+/// 词级差异（中日韩文本按字切分）
+pub fn diff_words(old: &str, new: &str) -> Vec<DiffSegment> {
+    let diff = TextDiff::from_unicode_words(old, new);
+    let mut segments = Vec::new();
+    for change in diff.iter_all_changes() {
+        push_segment(&mut segments, diff_kind(change.tag()), change.value());
+    }
+    segments
+}
+
+/// 行级差异，被修改的行附带词级差异
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let diff = TextDiff::from_lines(old, new);
+    let mut lines = Vec::new();
+
+    for op in diff.ops() {
+        let replaced = op.tag() == DiffTag::Replace;
+        for change in diff.iter_inline_changes(op) {
+            let kind = diff_kind(change.tag());
+            let mut text = String::new();
+            let mut segments = Vec::new();
+            for (emphasized, value) in change.iter_strings_lossy() {
+                let value = value.trim_end_matches('\n');
+                text.push_str(value);
+                if replaced {
+                    let segment_kind = if emphasized { kind } else { DiffKind::Equal };
+                    push_segment(&mut segments, segment_kind, value);
+                }
+            }
+            lines.push(DiffLine {
+                kind,
+                text,
+                segments,
+            });
+        }
+    }
+
+    lines
+}
+
+/// 对比两个版本
+pub fn diff_snapshots(from: RevisionSnapshot, to: RevisionSnapshot) -> NoteRevisionDiff {
+    let from_ids: HashSet<i64> = from.revision.tags.iter().map(|t| t.id).collect();
+    let to_ids: HashSet<i64> = to.revision.tags.iter().map(|t| t.id).collect();
+
+    let old_text = content_to_lines(&from.content, from.revision.content_type);
+    let new_text = content_to_lines(&to.content, to.revision.content_type);
+
+    NoteRevisionDiff {
+        title: diff_words(&from.revision.title, &to.revision.title),
+        content: diff_lines(&old_text, &new_text),
+        tags_added: to
+            .revision
+            .tags
+            .iter()
+            .filter(|t| !from_ids.contains(&t.id))
+            .cloned()
+            .collect(),
+        tags_removed: from
+            .revision
+            .tags
+            .iter()
+            .filter(|t| !to_ids.contains(&t.id))
+            .cloned()
+            .collect(),
+        notebook_changed: from.revision.notebook_id != to.revision.notebook_id,
+        content_type_changed: from.revision.content_type != to.revision.content_type,
+        from: from.revision,
+        to: to.revision,
+    }
+}
+
+/// 对比两个历史版本，或历史版本与当前版本
+///
+/// 任一版本不存在时返回 None；两个历史记录不属于同一笔记时报错
+pub async fn diff(
+    db: &DatabaseConnection,
+    param: &NoteRevisionDiffParam,
+    encryption_key: Option<&str>,
+) -> Result<Option<NoteRevisionDiff>> {
+    param.validate()?;
+
+    let Some(from) = history_snapshot(db, param.from_history_id, encryption_key).await? else {
+        return Ok(None);
+    };
+
+    let to = if param.to_history_id > 0 {
+        history_snapshot(db, param.to_history_id, encryption_key).await?
+    } else {
+        current_snapshot(db, from.revision.note_id, encryption_key).await?
+    };
+    let Some(to) = to else {
+        return Ok(None);
+    };
+
+    if from.revision.note_id != to.revision.note_id {
+        anyhow::bail!("{}", t_simple("validation.invalidRevision"));
+    }
+
+    Ok(Some(diff_snapshots(from, to)))
+}
+
+/// 解析快照中的笔记本：优先按 ID，已删除时按名称查找同名笔记本，仍找不到时返回 None
+async fn resolve_notebook(db: &DatabaseConnection, revision: &NoteRevision) -> Result<Option<i64>> {
+    if revision.notebook_id > 0
+        && let Some(notebook) = entity::notebook::Entity::find_by_id(revision.notebook_id)
+            .one(db)
+            .await?
+    {
+        return Ok(Some(notebook.id));
+    }
+    if revision.notebook_name.is_empty() {
+        return Ok(None);
+    }
+    Ok(entity::notebook::Entity::find()
+        .filter(entity::notebook::Column::Name.eq(&revision.notebook_name))
+        .one(db)
+        .await?
+        .map(|n| n.id))
+}
+
+/// 解析快照中的标签：优先按 ID，已删除时按名称查找同名标签，仍找不到的标签被跳过
+async fn resolve_tags(db: &DatabaseConnection, tags: &[Tag]) -> Result<Vec<Tag>> {
+    let mut resolved: Vec<Tag> = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let found = match entity::tag::Entity::find_by_id(tag.id).one(db).await? {
+            Some(model) => Some(model),
+            None => {
+                entity::tag::Entity::find()
+                    .filter(entity::tag::Column::Name.eq(&tag.name))
+                    .one(db)
+                    .await?
+            }
+        };
+        match found {
+            Some(model) if !resolved.iter().any(|t| t.id == model.id) => {
+                resolved.push(Tag::from(model))
+            }
+            Some(_) => {}
+            None => info!("Skip deleted tag when restoring revision: {}", tag.name),
+        }
+    }
+    Ok(resolved)
+}
+
+/// 将笔记恢复到指定历史版本
+///
+/// 历史记录不存在或不属于该笔记、笔记已被永久删除时返回 None；
+/// 快照内容无法解密或笔记在回收站中时报错。`version` 为调用方读取到的笔记版本，
+/// 大于 0 时与当前版本比较，不一致时返回 `NOTE_VERSION_CONFLICT` 错误。
+/// 快照引用的笔记本或标签已被删除时，按名称匹配现有的同名项，
+/// 匹配不到时笔记本保持不变、标签被跳过
+pub async fn restore(
+    db: &DatabaseConnection,
+    note_id: i64,
+    history_id: i64,
+    version: i64,
+    source: OperateSource,
+    encryption_key: Option<&str>,
+) -> Result<Option<Note>> {
+    let Some(snapshot) = history_snapshot(db, history_id, encryption_key).await? else {
+        return Ok(None);
+    };
+    if snapshot.revision.note_id != note_id {
+        return Ok(None);
+    }
+    if snapshot.revision.content_encrypted {
+        anyhow::bail!("{}", t_simple("validation.revisionEncrypted"));
+    }
+    let Some(mut note) = note::find_by_id(db, note_id).await? else {
+        return Ok(None);
+    };
+    if note.deleted_at.is_some() {
+        anyhow::bail!("{}", t_simple("validation.revisionNoteDeleted"));
+    }
+
+    if let Some(notebook_id) = resolve_notebook(db, &snapshot.revision).await? {
+        note.notebook_id = notebook_id;
+    }
+    note.tags = resolve_tags(db, &snapshot.revision.tags).await?;
+    note.title = snapshot.revision.title;
+    note.content = snapshot.content;
+    note.content_type = snapshot.revision.content_type;
+    note.version = version;
+
+    note::update_with_operation(db, &note, source, OperationType::Restore, encryption_key).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn html_is_split_into_lines() {
+        let html = "<h1>Title</h1><p>Hello&nbsp;<b>world</b></p><ul><li>a</li><li>b</li></ul>";
+        assert_eq!(content_to_lines(html, 0), "Title\nHello world\na\nb");
+        assert_eq!(content_to_lines("# T\n\n  a   b ", 1), "# T\na b");
+    }
+
+    #[test]
+    fn changed_line_has_word_segments() {
+        let lines = diff_lines("keep\nold word\n", "keep\nnew word\nadded\n");
+        let kinds: Vec<DiffKind> = lines.iter().map(|l| l.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                DiffKind::Equal,
+                DiffKind::Delete,
+                DiffKind::Insert,
+                DiffKind::Insert
+            ]
+        );
+        assert_eq!(lines[1].text, "old word");
+        assert!(
+            lines[1]
+                .segments
+                .iter()
+                .any(|s| s.kind == DiffKind::Delete && s.text == "old")
+        );
+        assert!(lines[0].segments.is_empty());
+    }
+
+    #[test]
+    fn title_words_are_diffed() {
+        let segments = diff_words("会议纪要", "会议记录");
+        assert_eq!(segments.first().map(|s| s.kind), Some(DiffKind::Equal));
+        assert!(segments.iter().any(|s| s.kind == DiffKind::Insert));
+        assert!(segments.iter().any(|s| s.kind == DiffKind::Delete));
+    }
+}
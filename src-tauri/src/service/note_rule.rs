@@ -0,0 +1,644 @@
+//! 笔记自动规则服务模块
+//!
+//! 本模块提供笔记自动规则相关的业务逻辑实现。
+//!
+//! # 功能概述
+//! - 查询、创建、更新、删除规则（按排序值降序、ID 升序依次执行）
+//! - 计算创建/更新笔记时规则产生的变更，由 `note::create_with_key` /
+//!   `note::update_with_key` 在同一次保存中写入（同一条历史记录）
+//! - 对已有笔记手动执行规则，支持仅预览（dry run）
+//!
+//! # 执行语义
+//! - 所有规则都按笔记保存时的状态判断，前面规则的动作不影响后续规则的匹配
+//! - 移动、收藏、置顶与 MCP 权限以最后一条命中规则的动作为准，标签增删按顺序叠加
+//! - 引用已删除的笔记本或标签的动作会被忽略
+//! - 内容无法解密的笔记，内容条件一律不满足
+//! - 手动执行时 `source` 条件按笔记的创建来源判断
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::Local;
+use sea_orm::{
+    ActiveModelTrait,
+    ActiveValue::{NotSet, Set},
+    ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, IntoActiveModel, QueryFilter,
+    QueryOrder, QuerySelect, TryIntoModel,
+};
+
+use crate::{
+    entity,
+    model::{
+        Note, NoteRule, NoteRuleCondition, NoteRuleEffect, NoteRuleMatch, NoteRuleRunResult,
+        OperateSource, OperationType, Tag,
+    },
+    service::{crypto, hierarchy, note, notebook, tag},
+};
+
+/// 规则触发时机
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RuleTrigger {
+    /// 创建笔记
+    Create,
+    /// 更新笔记
+    Update,
+}
+
+/// 规则匹配时笔记的状态
+struct RuleSubject<'a> {
+    title: &'a str,
+    /// 明文内容，无法解密时为 `None`
+    content: Option<&'a str>,
+    source: OperateSource,
+    notebook_id: i64,
+    tag_ids: Vec<i64>,
+    is_starred: bool,
+    is_pinned: bool,
+    mcp_access: i32,
+}
+
+/// 规则匹配所需的笔记本与标签层级，均为 (id, parent_id)
+struct RuleContext {
+    notebook_pairs: Vec<(i64, i64)>,
+    tag_pairs: Vec<(i64, i64)>,
+}
+
+/// 操作来源在条件中的名称
+fn source_name(source: OperateSource) -> &'static str {
+    match source {
+        OperateSource::User => "user",
+        OperateSource::Mcp => "mcp",
+        OperateSource::Sync => "sync",
+    }
+}
+
+/// 文本条件判断（不区分大小写）
+fn text_matches(operator: &str, text: &str, value: &str) -> bool {
+    let text = text.to_lowercase();
+    let value = value.to_lowercase();
+    match operator {
+        "contains" => text.contains(&value),
+        "not_contains" => !text.contains(&value),
+        "starts_with" => text.starts_with(&value),
+        "ends_with" => text.ends_with(&value),
+        "equals" => text == value,
+        _ => false,
+    }
+}
+
+/// 单个条件是否满足
+fn condition_matches(
+    condition: &NoteRuleCondition,
+    subject: &RuleSubject,
+    context: &RuleContext,
+) -> bool {
+    let id = condition.value.parse::<i64>().unwrap_or(-1);
+    match (condition.field.as_str(), condition.operator.as_str()) {
+        ("title", operator) => text_matches(operator, subject.title, &condition.value),
+        ("content", operator) => subject
+            .content
+            .is_some_and(|content| text_matches(operator, content, &condition.value)),
+        ("source", "equals") => source_name(subject.source) == condition.value,
+        ("source", "not_equals") => source_name(subject.source) != condition.value,
+        ("notebook", "equals") => subject.notebook_id == id,
+        ("notebook", "not_equals") => subject.notebook_id != id,
+        ("notebook", "within") => {
+            hierarchy::subtree_ids(&context.notebook_pairs, id).contains(&subject.notebook_id)
+        }
+        ("tag", "has") => subject.tag_ids.contains(&id),
+        ("tag", "not_has") => !subject.tag_ids.contains(&id),
+        ("tag", "has_within") => hierarchy::subtree_ids(&context.tag_pairs, id)
+            .iter()
+            .any(|t| subject.tag_ids.contains(t)),
+        _ => false,
+    }
+}
+
+/// 规则是否命中（没有条件的规则命中所有笔记）
+fn rule_matches(rule: &NoteRule, subject: &RuleSubject, context: &RuleContext) -> bool {
+    if rule.conditions.is_empty() {
+        return true;
+    }
+    let mut results = rule
+        .conditions
+        .iter()
+        .map(|c| condition_matches(c, subject, context));
+    if rule.match_any {
+        results.any(|m| m)
+    } else {
+        results.all(|m| m)
+    }
+}
+
+/// 依次执行命中的规则，返回命中的规则 ID 与相对笔记当前状态的变更
+fn evaluate(
+    rules: &[NoteRule],
+    subject: &RuleSubject,
+    context: &RuleContext,
+) -> (Vec<i64>, NoteRuleEffect) {
+    let notebook_ids: HashSet<i64> = context.notebook_pairs.iter().map(|(id, _)| *id).collect();
+    let tag_ids: HashSet<i64> = context.tag_pairs.iter().map(|(id, _)| *id).collect();
+
+    let mut matched = Vec::new();
+    let mut notebook_id = subject.notebook_id;
+    let mut tags = subject.tag_ids.clone();
+    let mut is_starred = subject.is_starred;
+    let mut is_pinned = subject.is_pinned;
+    let mut mcp_access = subject.mcp_access;
+
+    for rule in rules.iter().filter(|r| rule_matches(r, subject, context)) {
+        matched.push(rule.id);
+        for action in &rule.actions {
+            let id = action.value.parse::<i64>().unwrap_or(-1);
+            match action.action.as_str() {
+                "move" if notebook_ids.contains(&id) => notebook_id = id,
+                "add_tag" if tag_ids.contains(&id) && !tags.contains(&id) => tags.push(id),
+                "remove_tag" => tags.retain(|t| *t != id),
+                "star" => is_starred = true,
+                "unstar" => is_starred = false,
+                "pin" => is_pinned = true,
+                "unpin" => is_pinned = false,
+                "set_mcp_access" if (0..=3).contains(&id) => mcp_access = id as i32,
+                _ => {}
+            }
+        }
+    }
+
+    let effect = NoteRuleEffect {
+        notebook_id: (notebook_id != subject.notebook_id).then_some(notebook_id),
+        add_tag_ids: tags
+            .iter()
+            .filter(|t| !subject.tag_ids.contains(t))
+            .copied()
+            .collect(),
+        remove_tag_ids: subject
+            .tag_ids
+            .iter()
+            .filter(|t| !tags.contains(t))
+            .copied()
+            .collect(),
+        is_starred: (is_starred != subject.is_starred).then_some(is_starred),
+        is_pinned: (is_pinned != subject.is_pinned).then_some(is_pinned),
+        mcp_access: (mcp_access != subject.mcp_access).then_some(mcp_access),
+    };
+    (matched, effect)
+}
+
+/// 加载规则匹配所需的层级信息
+async fn load_context<C: ConnectionTrait>(db: &C) -> anyhow::Result<RuleContext> {
+    Ok(RuleContext {
+        notebook_pairs: notebook::hierarchy_pairs(db).await?,
+        tag_pairs: tag::hierarchy_pairs(db).await?,
+    })
+}
+
+/// 查询所有规则
+///
+/// # 返回
+/// - `Ok(Vec<NoteRule>)`: 规则列表，按执行顺序（排序值降序、ID 升序）排列
+/// - `Err`: 查询失败
+pub async fn find_all<C: ConnectionTrait>(db: &C) -> anyhow::Result<Vec<NoteRule>> {
+    let rules = entity::note_rule::Entity::find()
+        .order_by_desc(entity::note_rule::Column::SortOrder)
+        .order_by_asc(entity::note_rule::Column::Id)
+        .all(db)
+        .await?
+        .into_iter()
+        .map(NoteRule::from)
+        .collect::<Vec<NoteRule>>();
+
+    Ok(rules)
+}
+
+/// 根据 ID 查询规则
+pub async fn find_by_id(db: &DatabaseConnection, id: i64) -> anyhow::Result<Option<NoteRule>> {
+    Ok(entity::note_rule::Entity::find_by_id(id)
+        .one(db)
+        .await?
+        .map(NoteRule::from))
+}
+
+/// 创建规则
+///
+/// # 返回
+/// - `Ok(Some(NoteRule))`: 创建成功，返回新规则
+/// - `Err`: 创建失败
+pub async fn create(db: &DatabaseConnection, rule: &NoteRule) -> anyhow::Result<Option<NoteRule>> {
+    let now = Local::now().naive_local();
+
+    let active_model = entity::note_rule::ActiveModel {
+        id: NotSet,
+        name: Set(rule.name.trim().to_string()),
+        enabled: Set(i32::from(rule.enabled)),
+        run_on_create: Set(i32::from(rule.run_on_create)),
+        run_on_update: Set(i32::from(rule.run_on_update)),
+        match_any: Set(i32::from(rule.match_any)),
+        conditions: Set(serde_json::to_string(&rule.conditions)?),
+        actions: Set(serde_json::to_string(&rule.actions)?),
+        sort_order: Set(rule.sort_order),
+        create_time: Set(now),
+        update_time: Set(now),
+    };
+
+    let entity = active_model.insert(db).await?;
+
+    Ok(Some(NoteRule::from(entity)))
+}
+
+/// 更新规则
+///
+/// # 返回
+/// - `Ok(Some(NoteRule))`: 更新成功，返回更新后的规则
+/// - `Ok(None)`: 规则不存在
+/// - `Err`: 更新失败
+pub async fn update(db: &DatabaseConnection, rule: &NoteRule) -> anyhow::Result<Option<NoteRule>> {
+    if let Some(entity) = entity::note_rule::Entity::find_by_id(rule.id)
+        .one(db)
+        .await?
+    {
+        let mut active_model: entity::note_rule::ActiveModel = entity.into_active_model();
+
+        active_model
+            .name
+            .set_if_not_equals(rule.name.trim().to_string());
+        active_model
+            .enabled
+            .set_if_not_equals(i32::from(rule.enabled));
+        active_model
+            .run_on_create
+            .set_if_not_equals(i32::from(rule.run_on_create));
+        active_model
+            .run_on_update
+            .set_if_not_equals(i32::from(rule.run_on_update));
+        active_model
+            .match_any
+            .set_if_not_equals(i32::from(rule.match_any));
+        active_model
+            .conditions
+            .set_if_not_equals(serde_json::to_string(&rule.conditions)?);
+        active_model
+            .actions
+            .set_if_not_equals(serde_json::to_string(&rule.actions)?);
+        active_model.sort_order.set_if_not_equals(rule.sort_order);
+
+        if active_model.is_changed() {
+            active_model.update_time = Set(Local::now().naive_local());
+            let entity = active_model.update(db).await?;
+            return Ok(Some(NoteRule::from(entity)));
+        }
+
+        Ok(Some(NoteRule::from(active_model.try_into_model()?)))
+    } else {
+        Ok(None)
+    }
+}
+
+/// 根据 ID 删除规则
+pub async fn delete_by_id(db: &DatabaseConnection, id: i64) -> anyhow::Result<()> {
+    entity::note_rule::Entity::delete_by_id(id).exec(db).await?;
+
+    Ok(())
+}
+
+/// 计算保存笔记时启用的规则产生的变更
+///
+/// `note` 为即将保存的笔记（明文内容）；更新时收藏、置顶与 MCP 权限取笔记当前值，
+/// 创建时收藏与置顶视为未设置
+pub async fn effect_on_save(
+    db: &DatabaseConnection,
+    note: &Note,
+    source: OperateSource,
+    trigger: RuleTrigger,
+) -> anyhow::Result<NoteRuleEffect> {
+    let rules: Vec<NoteRule> = find_all(db)
+        .await?
+        .into_iter()
+        .filter(|r| {
+            r.enabled
+                && match trigger {
+                    RuleTrigger::Create => r.run_on_create,
+                    RuleTrigger::Update => r.run_on_update,
+                }
+        })
+        .collect();
+    if rules.is_empty() {
+        return Ok(NoteRuleEffect::default());
+    }
+
+    let (is_starred, is_pinned, mcp_access) = match trigger {
+        RuleTrigger::Create => (false, false, note.mcp_access),
+        RuleTrigger::Update => match entity::note::Entity::find_by_id(note.id).one(db).await? {
+            Some(current) => (
+                current.is_starred == 1,
+                current.is_pinned == 1,
+                current.mcp_access,
+            ),
+            None => return Ok(NoteRuleEffect::default()),
+        },
+    };
+
+    let subject = RuleSubject {
+        title: &note.title,
+        content: Some(&note.content),
+        source,
+        notebook_id: note.notebook_id,
+        tag_ids: note.tags.iter().map(|t| t.id).collect(),
+        is_starred,
+        is_pinned,
+        mcp_access,
+    };
+    let context = load_context(db).await?;
+    Ok(evaluate(&rules, &subject, &context).1)
+}
+
+/// 将规则变更中的笔记本与标签应用到待保存的笔记
+///
+/// # 返回
+/// - `Ok(Some(Note))`: 应用变更后的笔记
+/// - `Ok(None)`: 笔记本与标签均无变化
+pub async fn apply_to_note(
+    db: &DatabaseConnection,
+    note: &Note,
+    effect: &NoteRuleEffect,
+) -> anyhow::Result<Option<Note>> {
+    if effect.notebook_id.is_none()
+        && effect.add_tag_ids.is_empty()
+        && effect.remove_tag_ids.is_empty()
+    {
+        return Ok(None);
+    }
+
+    let mut note = note.clone();
+    if let Some(notebook_id) = effect.notebook_id {
+        note.notebook_id = notebook_id;
+    }
+    note.tags.retain(|t| !effect.remove_tag_ids.contains(&t.id));
+    if !effect.add_tag_ids.is_empty() {
+        let added = entity::tag::Entity::find()
+            .filter(entity::tag::Column::Id.is_in(effect.add_tag_ids.clone()))
+            .all(db)
+            .await?;
+        note.tags.extend(added.into_iter().map(Tag::from));
+    }
+    Ok(Some(note))
+}
+
+/// 对所有未删除的笔记手动执行规则
+///
+/// - `rule_ids`: 要执行的规则 ID，为空时执行所有启用的规则（指定时停用的规则也会执行）
+/// - `dry_run`: 为 true 时只返回预览，不修改笔记
+///
+/// # 返回
+/// - `Ok(NoteRuleRunResult)`: 会产生变更（预览）或已变更的笔记
+pub async fn run(
+    db: &DatabaseConnection,
+    rule_ids: &[i64],
+    dry_run: bool,
+    source: OperateSource,
+    encryption_key: Option<&str>,
+) -> anyhow::Result<NoteRuleRunResult> {
+    let rules: Vec<NoteRule> = find_all(db)
+        .await?
+        .into_iter()
+        .filter(|r| {
+            if rule_ids.is_empty() {
+                r.enabled
+            } else {
+                rule_ids.contains(&r.id)
+            }
+        })
+        .collect();
+
+    let notes = entity::note::Entity::find()
+        .filter(entity::note::Column::DeletedAt.is_null())
+        .order_by_asc(entity::note::Column::Id)
+        .all(db)
+        .await?;
+    let mut result = NoteRuleRunResult {
+        dry_run,
+        checked: notes.len() as u64,
+        matches: Vec::new(),
+    };
+    if rules.is_empty() || notes.is_empty() {
+        return Ok(result);
+    }
+
+    let context = load_context(db).await?;
+
+    let mut tags_by_note: HashMap<i64, Vec<i64>> = HashMap::new();
+    for (note_id, tag_id) in entity::note_tags::Entity::find()
+        .select_only()
+        .column(entity::note_tags::Column::NoteId)
+        .column(entity::note_tags::Column::TagId)
+        .order_by_desc(entity::note_tags::Column::SortOrder)
+        .into_tuple::<(i64, i64)>()
+        .all(db)
+        .await?
+    {
+        tags_by_note.entry(note_id).or_default().push(tag_id);
+    }
+
+    // 笔记的创建来源（取 Create 历史记录，缺失时视为用户创建）
+    let created_by: HashMap<i64, i32> = entity::note_history::Entity::find()
+        .select_only()
+        .column(entity::note_history::Column::NoteId)
+        .column(entity::note_history::Column::OperateSource)
+        .filter(entity::note_history::Column::OperateType.eq(OperationType::Create.as_i32()))
+        .into_tuple::<(i64, i32)>()
+        .all(db)
+        .await?
+        .into_iter()
+        .collect();
+
+    for n in notes {
+        let content = if crypto::is_encrypted(&n.content) {
+            encryption_key.and_then(|key| crypto::decrypt(&n.content, key).ok())
+        } else {
+            Some(n.content.clone())
+        };
+        let subject = RuleSubject {
+            title: &n.title,
+            content: content.as_deref(),
+            source: created_by
+                .get(&n.id)
+                .map_or(OperateSource::User, |s| OperateSource::from(*s)),
+            notebook_id: n.notebook_id,
+            tag_ids: tags_by_note.remove(&n.id).unwrap_or_default(),
+            is_starred: n.is_starred == 1,
+            is_pinned: n.is_pinned == 1,
+            mcp_access: n.mcp_access,
+        };
+        let (rule_ids, effect) = evaluate(&rules, &subject, &context);
+        if effect.is_empty() {
+            continue;
+        }
+        if !dry_run {
+            // 历史记录保存明文内容，加密笔记无法解密时保存原文
+            let history_content = content.as_deref().unwrap_or(&n.content);
+            note::apply_rule_effect(db, &n, history_content, &effect, source).await?;
+        }
+        result.matches.push(NoteRuleMatch {
+            note_id: n.id,
+            title: n.title,
+            rule_ids,
+            effect,
+        });
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::NoteRuleAction;
+
+    fn condition(field: &str, operator: &str, value: &str) -> NoteRuleCondition {
+        NoteRuleCondition {
+            field: field.to_string(),
+            operator: operator.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    fn action(action: &str, value: &str) -> NoteRuleAction {
+        NoteRuleAction {
+            action: action.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    fn rule(id: i64, conditions: Vec<NoteRuleCondition>, actions: Vec<NoteRuleAction>) -> NoteRule {
+        NoteRule {
+            id,
+            name: format!("rule {id}"),
+            enabled: true,
+            conditions,
+            actions,
+            ..Default::default()
+        }
+    }
+
+    fn subject(title: &str, source: OperateSource, notebook_id: i64) -> RuleSubject<'_> {
+        RuleSubject {
+            title,
+            content: Some("Agenda: release plan"),
+            source,
+            notebook_id,
+            tag_ids: vec![20],
+            is_starred: false,
+            is_pinned: false,
+            mcp_access: 0,
+        }
+    }
+
+    fn context() -> RuleContext {
+        // 笔记本 1 ─ 2，3；标签 10 ─ 20，30
+        RuleContext {
+            notebook_pairs: vec![(1, 0), (2, 1), (3, 0)],
+            tag_pairs: vec![(10, 0), (20, 10), (30, 0)],
+        }
+    }
+
+    #[test]
+    fn matches_conditions() {
+        let context = context();
+        let s = subject("[Meeting] Weekly sync", OperateSource::Mcp, 2);
+        let matches = |c: NoteRuleCondition| condition_matches(&c, &s, &context);
+
+        assert!(matches(condition("title", "starts_with", "[meeting]")));
+        assert!(!matches(condition("title", "ends_with", "meeting")));
+        assert!(matches(condition("content", "contains", "RELEASE")));
+        assert!(matches(condition("source", "equals", "mcp")));
+        assert!(!matches(condition("source", "not_equals", "mcp")));
+        assert!(matches(condition("notebook", "within", "1")));
+        assert!(!matches(condition("notebook", "equals", "1")));
+        assert!(matches(condition("tag", "has_within", "10")));
+        assert!(!matches(condition("tag", "has", "10")));
+        assert!(matches(condition("tag", "not_has", "30")));
+
+        let locked = RuleSubject {
+            content: None,
+            ..subject("Secret", OperateSource::User, 3)
+        };
+        let c = condition("content", "not_contains", "agenda");
+        assert!(!condition_matches(&c, &locked, &context));
+    }
+
+    #[test]
+    fn combines_conditions() {
+        let context = context();
+        let s = subject("[Meeting] Weekly sync", OperateSource::User, 3);
+        let mut r = rule(
+            1,
+            vec![
+                condition("title", "contains", "meeting"),
+                condition("source", "equals", "mcp"),
+            ],
+            vec![action("star", "")],
+        );
+        assert!(!rule_matches(&r, &s, &context));
+        r.match_any = true;
+        assert!(rule_matches(&r, &s, &context));
+        assert!(rule_matches(&rule(2, vec![], vec![]), &s, &context));
+    }
+
+    #[test]
+    fn evaluates_rules_in_order() {
+        let context = context();
+        let s = subject("[Meeting] Weekly sync", OperateSource::Mcp, 3);
+        let rules = vec![
+            rule(
+                1,
+                vec![condition("title", "starts_with", "[Meeting]")],
+                vec![
+                    action("move", "1"),
+                    action("add_tag", "30"),
+                    action("pin", ""),
+                ],
+            ),
+            rule(
+                2,
+                vec![condition("source", "equals", "mcp")],
+                vec![
+                    action("move", "2"),
+                    action("add_tag", "99"),
+                    action("remove_tag", "20"),
+                    action("set_mcp_access", "2"),
+                ],
+            ),
+            rule(
+                3,
+                vec![condition("source", "equals", "user")],
+                vec![action("star", "")],
+            ),
+        ];
+
+        let (matched, effect) = evaluate(&rules, &s, &context);
+        assert_eq!(matched, vec![1, 2]);
+        assert_eq!(
+            effect,
+            NoteRuleEffect {
+                notebook_id: Some(2),
+                add_tag_ids: vec![30],
+                remove_tag_ids: vec![20],
+                is_starred: None,
+                is_pinned: Some(true),
+                mcp_access: Some(2),
+            }
+        );
+
+        // 动作结果与当前状态一致时不产生变更
+        let noop = vec![rule(
+            4,
+            vec![],
+            vec![
+                action("move", "3"),
+                action("add_tag", "20"),
+                action("unstar", ""),
+            ],
+        )];
+        let (matched, effect) = evaluate(&noop, &s, &context);
+        assert_eq!(matched, vec![4]);
+        assert!(effect.is_empty());
+    }
+}
@@ -3,20 +3,53 @@
 //! 本模块提供笔记本相关的业务逻辑实现。
 //!
 //! # 功能概述
-//! - 查询所有笔记本（按排序值和更新时间排序）
+//! - 查询所有笔记本（按排序值和更新时间排序），或按层级返回笔记本树
 //! - 创建新笔记本
 //! - 更新笔记本（智能检测变更）
-//! - 删除笔记本
+//! - 移动笔记本（连同子孙笔记本，禁止移动到自身或子孙之下）
+//! - 删除笔记本（级联删除子孙笔记本）
+//!
+//! # 层级
+//! `parent_id` 为 0 表示顶级；父笔记本不存在时按顶级处理。
 
 use chrono::Local;
 use sea_orm::{
     ActiveModelTrait,
     ActiveValue::{NotSet, Set},
-    ColumnTrait, DatabaseConnection, EntityTrait, IntoActiveModel, QueryFilter, QueryOrder,
-    TransactionTrait,
+    ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, IntoActiveModel, QueryFilter,
+    QueryOrder, QuerySelect, TransactionTrait,
+};
+
+use crate::{
+    entity,
+    error::AppError,
+    model::{Notebook, NotebookTreeNode},
+    service::{hierarchy, note_link},
 };
 
-use crate::{entity, model::Notebook};
+/// 所有笔记本的 (id, parent_id)
+pub async fn hierarchy_pairs<C: ConnectionTrait>(db: &C) -> anyhow::Result<Vec<(i64, i64)>> {
+    Ok(entity::notebook::Entity::find()
+        .select_only()
+        .column(entity::notebook::Column::Id)
+        .column(entity::notebook::Column::ParentId)
+        .into_tuple::<(i64, i64)>()
+        .all(db)
+        .await?)
+}
+
+/// 检查父笔记本存在（0 表示顶级，总是有效）
+async fn ensure_parent_exists<C: ConnectionTrait>(db: &C, parent_id: i64) -> anyhow::Result<()> {
+    if parent_id != 0
+        && entity::notebook::Entity::find_by_id(parent_id)
+            .one(db)
+            .await?
+            .is_none()
+    {
+        return Err(AppError::code("NOTEBOOK_PARENT_NOT_FOUND").into());
+    }
+    Ok(())
+}
 
 /// 查询所有笔记本
 ///
@@ -39,6 +72,25 @@ pub async fn find_all(db: &DatabaseConnection) -> anyhow::Result<Vec<Notebook>>
     Ok(notebooks)
 }
 
+/// 查询笔记本树
+///
+/// # 返回
+/// - `Ok(Vec<NotebookTreeNode>)`: 顶级笔记本列表，子笔记本位于各节点的 `children` 中，
+///   同级按排序值降序、更新时间降序排列
+/// - `Err`: 查询失败
+pub async fn find_tree(db: &DatabaseConnection) -> anyhow::Result<Vec<NotebookTreeNode>> {
+    Ok(hierarchy::build_tree(
+        find_all(db).await?,
+        |n| (n.id, n.parent_id),
+        |notebook, children| NotebookTreeNode { notebook, children },
+    ))
+}
+
+/// 指定笔记本及其全部子孙笔记本的 ID（含自身）
+pub async fn find_subtree_ids<C: ConnectionTrait>(db: &C, id: i64) -> anyhow::Result<Vec<i64>> {
+    Ok(hierarchy::subtree_ids(&hierarchy_pairs(db).await?, id))
+}
+
 /// 创建笔记本
 ///
 /// # 参数
@@ -52,10 +104,12 @@ pub async fn find_all(db: &DatabaseConnection) -> anyhow::Result<Vec<Notebook>>
 /// # 说明
 /// - 自动设置创建时间和更新时间为当前时间
 /// - ID 由数据库自动生成
+/// - `parent_id` 不为 0 时父笔记本必须存在
 pub async fn create(
     db: &DatabaseConnection,
     notebook: &Notebook,
 ) -> anyhow::Result<Option<Notebook>> {
+    ensure_parent_exists(db, notebook.parent_id).await?;
     let now = Local::now().naive_local();
 
     let active_model = entity::notebook::ActiveModel {
@@ -83,22 +137,38 @@ pub async fn create(
 /// - `id`: 笔记本 ID
 ///
 /// # 返回
-/// - `Ok(())`: 删除成功
-/// - `Err`: 删除失败
+/// - `Ok(())`: 删除成功，笔记本不存在时不做任何操作
+/// - `Err`: ID 不大于 0，或删除失败
 ///
 /// # 注意
-/// 级联删除：事务中先删除关联的 note_tags，再删除 notes，最后删除 notebook
+/// 级联删除：子孙笔记本一并删除；事务中先删除关联的 note_tags、链接与历史，
+/// 再删除 notes，最后删除 notebook
 pub async fn delete_by_id(db: &DatabaseConnection, id: i64) -> anyhow::Result<()> {
+    // 0 表示顶级，其子树为全部笔记本
+    if id <= 0 {
+        return Err(AppError::code("NOTEBOOK_INVALID_ID").into());
+    }
+
     let txn = db.begin().await?;
 
-    // Find all note IDs belonging to this notebook
+    if entity::notebook::Entity::find_by_id(id)
+        .one(&txn)
+        .await?
+        .is_none()
+    {
+        return Ok(());
+    }
+
+    let notebook_ids = find_subtree_ids(&txn, id).await?;
+
+    // Find all note IDs belonging to these notebooks
     let note_ids: Vec<i64> = entity::note::Entity::find()
-        .filter(entity::note::Column::NotebookId.eq(id))
+        .select_only()
+        .column(entity::note::Column::Id)
+        .filter(entity::note::Column::NotebookId.is_in(notebook_ids.clone()))
+        .into_tuple::<i64>()
         .all(&txn)
-        .await?
-        .into_iter()
-        .map(|n| n.id)
-        .collect();
+        .await?;
 
     if !note_ids.is_empty() {
         // Delete note_tags for those notes
@@ -107,6 +177,9 @@ pub async fn delete_by_id(db: &DatabaseConnection, id: i64) -> anyhow::Result<()
             .exec(&txn)
             .await?;
 
+        // Delete links from and to those notes
+        note_link::delete_note_links(&txn, &note_ids).await?;
+
         // Delete note histories for those notes
         entity::note_history::Entity::delete_many()
             .filter(entity::note_history::Column::NoteId.is_in(note_ids))
@@ -114,14 +187,15 @@ pub async fn delete_by_id(db: &DatabaseConnection, id: i64) -> anyhow::Result<()
             .await?;
     }
 
-    // Delete all notes in this notebook
+    // Delete all notes in these notebooks
     entity::note::Entity::delete_many()
-        .filter(entity::note::Column::NotebookId.eq(id))
+        .filter(entity::note::Column::NotebookId.is_in(notebook_ids.clone()))
         .exec(&txn)
         .await?;
 
-    // Delete the notebook itself
-    entity::notebook::Entity::delete_by_id(id)
+    // Delete the notebook and its descendants
+    entity::notebook::Entity::delete_many()
+        .filter(entity::notebook::Column::Id.is_in(notebook_ids))
         .exec(&txn)
         .await?;
 
@@ -130,6 +204,44 @@ pub async fn delete_by_id(db: &DatabaseConnection, id: i64) -> anyhow::Result<()
     Ok(())
 }
 
+/// 移动笔记本（连同其子孙笔记本）到新的父笔记本下
+///
+/// # 参数
+/// - `db`: 数据库连接
+/// - `id`: 要移动的笔记本 ID
+/// - `parent_id`: 新的父笔记本 ID，0 表示移动到顶级
+///
+/// # 返回
+/// - `Ok(Some(Notebook))`: 移动成功，返回更新后的笔记本
+/// - `Ok(None)`: 笔记本不存在
+/// - `Err`: 父笔记本不存在，或目标为自身及其子孙（会形成环）
+pub async fn move_subtree(
+    db: &DatabaseConnection,
+    id: i64,
+    parent_id: i64,
+) -> anyhow::Result<Option<Notebook>> {
+    let txn = db.begin().await?;
+    let Some(entity) = entity::notebook::Entity::find_by_id(id).one(&txn).await? else {
+        return Ok(None);
+    };
+    if entity.parent_id == parent_id {
+        return Ok(Some(Notebook::from(entity)));
+    }
+
+    ensure_parent_exists(&txn, parent_id).await?;
+    if parent_id != 0 && find_subtree_ids(&txn, id).await?.contains(&parent_id) {
+        return Err(AppError::code("NOTEBOOK_MOVE_CYCLE").into());
+    }
+
+    let mut active_model: entity::notebook::ActiveModel = entity.into_active_model();
+    active_model.parent_id = Set(parent_id);
+    active_model.update_time = Set(Local::now().naive_local());
+    let updated = active_model.update(&txn).await?;
+    txn.commit().await?;
+
+    Ok(Some(Notebook::from(updated)))
+}
+
 /// 更新笔记本
 ///
 /// # 参数
@@ -144,6 +256,7 @@ pub async fn delete_by_id(db: &DatabaseConnection, id: i64) -> anyhow::Result<()
 /// # 智能更新
 /// - 使用 `set_if_not_equals` 仅更新有变化的字段
 /// - 只有实际发生变更时才更新 update_time
+/// - 不修改 `parent_id`，调整层级使用 [`move_subtree`]
 pub async fn update(
     db: &DatabaseConnection,
     notebook: &Notebook,
@@ -0,0 +1,215 @@
+//! 保存的搜索服务模块
+//!
+//! 本模块提供保存的搜索（智能笔记本）相关的业务逻辑实现。
+//!
+//! # 功能概述
+//! - 查询所有保存的搜索（按排序值和更新时间排序）
+//! - 创建、更新、删除保存的搜索
+//! - 执行保存的搜索（复用 `note::search_page_with_key`）
+//! - 统计各保存的搜索当前命中的笔记数量（`note::count_many`，一次查询），随 `note::stats` 返回
+
+use std::collections::HashMap;
+
+use chrono::Local;
+use sea_orm::{
+    ActiveModelTrait,
+    ActiveValue::{NotSet, Set},
+    DatabaseConnection, EntityTrait, IntoActiveModel, QueryOrder, TryIntoModel,
+};
+
+use crate::{
+    entity,
+    model::{Note, PageParam, PageResult, SavedSearch},
+    service,
+};
+
+/// 查询所有保存的搜索
+///
+/// # 返回
+/// - `Ok(Vec<SavedSearch>)`: 列表，按排序值降序、更新时间降序排列
+/// - `Err`: 查询失败
+pub async fn find_all(db: &DatabaseConnection) -> anyhow::Result<Vec<SavedSearch>> {
+    let saved_searches = entity::saved_search::Entity::find()
+        .order_by_desc(entity::saved_search::Column::SortOrder)
+        .order_by_desc(entity::saved_search::Column::UpdateTime)
+        .all(db)
+        .await?
+        .into_iter()
+        .map(SavedSearch::from)
+        .collect::<Vec<SavedSearch>>();
+
+    Ok(saved_searches)
+}
+
+/// 根据 ID 查询保存的搜索
+pub async fn find_by_id(db: &DatabaseConnection, id: i64) -> anyhow::Result<Option<SavedSearch>> {
+    Ok(entity::saved_search::Entity::find_by_id(id)
+        .one(db)
+        .await?
+        .map(SavedSearch::from))
+}
+
+/// 创建保存的搜索
+///
+/// # 返回
+/// - `Ok(Some(SavedSearch))`: 创建成功，返回新记录
+/// - `Err`: 创建失败
+pub async fn create(
+    db: &DatabaseConnection,
+    saved_search: &SavedSearch,
+) -> anyhow::Result<Option<SavedSearch>> {
+    let now = Local::now().naive_local();
+
+    let active_model = entity::saved_search::ActiveModel {
+        id: NotSet,
+        name: Set(saved_search.name.trim().to_string()),
+        icon: Set(saved_search.icon.clone()),
+        filter: Set(serde_json::to_string(&saved_search.filter)?),
+        sort_order: Set(saved_search.sort_order),
+        create_time: Set(now),
+        update_time: Set(now),
+    };
+
+    let entity = active_model.insert(db).await?;
+
+    Ok(Some(SavedSearch::from(entity)))
+}
+
+/// 更新保存的搜索
+///
+/// # 返回
+/// - `Ok(Some(SavedSearch))`: 更新成功，返回更新后的记录
+/// - `Ok(None)`: 记录不存在
+/// - `Err`: 更新失败
+pub async fn update(
+    db: &DatabaseConnection,
+    saved_search: &SavedSearch,
+) -> anyhow::Result<Option<SavedSearch>> {
+    if let Some(entity) = entity::saved_search::Entity::find_by_id(saved_search.id)
+        .one(db)
+        .await?
+    {
+        let mut active_model: entity::saved_search::ActiveModel = entity.into_active_model();
+
+        active_model
+            .name
+            .set_if_not_equals(saved_search.name.trim().to_string());
+        active_model
+            .icon
+            .set_if_not_equals(saved_search.icon.clone());
+        active_model
+            .filter
+            .set_if_not_equals(serde_json::to_string(&saved_search.filter)?);
+        active_model
+            .sort_order
+            .set_if_not_equals(saved_search.sort_order);
+
+        if active_model.is_changed() {
+            active_model.update_time = Set(Local::now().naive_local());
+            let entity = active_model.update(db).await?;
+            return Ok(Some(SavedSearch::from(entity)));
+        }
+
+        Ok(Some(SavedSearch::from(active_model.try_into_model()?)))
+    } else {
+        Ok(None)
+    }
+}
+
+/// 根据 ID 删除保存的搜索
+pub async fn delete_by_id(db: &DatabaseConnection, id: i64) -> anyhow::Result<()> {
+    entity::saved_search::Entity::delete_by_id(id)
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
+
+/// 执行保存的搜索
+///
+/// # 返回
+/// - `Ok(Some(PageResult<Note>))`: 搜索结果
+/// - `Ok(None)`: 保存的搜索不存在
+pub async fn run(
+    db: &DatabaseConnection,
+    id: i64,
+    page_param: PageParam,
+    encryption_key: Option<&str>,
+) -> anyhow::Result<Option<PageResult<Note>>> {
+    let Some(saved_search) = find_by_id(db, id).await? else {
+        return Ok(None);
+    };
+
+    let search_param = saved_search.filter.to_search_param(page_param);
+    let result = service::note::search_page_with_key(db, &search_param, encryption_key).await?;
+
+    Ok(Some(result))
+}
+
+/// 统计每个保存的搜索当前命中的笔记数量
+///
+/// 保存的条件校验失败（如 ID 数量超出上限）的保存的搜索计为 0，不影响其余统计
+///
+/// # 返回
+/// - `Ok(HashMap<i64, i64>)`: 保存的搜索 ID → 命中数量
+pub async fn counts(db: &DatabaseConnection) -> anyhow::Result<HashMap<i64, i64>> {
+    let mut result = HashMap::new();
+    let mut ids = Vec::new();
+    let mut search_params = Vec::new();
+    for saved_search in find_all(db).await? {
+        let search_param = saved_search.filter.to_search_param(PageParam::default());
+        if let Err(e) = search_param.validate() {
+            tracing::warn!(
+                "Saved search {} has an invalid filter: {}",
+                saved_search.id,
+                e
+            );
+            result.insert(saved_search.id, 0);
+            continue;
+        }
+        ids.push(saved_search.id);
+        search_params.push(search_param);
+    }
+    let totals = service::note::count_many(db, &search_params).await?;
+
+    result.extend(ids.into_iter().zip(totals));
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+    use crate::model::SavedSearchFilter;
+
+    #[test]
+    fn filter_appends_date_range_to_keyword() {
+        let filter = SavedSearchFilter {
+            keyword: " rust ".to_string(),
+            notebook_ids: vec![1, 2],
+            any_tag_ids: vec![3],
+            updated_after: NaiveDate::from_ymd_opt(2026, 1, 1),
+            updated_before: NaiveDate::from_ymd_opt(2026, 2, 1),
+            ..Default::default()
+        };
+        let param = filter.to_search_param(PageParam {
+            page_index: 2,
+            page_size: 20,
+        });
+        assert_eq!(param.keyword, "rust after:2026-01-01 before:2026-02-01");
+        assert_eq!(param.notebook_ids, vec![1, 2]);
+        assert_eq!(param.any_tag_ids, vec![3]);
+        assert_eq!(param.page_param.page_index, 2);
+    }
+
+    #[test]
+    fn filter_without_dates_keeps_keyword() {
+        let param = SavedSearchFilter {
+            keyword: "tag:work".to_string(),
+            ..Default::default()
+        }
+        .to_search_param(PageParam::default());
+        assert_eq!(param.keyword, "tag:work");
+    }
+}
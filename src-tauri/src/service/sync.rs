@@ -4,11 +4,15 @@
 //! 加密内容自动解密后重新加密。
 //! 笔记创建使用 `create_for_sync` 跳过历史生成，原始历史记录单独同步，避免重复。
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 
 use chrono::Local;
-use sea_orm::{DatabaseConnection, EntityTrait, PaginatorTrait, QuerySelect, TransactionTrait};
+use sea_orm::{
+    ActiveValue::{NotSet, Set},
+    ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder,
+    QuerySelect, TransactionTrait,
+};
 use tauri::{AppHandle, Emitter};
 use tracing::info;
 
@@ -16,15 +20,21 @@ use crate::{
     config::database_connection_from_profile,
     entity,
     i18n::t,
-    model::{DataStats, SyncLog, SyncMode, SyncOptions, SyncPreview, SyncProgress},
-    service::{backup, note, note_template, notebook, profile, settings, sync_log, tag},
+    model::{
+        DataStats, LinkOrigin, Notebook, SyncLog, SyncMode, SyncOptions, SyncPreview, SyncProgress,
+        Tag,
+    },
+    service::history_storage::{self, HistoryDecoder},
+    service::{
+        backup, hierarchy, note, note_link, note_template, notebook, profile, settings, sync_log,
+        tag,
+    },
 };
 
 /// ID 映射表，用于修复外键关系
 struct IdMaps {
     notebook: HashMap<i64, i64>,
     tag: HashMap<i64, i64>,
-    #[allow(dead_code)]
     note: HashMap<i64, i64>,
 }
 
@@ -38,6 +48,39 @@ impl IdMaps {
     }
 }
 
+/// 将两端笔记均已同步的手动链接复制到目标库
+async fn copy_manual_links(
+    source_db: &DatabaseConnection,
+    target_db: &DatabaseConnection,
+    note_ids: &HashMap<i64, i64>,
+) -> anyhow::Result<()> {
+    let links: Vec<entity::note_link::ActiveModel> = entity::note_link::Entity::find()
+        .filter(entity::note_link::Column::Origin.eq(LinkOrigin::Manual.as_i32()))
+        .all(source_db)
+        .await?
+        .into_iter()
+        .filter_map(|link| {
+            let source = *note_ids.get(&link.source_note_id)?;
+            let target = *note_ids.get(&link.target_note_id)?;
+            Some(entity::note_link::ActiveModel {
+                id: NotSet,
+                source_note_id: Set(source),
+                target_note_id: Set(target),
+                create_time: Set(link.create_time),
+                origin: Set(link.origin),
+                relation: Set(link.relation),
+            })
+        })
+        .collect();
+    // 分批插入，避免单条语句参数过多
+    for chunk in links.chunks(200) {
+        entity::note_link::Entity::insert_many(chunk.to_vec())
+            .exec(target_db)
+            .await?;
+    }
+    Ok(())
+}
+
 /// 发送进度事件到前端
 fn emit_progress(
     app_handle: &AppHandle,
@@ -233,6 +276,7 @@ pub async fn sync_to_profile(
         let txn = target_db.begin().await?;
         // 按外键反序清空
         entity::note_tags::Entity::delete_many().exec(&txn).await?;
+        entity::note_link::Entity::delete_many().exec(&txn).await?;
         entity::note_history::Entity::delete_many()
             .exec(&txn)
             .await?;
@@ -272,9 +316,17 @@ pub async fn sync_to_profile(
         let source_notebooks = notebook::find_all(source_db).await?;
         let count = source_notebooks.len() as u32;
 
-        // 按 parent_id 排序确保父级先插入（parent_id=0 的排前面）
-        let mut sorted_notebooks = source_notebooks;
-        sorted_notebooks.sort_by_key(|nb| nb.parent_id);
+        // 按层级排序确保父级先插入，子笔记本的 parent_id 可映射到目标库中的新 ID
+        let pairs: Vec<(i64, i64)> = source_notebooks
+            .iter()
+            .map(|nb| (nb.id, nb.parent_id))
+            .collect();
+        let mut by_id: HashMap<i64, Notebook> =
+            source_notebooks.into_iter().map(|nb| (nb.id, nb)).collect();
+        let sorted_notebooks: Vec<Notebook> = hierarchy::parents_first(&pairs)
+            .into_iter()
+            .filter_map(|id| by_id.remove(&id))
+            .collect();
 
         for (i, nb) in sorted_notebooks.iter().enumerate() {
             let source_id = nb.id;
@@ -342,14 +394,23 @@ pub async fn sync_to_profile(
         let source_tags = tag::find_all(source_db).await?;
         let count = source_tags.len() as u32;
 
-        for (i, t) in source_tags.iter().enumerate() {
+        // 按层级排序确保父级先插入，子标签的 parent_id 可映射到目标库中的新 ID
+        let pairs: Vec<(i64, i64)> = source_tags.iter().map(|t| (t.id, t.parent_id)).collect();
+        let mut by_id: HashMap<i64, Tag> = source_tags.into_iter().map(|t| (t.id, t)).collect();
+        let sorted_tags: Vec<Tag> = hierarchy::parents_first(&pairs)
+            .into_iter()
+            .filter_map(|id| by_id.remove(&id))
+            .collect();
+
+        for (i, t) in sorted_tags.iter().enumerate() {
             let source_id = t.id;
             let synced_at = Local::now().naive_local();
 
             let mut new_tag = t.clone();
             new_tag.id = 0;
+            new_tag.parent_id = id_maps.tag.get(&t.parent_id).copied().unwrap_or(0);
 
-            match tag::create(&target_db, &new_tag).await {
+            match tag::create_as_is(&target_db, &new_tag).await {
                 Ok(Some(created)) => {
                     id_maps.tag.insert(source_id, created.id);
                     sync_log::add_detail(
@@ -545,6 +606,11 @@ pub async fn sync_to_profile(
                 );
             }
         } // end batch loop
+
+        // 引用可能指向之后才同步的笔记，全部写入后重新同步内容链接，并按新 ID 复制手动链接
+        let created_ids: Vec<i64> = id_maps.note.values().copied().collect();
+        note_link::resync_content_links(&target_db, &created_ids, target_encryption_key).await?;
+        copy_manual_links(source_db, &target_db, &id_maps.note).await?;
     }
 
     // 4d. 同步笔记历史（可选：由于 create_with_key 已自动生成了同步的历史记录，
@@ -554,17 +620,23 @@ pub async fn sync_to_profile(
             .count(source_db)
             .await? as u32;
         let mut global_hist_idx: u32 = 0;
+        // 源端历史按 (笔记, ID) 顺序读取并还原为完整文本，写入目标端后再重新编码
+        let mut decoder = HistoryDecoder::default();
+        let mut target_note_ids: HashSet<i64> = HashSet::new();
 
         let total_hist_batches = (count as u64).div_ceil(SYNC_BATCH_SIZE);
         for batch_idx in 0..total_hist_batches {
             let source_histories: Vec<entity::note_history::Model> =
                 entity::note_history::Entity::find()
+                    .order_by_asc(entity::note_history::Column::NoteId)
+                    .order_by_asc(entity::note_history::Column::Id)
                     .offset(batch_idx * SYNC_BATCH_SIZE)
                     .limit(SYNC_BATCH_SIZE)
                     .all(source_db)
                     .await?;
 
-            for h in source_histories.iter() {
+            for h in source_histories {
+                let h = decoder.decode(source_db, h).await?;
                 let i = global_hist_idx as usize;
                 global_hist_idx += 1;
                 let source_id = h.id;
@@ -582,11 +654,14 @@ pub async fn sync_to_profile(
                     operate_source: Set(h.operate_source),
                     operate_time: Set(h.operate_time),
                     create_time: Set(h.create_time),
+                    content_format: Set(0),
+                    base_id: Set(0),
                 };
 
                 use sea_orm::ActiveModelTrait;
                 match active_model.insert(&target_db).await {
                     Ok(inserted) => {
+                        target_note_ids.insert(note_id);
                         let name = format!("note_id={} type={}", h.note_id, h.operate_type);
                         sync_log::add_detail(
                             source_db,
@@ -633,6 +708,9 @@ pub async fn sync_to_profile(
                 }
             }
         } // end history batch loop
+
+        let target_note_ids: Vec<i64> = target_note_ids.into_iter().collect();
+        history_storage::repack(&target_db, Some(&target_note_ids)).await?;
     }
 
     // 4e. 同步模板
@@ -708,7 +786,9 @@ pub async fn sync_to_profile(
 
     // 4f. 同步设置
     if options.scope.settings {
-        let source_settings = settings::get_all(source_db).await?;
+        let mut source_settings = settings::get_all(source_db).await?;
+        // 内容链接回填的进度属于各自的数据库
+        source_settings.remove(note_link::BACKFILL_DONE_KEY);
         if !source_settings.is_empty() {
             let synced_at = Local::now().naive_local();
             let count = source_settings.len();
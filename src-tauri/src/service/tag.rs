@@ -3,20 +3,146 @@
 //! 本模块提供标签相关的业务逻辑实现。
 //!
 //! # 功能概述
-//! - 查询所有标签（按排序值和更新时间排序）
-//! - 创建新标签
+//! - 查询所有标签（按排序值和更新时间排序），或按层级返回标签树
+//! - 创建新标签（名称为 `a/b/c` 形式的路径时自动创建缺失的祖先标签）
 //! - 更新标签（智能检测变更）
-//! - 删除标签
+//! - 移动标签（连同子孙标签，禁止移动到自身或子孙之下）
+//! - 删除标签（子标签上移到被删除标签的父级）
+//! - 合并标签，批量为笔记添加或移除标签（每条变化的笔记记录历史）
+//!
+//! # 层级
+//! `parent_id` 为 0 表示顶级；父标签不存在时按顶级处理。
+
+use std::collections::{HashMap, HashSet};
 
 use chrono::Local;
 use sea_orm::{
     ActiveModelTrait,
     ActiveValue::{NotSet, Set},
-    ColumnTrait, DatabaseConnection, EntityTrait, IntoActiveModel, QueryFilter, QueryOrder,
-    TransactionTrait,
+    ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, IntoActiveModel, QueryFilter,
+    QueryOrder, QuerySelect, TransactionTrait,
+    prelude::Expr,
+};
+
+use crate::{
+    entity,
+    error::AppError,
+    model::{OperateSource, Tag, TagTreeNode},
+    service::{crypto, hierarchy, note},
 };
 
-use crate::{entity, model::Tag};
+/// 标签路径分隔符
+const PATH_SEPARATOR: char = '/';
+
+/// 拆分标签路径：按 `/` 分段并去除首尾空白，忽略空段
+fn path_segments(path: &str) -> Vec<&str> {
+    path.split(PATH_SEPARATOR)
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// 计算每个标签的完整路径（如 `project/alpha/backend`）
+///
+/// 父标签不存在时从该标签开始；父子关系成环时在回到已访问标签处截止
+pub fn tag_paths(tags: &[Tag]) -> HashMap<i64, String> {
+    let by_id: HashMap<i64, &Tag> = tags.iter().map(|t| (t.id, t)).collect();
+    tags.iter()
+        .map(|t| {
+            let mut names = vec![t.name.as_str()];
+            let mut visited = HashSet::from([t.id]);
+            let mut parent_id = t.parent_id;
+            while let Some(parent) = by_id.get(&parent_id) {
+                if !visited.insert(parent.id) {
+                    break;
+                }
+                names.push(parent.name.as_str());
+                parent_id = parent.parent_id;
+            }
+            names.reverse();
+            (t.id, names.join("/"))
+        })
+        .collect()
+}
+
+/// 所有标签的 (id, parent_id)
+pub async fn hierarchy_pairs<C: ConnectionTrait>(db: &C) -> anyhow::Result<Vec<(i64, i64)>> {
+    Ok(entity::tag::Entity::find()
+        .select_only()
+        .column(entity::tag::Column::Id)
+        .column(entity::tag::Column::ParentId)
+        .into_tuple::<(i64, i64)>()
+        .all(db)
+        .await?)
+}
+
+/// 检查父标签存在（0 表示顶级，总是有效）
+async fn ensure_parent_exists<C: ConnectionTrait>(db: &C, parent_id: i64) -> anyhow::Result<()> {
+    if parent_id != 0
+        && entity::tag::Entity::find_by_id(parent_id)
+            .one(db)
+            .await?
+            .is_none()
+    {
+        return Err(AppError::code("TAG_PARENT_NOT_FOUND").into());
+    }
+    Ok(())
+}
+
+/// 插入一个标签，名称与父级单独指定，其余字段取自 `tag`
+async fn insert<C: ConnectionTrait>(
+    db: &C,
+    tag: &Tag,
+    parent_id: i64,
+    name: &str,
+) -> anyhow::Result<entity::tag::Model> {
+    let now = Local::now().naive_local();
+
+    let active_model = entity::tag::ActiveModel {
+        id: NotSet,
+        parent_id: Set(parent_id),
+        name: Set(name.to_string()),
+        icon: Set(tag.icon.clone()),
+        cls: Set(tag.cls.clone()),
+        sort_order: Set(tag.sort_order),
+        mcp_access: Set(tag.mcp_access),
+        create_time: Set(now),
+        update_time: Set(now),
+    };
+
+    Ok(active_model.insert(db).await?)
+}
+
+/// 按路径逐级查找或创建标签，返回路径末端的标签
+///
+/// 每一级在当前父级下查找同名标签（同名多个时取排序最靠前的），不存在则创建；
+/// 自动创建的祖先标签只有名称，末端标签使用 `tag` 的图标、样式、排序与访问控制
+async fn find_or_create_path<C: ConnectionTrait>(
+    db: &C,
+    tag: &Tag,
+    segments: &[&str],
+) -> anyhow::Result<entity::tag::Model> {
+    let ancestor = Tag::default();
+    let mut parent_id = tag.parent_id;
+    let mut current = None;
+    for (i, name) in segments.iter().enumerate() {
+        let existing = entity::tag::Entity::find()
+            .filter(entity::tag::Column::ParentId.eq(parent_id))
+            .filter(entity::tag::Column::Name.eq(*name))
+            .order_by_desc(entity::tag::Column::SortOrder)
+            .order_by_asc(entity::tag::Column::Id)
+            .one(db)
+            .await?;
+        let model = match existing {
+            Some(m) => m,
+            None if i + 1 == segments.len() => insert(db, tag, parent_id, name).await?,
+            None => insert(db, &ancestor, parent_id, name).await?,
+        };
+        parent_id = model.id;
+        current = Some(model);
+    }
+    current.ok_or_else(|| AppError::code("TAG_PATH_INVALID").into())
+}
 
 /// 查询所有标签
 ///
@@ -39,6 +165,25 @@ pub async fn find_all(db: &DatabaseConnection) -> anyhow::Result<Vec<Tag>> {
     Ok(tags)
 }
 
+/// 查询标签树
+///
+/// # 返回
+/// - `Ok(Vec<TagTreeNode>)`: 顶级标签列表，子标签位于各节点的 `children` 中，
+///   同级按排序值降序、更新时间降序排列
+/// - `Err`: 查询失败
+pub async fn find_tree(db: &DatabaseConnection) -> anyhow::Result<Vec<TagTreeNode>> {
+    Ok(hierarchy::build_tree(
+        find_all(db).await?,
+        |t| (t.id, t.parent_id),
+        |tag, children| TagTreeNode { tag, children },
+    ))
+}
+
+/// 指定标签及其全部子孙标签的 ID（含自身）
+pub async fn find_subtree_ids<C: ConnectionTrait>(db: &C, id: i64) -> anyhow::Result<Vec<i64>> {
+    Ok(hierarchy::subtree_ids(&hierarchy_pairs(db).await?, id))
+}
+
 /// 创建标签
 ///
 /// # 参数
@@ -46,31 +191,41 @@ pub async fn find_all(db: &DatabaseConnection) -> anyhow::Result<Vec<Tag>> {
 /// - `tag`: 标签数据
 ///
 /// # 返回
-/// - `Ok(Some(Tag))`: 创建成功，返回新标签
-/// - `Err`: 创建失败
+/// - `Ok(Some(Tag))`: 创建成功，返回新标签（按路径创建且路径已存在时返回已有标签）
+/// - `Err`: 父标签不存在，或路径中没有有效名称
 ///
 /// # 说明
 /// - 自动设置创建时间和更新时间为当前时间
 /// - ID 由数据库自动生成
+/// - `parent_id` 不为 0 时父标签必须存在
+/// - 名称包含 `/` 时视为相对 `parent_id` 的路径，如 `project/alpha/backend`，
+///   逐级复用已有的同名标签，缺失的祖先标签自动创建
 pub async fn create(db: &DatabaseConnection, tag: &Tag) -> anyhow::Result<Option<Tag>> {
-    let now = Local::now().naive_local();
+    let txn = db.begin().await?;
+    ensure_parent_exists(&txn, tag.parent_id).await?;
 
-    let active_model = entity::tag::ActiveModel {
-        id: NotSet,
-        name: Set(tag.name.clone()),
-        icon: Set(tag.icon.clone()),
-        cls: Set(tag.cls.clone()),
-        sort_order: Set(tag.sort_order),
-        mcp_access: Set(tag.mcp_access),
-        create_time: Set(now),
-        update_time: Set(now),
+    let entity = if tag.name.contains(PATH_SEPARATOR) {
+        let segments = path_segments(&tag.name);
+        find_or_create_path(&txn, tag, &segments).await?
+    } else {
+        insert(&txn, tag, tag.parent_id, &tag.name).await?
     };
 
-    let entity = active_model.insert(db).await?;
+    txn.commit().await?;
 
     Ok(Some(Tag::from(entity)))
 }
 
+/// 按原样创建标签：名称中的 `/` 不作为路径解析
+///
+/// 用于同步等复制已有标签的场景，父标签需由调用方先行创建；
+/// 父标签不存在时返回错误
+pub async fn create_as_is(db: &DatabaseConnection, tag: &Tag) -> anyhow::Result<Option<Tag>> {
+    ensure_parent_exists(db, tag.parent_id).await?;
+    let entity = insert(db, tag, tag.parent_id, &tag.name).await?;
+    Ok(Some(Tag::from(entity)))
+}
+
 /// 根据 ID 删除标签
 ///
 /// # 参数
@@ -82,10 +237,22 @@ pub async fn create(db: &DatabaseConnection, tag: &Tag) -> anyhow::Result<Option
 /// - `Err`: 删除失败
 ///
 /// # 注意
-/// 级联删除：事务中先删除 note_tags 关联行，再删除 tag
+/// 级联删除：事务中先删除 note_tags 关联行，再删除 tag；
+/// 子标签不会被删除，而是上移到被删除标签的父级下
 pub async fn delete_by_id(db: &DatabaseConnection, id: i64) -> anyhow::Result<()> {
     let txn = db.begin().await?;
 
+    let Some(entity) = entity::tag::Entity::find_by_id(id).one(&txn).await? else {
+        return Ok(());
+    };
+
+    // Move child tags up to the deleted tag's parent
+    entity::tag::Entity::update_many()
+        .col_expr(entity::tag::Column::ParentId, Expr::value(entity.parent_id))
+        .filter(entity::tag::Column::ParentId.eq(id))
+        .exec(&txn)
+        .await?;
+
     // Delete all note_tags associations for this tag
     entity::note_tags::Entity::delete_many()
         .filter(entity::note_tags::Column::TagId.eq(id))
@@ -100,6 +267,44 @@ pub async fn delete_by_id(db: &DatabaseConnection, id: i64) -> anyhow::Result<()
     Ok(())
 }
 
+/// 移动标签（连同其子孙标签）到新的父标签下
+///
+/// # 参数
+/// - `db`: 数据库连接
+/// - `id`: 要移动的标签 ID
+/// - `parent_id`: 新的父标签 ID，0 表示移动到顶级
+///
+/// # 返回
+/// - `Ok(Some(Tag))`: 移动成功，返回更新后的标签
+/// - `Ok(None)`: 标签不存在
+/// - `Err`: 父标签不存在，或目标为自身及其子孙（会形成环）
+pub async fn move_subtree(
+    db: &DatabaseConnection,
+    id: i64,
+    parent_id: i64,
+) -> anyhow::Result<Option<Tag>> {
+    let txn = db.begin().await?;
+    let Some(entity) = entity::tag::Entity::find_by_id(id).one(&txn).await? else {
+        return Ok(None);
+    };
+    if entity.parent_id == parent_id {
+        return Ok(Some(Tag::from(entity)));
+    }
+
+    ensure_parent_exists(&txn, parent_id).await?;
+    if parent_id != 0 && find_subtree_ids(&txn, id).await?.contains(&parent_id) {
+        return Err(AppError::code("TAG_MOVE_CYCLE").into());
+    }
+
+    let mut active_model: entity::tag::ActiveModel = entity.into_active_model();
+    active_model.parent_id = Set(parent_id);
+    active_model.update_time = Set(Local::now().naive_local());
+    let updated = active_model.update(&txn).await?;
+    txn.commit().await?;
+
+    Ok(Some(Tag::from(updated)))
+}
+
 /// 更新标签
 ///
 /// # 参数
@@ -114,6 +319,7 @@ pub async fn delete_by_id(db: &DatabaseConnection, id: i64) -> anyhow::Result<()
 /// # 智能更新
 /// - 使用 `set_if_not_equals` 仅更新有变化的字段
 /// - 只有实际发生变更时才更新 update_time
+/// - 不修改 `parent_id`，调整层级使用 [`move_subtree`]
 pub async fn update(db: &DatabaseConnection, tag: &Tag) -> anyhow::Result<Option<Tag>> {
     if let Some(entity) = entity::tag::Entity::find_by_id(tag.id).one(db).await? {
         let mut m = tag.clone();
@@ -174,3 +380,396 @@ pub async fn reorder(db: &DatabaseConnection, orders: Vec<(i64, i32)>) -> anyhow
     txn.commit().await?;
     Ok(())
 }
+
+/// 检查标签存在并返回
+async fn require_tag<C: ConnectionTrait>(db: &C, id: i64) -> anyhow::Result<entity::tag::Model> {
+    entity::tag::Entity::find_by_id(id)
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::code("TAG_NOT_FOUND").into())
+}
+
+/// 按笔记分组的标签列表（每组按关联排序值降序，与笔记详情中的标签顺序一致）
+async fn tags_by_note<C: ConnectionTrait>(
+    db: &C,
+    note_ids: &[i64],
+) -> anyhow::Result<HashMap<i64, Vec<Tag>>> {
+    let mut result: HashMap<i64, Vec<Tag>> = HashMap::new();
+    let rows = entity::note_tags::Entity::find()
+        .filter(entity::note_tags::Column::NoteId.is_in(note_ids.to_vec()))
+        .find_also_related(entity::tag::Entity)
+        .order_by_desc(entity::note_tags::Column::SortOrder)
+        .all(db)
+        .await?;
+    for (nt, tag) in rows {
+        if let Some(tag) = tag {
+            result.entry(nt.note_id).or_default().push(Tag::from(tag));
+        }
+    }
+    Ok(result)
+}
+
+/// 为笔记逐条记录标签变更历史（`old_tags` 为变更前按笔记分组的标签）
+///
+/// 历史记录保存明文内容，加密笔记无法解密时保存原文
+async fn record_tags_changes<C: ConnectionTrait>(
+    db: &C,
+    notes: &[entity::note::Model],
+    mut old_tags: HashMap<i64, Vec<Tag>>,
+    source: OperateSource,
+    encryption_key: Option<&str>,
+) -> anyhow::Result<()> {
+    for n in notes {
+        let content = match encryption_key {
+            Some(key) if crypto::is_encrypted(&n.content) => {
+                crypto::decrypt(&n.content, key).unwrap_or_else(|_| n.content.clone())
+            }
+            _ => n.content.clone(),
+        };
+        let tags = old_tags.remove(&n.id).unwrap_or_default();
+        note::record_tags_change(db, n, &content, tags, source).await?;
+    }
+    Ok(())
+}
+
+/// 带有任一指定标签的笔记 ID（含回收站中的笔记）
+pub async fn find_tagged_note_ids<C: ConnectionTrait>(
+    db: &C,
+    tag_ids: &[i64],
+) -> anyhow::Result<Vec<i64>> {
+    Ok(entity::note_tags::Entity::find()
+        .select_only()
+        .column(entity::note_tags::Column::NoteId)
+        .distinct()
+        .filter(entity::note_tags::Column::TagId.is_in(tag_ids.to_vec()))
+        .into_tuple::<i64>()
+        .all(db)
+        .await?)
+}
+
+/// 合并标签时，源标签下子标签的新父级 (id, parent_id)
+///
+/// 子标签移到目标标签下；目标标签自身及其祖先若位于源标签下，
+/// 则改挂到最近的未被合并的祖先下（没有时为顶级），避免形成环
+fn merge_reparent(
+    pairs: &[(i64, i64)],
+    source_ids: &HashSet<i64>,
+    target_id: i64,
+) -> Vec<(i64, i64)> {
+    let parents: HashMap<i64, i64> = pairs.iter().copied().collect();
+
+    // 目标标签及其全部祖先
+    let mut target_line = HashSet::new();
+    let mut current = target_id;
+    while parents.contains_key(&current) && target_line.insert(current) {
+        current = parents[&current];
+    }
+
+    pairs
+        .iter()
+        .filter(|(id, parent_id)| source_ids.contains(parent_id) && !source_ids.contains(id))
+        .map(|(id, parent_id)| {
+            if !target_line.contains(id) {
+                return (*id, target_id);
+            }
+            let mut visited = HashSet::new();
+            let mut ancestor = *parent_id;
+            while source_ids.contains(&ancestor) && visited.insert(ancestor) {
+                ancestor = parents.get(&ancestor).copied().unwrap_or(0);
+            }
+            let valid = !source_ids.contains(&ancestor)
+                && ancestor != *id
+                && parents.contains_key(&ancestor);
+            (*id, if valid { ancestor } else { 0 })
+        })
+        .collect()
+}
+
+/// 合并时受影响的笔记超出允许范围的错误码（参数为超出的笔记 ID）
+const MERGE_NOTES_CHANGED: &str = "TAG_MERGE_NOTES_CHANGED";
+
+/// 若错误为合并时受影响的笔记超出允许范围，返回超出的笔记 ID
+pub fn merge_unexpected_notes(err: &anyhow::Error) -> Option<Vec<i64>> {
+    match err.downcast_ref::<AppError>()? {
+        AppError::BusinessCode { code, args } if code == MERGE_NOTES_CHANGED => {
+            Some(args.iter().filter_map(|id| id.parse().ok()).collect())
+        }
+        _ => None,
+    }
+}
+
+/// 合并标签：将源标签改写为目标标签后删除源标签
+///
+/// # 参数
+/// - `db`: 数据库连接
+/// - `source_ids`: 要合并掉的标签 ID（包含目标标签时忽略该项）
+/// - `target_id`: 保留的目标标签 ID
+/// - `allowed_note_ids`: 允许改写的笔记；为 Some 时在事务中确认受影响的笔记全部在其中
+/// - `source`: 操作来源（写入历史记录）
+/// - `encryption_key`: 加密密钥（历史记录保存明文内容）
+///
+/// # 返回
+/// - `Ok(u64)`: 标签发生变化的笔记数量
+/// - `Err`: 目标标签不存在，或存在不在 `allowed_note_ids` 中的受影响笔记（不做任何修改）
+///
+/// # 说明
+/// - 笔记已有目标标签时只删除源标签关联，否则将排序最靠前的源标签关联改为目标标签，
+///   保留其排序值，不产生重复关联
+/// - 每条受影响的笔记（含回收站中的笔记）记录一条历史，保存合并前的标签
+/// - 源标签的子标签移到目标标签下
+pub async fn merge_tags(
+    db: &DatabaseConnection,
+    source_ids: &[i64],
+    target_id: i64,
+    allowed_note_ids: Option<&[i64]>,
+    source: OperateSource,
+    encryption_key: Option<&str>,
+) -> anyhow::Result<u64> {
+    let txn = db.begin().await?;
+    require_tag(&txn, target_id).await?;
+
+    let source_set: HashSet<i64> = source_ids
+        .iter()
+        .copied()
+        .filter(|id| *id != target_id)
+        .collect();
+    if source_set.is_empty() {
+        return Ok(0);
+    }
+    let source_list: Vec<i64> = source_set.iter().copied().collect();
+
+    let note_ids = find_tagged_note_ids(&txn, &source_list).await?;
+    if let Some(allowed) = allowed_note_ids {
+        let allowed: HashSet<i64> = allowed.iter().copied().collect();
+        let denied: Vec<String> = note_ids
+            .iter()
+            .filter(|id| !allowed.contains(id))
+            .map(i64::to_string)
+            .collect();
+        if !denied.is_empty() {
+            return Err(AppError::code_with_args(MERGE_NOTES_CHANGED, denied).into());
+        }
+    }
+    let old_tags = tags_by_note(&txn, &note_ids).await?;
+
+    // 涉及的关联行，按笔记分组，组内按排序值降序
+    let mut involved = source_list.clone();
+    involved.push(target_id);
+    let mut rows_by_note: HashMap<i64, Vec<entity::note_tags::Model>> = HashMap::new();
+    for row in entity::note_tags::Entity::find()
+        .filter(entity::note_tags::Column::NoteId.is_in(note_ids.clone()))
+        .filter(entity::note_tags::Column::TagId.is_in(involved))
+        .order_by_desc(entity::note_tags::Column::SortOrder)
+        .order_by_asc(entity::note_tags::Column::Id)
+        .all(&txn)
+        .await?
+    {
+        rows_by_note.entry(row.note_id).or_default().push(row);
+    }
+
+    let now = Local::now().naive_local();
+    let mut to_delete = Vec::new();
+    for rows in rows_by_note.into_values() {
+        let has_target = rows.iter().any(|r| r.tag_id == target_id);
+        let mut sources = rows.into_iter().filter(|r| r.tag_id != target_id);
+        if !has_target && let Some(row) = sources.next() {
+            let mut active_model: entity::note_tags::ActiveModel = row.into_active_model();
+            active_model.tag_id = Set(target_id);
+            active_model.update_time = Set(now);
+            active_model.update(&txn).await?;
+        }
+        to_delete.extend(sources.map(|r| r.id));
+    }
+    if !to_delete.is_empty() {
+        entity::note_tags::Entity::delete_many()
+            .filter(entity::note_tags::Column::Id.is_in(to_delete))
+            .exec(&txn)
+            .await?;
+    }
+
+    let notes = entity::note::Entity::find()
+        .filter(entity::note::Column::Id.is_in(note_ids))
+        .all(&txn)
+        .await?;
+    record_tags_changes(&txn, &notes, old_tags, source, encryption_key).await?;
+
+    // 源标签的子标签改挂到新父级，再删除源标签
+    for (id, parent_id) in merge_reparent(&hierarchy_pairs(&txn).await?, &source_set, target_id) {
+        entity::tag::Entity::update_many()
+            .col_expr(entity::tag::Column::ParentId, Expr::value(parent_id))
+            .filter(entity::tag::Column::Id.eq(id))
+            .exec(&txn)
+            .await?;
+    }
+    entity::tag::Entity::delete_many()
+        .filter(entity::tag::Column::Id.is_in(source_list))
+        .exec(&txn)
+        .await?;
+
+    txn.commit().await?;
+
+    Ok(notes.len() as u64)
+}
+
+/// 批量为笔记添加标签
+///
+/// # 返回
+/// - `Ok(u64)`: 新增标签的笔记数量（已带有该标签或在回收站中的笔记不计入）
+/// - `Err`: 标签不存在
+///
+/// # 说明
+/// 新关联的排序值取标签自身的排序值；每条变化的笔记记录一条历史（`encryption_key` 用于保存明文内容）
+pub async fn add_tag_to_notes(
+    db: &DatabaseConnection,
+    note_ids: &[i64],
+    tag_id: i64,
+    source: OperateSource,
+    encryption_key: Option<&str>,
+) -> anyhow::Result<u64> {
+    let txn = db.begin().await?;
+    let tag = require_tag(&txn, tag_id).await?;
+
+    let tagged: HashSet<i64> = find_tagged_note_ids(&txn, &[tag_id])
+        .await?
+        .into_iter()
+        .collect();
+    let notes: Vec<entity::note::Model> = entity::note::Entity::find()
+        .filter(entity::note::Column::Id.is_in(note_ids.to_vec()))
+        .filter(entity::note::Column::DeletedAt.is_null())
+        .all(&txn)
+        .await?
+        .into_iter()
+        .filter(|n| !tagged.contains(&n.id))
+        .collect();
+    if notes.is_empty() {
+        return Ok(0);
+    }
+
+    let changed: Vec<i64> = notes.iter().map(|n| n.id).collect();
+    let old_tags = tags_by_note(&txn, &changed).await?;
+
+    let now = Local::now().naive_local();
+    let models = changed
+        .iter()
+        .map(|note_id| entity::note_tags::ActiveModel {
+            id: NotSet,
+            note_id: Set(*note_id),
+            tag_id: Set(tag_id),
+            sort_order: Set(tag.sort_order),
+            create_time: Set(now),
+            update_time: Set(now),
+        });
+    entity::note_tags::Entity::insert_many(models)
+        .exec(&txn)
+        .await?;
+
+    record_tags_changes(&txn, &notes, old_tags, source, encryption_key).await?;
+    txn.commit().await?;
+
+    Ok(notes.len() as u64)
+}
+
+/// 批量从笔记移除标签
+///
+/// # 返回
+/// - `Ok(u64)`: 移除了该标签的笔记数量（未带有该标签或在回收站中的笔记不计入）
+/// - `Err`: 标签不存在
+///
+/// # 说明
+/// 每条变化的笔记记录一条历史（`encryption_key` 用于保存明文内容）
+pub async fn remove_tag_from_notes(
+    db: &DatabaseConnection,
+    note_ids: &[i64],
+    tag_id: i64,
+    source: OperateSource,
+    encryption_key: Option<&str>,
+) -> anyhow::Result<u64> {
+    let txn = db.begin().await?;
+    require_tag(&txn, tag_id).await?;
+
+    let tagged: HashSet<i64> = find_tagged_note_ids(&txn, &[tag_id])
+        .await?
+        .into_iter()
+        .collect();
+    let notes: Vec<entity::note::Model> = entity::note::Entity::find()
+        .filter(entity::note::Column::Id.is_in(note_ids.to_vec()))
+        .filter(entity::note::Column::DeletedAt.is_null())
+        .all(&txn)
+        .await?
+        .into_iter()
+        .filter(|n| tagged.contains(&n.id))
+        .collect();
+    if notes.is_empty() {
+        return Ok(0);
+    }
+
+    let changed: Vec<i64> = notes.iter().map(|n| n.id).collect();
+    let old_tags = tags_by_note(&txn, &changed).await?;
+
+    entity::note_tags::Entity::delete_many()
+        .filter(entity::note_tags::Column::TagId.eq(tag_id))
+        .filter(entity::note_tags::Column::NoteId.is_in(changed))
+        .exec(&txn)
+        .await?;
+
+    record_tags_changes(&txn, &notes, old_tags, source, encryption_key).await?;
+    txn.commit().await?;
+
+    Ok(notes.len() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tag(id: i64, parent_id: i64, name: &str) -> Tag {
+        Tag {
+            id,
+            parent_id,
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn splits_path_segments() {
+        assert_eq!(
+            path_segments("project/alpha/backend"),
+            vec!["project", "alpha", "backend"]
+        );
+        assert_eq!(path_segments(" a // b /"), vec!["a", "b"]);
+        assert!(path_segments(" / ").is_empty());
+    }
+
+    #[test]
+    fn builds_full_paths() {
+        let tags = vec![
+            tag(1, 0, "project"),
+            tag(2, 1, "alpha"),
+            tag(3, 2, "backend"),
+            tag(4, 99, "orphan"),
+            tag(5, 6, "x"),
+            tag(6, 5, "y"),
+        ];
+        let paths = tag_paths(&tags);
+        assert_eq!(paths[&3], "project/alpha/backend");
+        assert_eq!(paths[&1], "project");
+        assert_eq!(paths[&4], "orphan");
+        assert_eq!(paths[&5], "y/x");
+    }
+
+    #[test]
+    fn reparents_children_of_merged_tags() {
+        // 1 ─ 2 ─ 3, 2 ─ 4 ─ 5; 合并 2 到 5：3 移到 5 下，4 是 5 的祖先，改挂到 1
+        let pairs = [(1, 0), (2, 1), (3, 2), (4, 2), (5, 4)];
+        let mut moves = merge_reparent(&pairs, &HashSet::from([2]), 5);
+        moves.sort();
+        assert_eq!(moves, vec![(3, 5), (4, 1)]);
+
+        // 源标签为顶级时，目标的祖先改为顶级
+        let pairs = [(1, 0), (2, 1), (3, 1)];
+        let mut moves = merge_reparent(&pairs, &HashSet::from([1]), 2);
+        moves.sort();
+        assert_eq!(moves, vec![(2, 0), (3, 2)]);
+    }
+}
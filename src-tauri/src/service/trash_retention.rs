@@ -0,0 +1,151 @@
+//! 回收站保留策略服务
+//!
+//! 软删除的笔记默认一直留在回收站中，直到手动清空。本模块按设置中的保留天数
+//! （`trashRetentionDays`，0 表示永久保留）找出在回收站中超过保留期的笔记并彻底删除，
+//! 与手动永久删除一样写入删除历史，并清理标签关联、链接与附件。
+//!
+//! 桌面端在后台定时执行清理，也可通过命令手动执行（支持仅预览、不删除）。
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::Result;
+use chrono::{Duration, Local, NaiveDateTime};
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder,
+    QuerySelect, TransactionTrait,
+};
+use tracing::info;
+
+use crate::entity;
+use crate::model::{OperateSource, TrashPurgeItem, TrashPurgeResult};
+use crate::service::{attachment, note, settings};
+
+/// 从设置中读取回收站保留天数，缺失或非法（负数）时为 0（永久保留）
+pub fn retention_days_from_settings(settings: &HashMap<String, String>) -> i64 {
+    settings
+        .get("trashRetentionDays")
+        .and_then(|v| v.trim().parse::<i64>().ok())
+        .filter(|v| *v >= 0)
+        .unwrap_or(0)
+}
+
+/// 保留期的截止时间：删除时间不晚于该时间的笔记已超过保留期；保留天数为 0 时为 None
+fn expiry_cutoff(retention_days: i64, now: NaiveDateTime) -> Option<NaiveDateTime> {
+    (retention_days > 0).then(|| now - Duration::days(retention_days))
+}
+
+/// 在回收站中超过保留期的笔记（按删除时间升序）
+async fn find_expired<C: ConnectionTrait>(
+    db: &C,
+    cutoff: NaiveDateTime,
+) -> Result<Vec<TrashPurgeItem>> {
+    Ok(entity::note::Entity::find()
+        .select_only()
+        .column(entity::note::Column::Id)
+        .column(entity::note::Column::Title)
+        .column(entity::note::Column::DeletedAt)
+        .filter(entity::note::Column::DeletedAt.is_not_null())
+        .filter(entity::note::Column::DeletedAt.lte(cutoff))
+        .order_by_asc(entity::note::Column::DeletedAt)
+        .into_tuple::<(i64, String, Option<NaiveDateTime>)>()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|(note_id, title, deleted_at)| TrashPurgeItem {
+            note_id,
+            title,
+            deleted_at,
+        })
+        .collect())
+}
+
+/// 彻底删除在回收站中超过保留期的笔记
+///
+/// `dry_run` 为 true 时只返回将被删除的笔记；保留天数为 0 时不做任何处理。
+/// 与手动永久删除一致，每条笔记写入一条删除历史；附件记录在同一事务中删除，
+/// 不再被引用的物理文件在事务提交后删除
+pub async fn purge(
+    db: &DatabaseConnection,
+    app_data_dir: &Path,
+    retention_days: i64,
+    dry_run: bool,
+    encryption_key: Option<&str>,
+) -> Result<TrashPurgeResult> {
+    let mut result = TrashPurgeResult {
+        dry_run,
+        retention_days,
+        ..Default::default()
+    };
+    let Some(cutoff) = expiry_cutoff(retention_days, Local::now().naive_local()) else {
+        return Ok(result);
+    };
+    if dry_run {
+        result.notes = find_expired(db, cutoff).await?;
+        return Ok(result);
+    }
+
+    // 在事务内重新按截止时间查询，期间被恢复的笔记不会被删除
+    let txn = db.begin().await?;
+    result.notes = find_expired(&txn, cutoff).await?;
+    if result.notes.is_empty() {
+        return Ok(result);
+    }
+    let ids: Vec<i64> = result.notes.iter().map(|n| n.note_id).collect();
+    let orphaned_files = attachment::delete_records_by_note_ids(&txn, &ids).await?;
+    for id in &ids {
+        note::permanent_delete_one(&txn, *id, OperateSource::User, encryption_key).await?;
+    }
+    txn.commit().await?;
+
+    attachment::remove_files(app_data_dir, &orphaned_files);
+
+    result.purged = ids.len() as u64;
+    info!(
+        "Trash purged: retention_days={}, purged={}",
+        retention_days, result.purged
+    );
+    Ok(result)
+}
+
+/// 按设置中保存的保留天数清理回收站
+pub async fn purge_with_settings(
+    db: &DatabaseConnection,
+    app_data_dir: &Path,
+    dry_run: bool,
+    encryption_key: Option<&str>,
+) -> Result<TrashPurgeResult> {
+    let retention_days = retention_days_from_settings(&settings::get_all(db).await?);
+    purge(db, app_data_dir, retention_days, dry_run, encryption_key).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dt(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn expires_after_retention_days() {
+        let now = dt("2026-06-30 12:00:00");
+        assert_eq!(expiry_cutoff(30, now), Some(dt("2026-05-31 12:00:00")));
+        // 0 表示永久保留
+        assert_eq!(expiry_cutoff(0, now), None);
+    }
+
+    #[test]
+    fn retention_days_from_settings_defaults_to_forever() {
+        let read = |v: &str| {
+            retention_days_from_settings(&HashMap::from([(
+                "trashRetentionDays".to_string(),
+                v.to_string(),
+            )]))
+        };
+        assert_eq!(read("30"), 30);
+        assert_eq!(read("-1"), 0);
+        assert_eq!(read("abc"), 0);
+        assert_eq!(retention_days_from_settings(&HashMap::new()), 0);
+    }
+}
@@ -3,28 +3,42 @@ import { invokeWithRetry } from '../utils/invokeWithRetry'
 
 import {
   AttachmentStats,
+  BatchNoteResult,
   CloudBackupEntry,
   CloudStorageConfig,
   ContentType,
+  CursorPageResult,
+  HistoryCompactResult,
+  LinkIntegrityReport,
   Note,
   NoteAttachment,
+  NoteCursorSearchParam,
   Notebook,
+  NotebookTreeEntry,
   NoteHistory,
   NoteHistorySearchPageParam,
   NoteSearchPageParam,
   NoteStatsResult,
+  NoteGraph,
+  NoteGraphNode,
+  NoteGraphQuery,
   NoteLink,
+  NoteRule,
+  NoteRuleRunResult,
   NoteTemplate,
   PageResult,
   ProfileConfig,
   ProfileIndex,
   ProfileSummary,
+  SavedSearch,
   ServerConfig,
   SyncLog,
   SyncLogDetail,
   SyncOptions,
   SyncPreview,
   Tag,
+  TagTreeEntry,
+  TrashPurgeResult,
 } from '../types'
 
 // ============================================================================
@@ -142,6 +156,10 @@ export const trashApi = {
   async emptyTrash(): Promise<void> {
     return await invoke('empty_trash')
   },
+
+  async purgeTrash(dryRun: boolean = false): Promise<TrashPurgeResult> {
+    return await invoke('purge_trash', { dryRun })
+  },
 }
 
 export const imageApi = {
@@ -174,6 +192,10 @@ export const backupApi = {
   async listAutoBackups(): Promise<[string, number][]> {
     return await invoke('list_auto_backups')
   },
+
+  async compactNoteHistory(): Promise<HistoryCompactResult> {
+    return await invoke('compact_note_history')
+  },
 }
 
 export const cloudBackupApi = {
@@ -221,6 +243,44 @@ export const templateApi = {
   },
 }
 
+export const noteRuleApi = {
+  async findAll(): Promise<NoteRule[]> {
+    return await invokeWithRetry('find_all_note_rules')
+  },
+  async create(noteRule: NoteRule): Promise<NoteRule> {
+    return await invoke('create_note_rule', { noteRule })
+  },
+  async update(noteRule: NoteRule): Promise<NoteRule> {
+    return await invoke('update_note_rule', { noteRule })
+  },
+  async delete(id: number): Promise<void> {
+    return await invoke('delete_note_rule_by_id', { id })
+  },
+  /** 对已有笔记执行规则；ruleIds 为空时执行所有启用的规则，dryRun 为 true 时只预览 */
+  async run(ruleIds: number[], dryRun: boolean): Promise<NoteRuleRunResult> {
+    return await invoke('run_note_rules', { ruleIds, dryRun })
+  },
+}
+
+export const savedSearchApi = {
+  async findAll(): Promise<SavedSearch[]> {
+    return await invokeWithRetry('find_all_saved_searches')
+  },
+  async create(savedSearch: SavedSearch): Promise<SavedSearch> {
+    return await invoke('create_saved_search', { savedSearch })
+  },
+  async update(savedSearch: SavedSearch): Promise<SavedSearch> {
+    return await invoke('update_saved_search', { savedSearch })
+  },
+  async delete(id: number): Promise<void> {
+    return await invoke('delete_saved_search_by_id', { id })
+  },
+  /** 执行保存的搜索（分页） */
+  async run(id: number, pageIndex: number, pageSize: number): Promise<PageResult<Note>> {
+    return await invoke('run_saved_search', { id, pageParam: { pageIndex, pageSize } })
+  },
+}
+
 export const cryptoApi = {
   async encrypt(content: string, password: string): Promise<string> {
     return await invoke('encrypt_content', { content, password })
@@ -252,8 +312,16 @@ export const noteLinkApi = {
     return await invoke('find_note_links', { noteId })
   },
 
-  async createLink(sourceNoteId: number, targetNoteId: number): Promise<void> {
-    return await invoke('create_note_link', { sourceNoteId, targetNoteId })
+  async findOutgoing(noteId: number): Promise<NoteLink[]> {
+    return await invoke('find_note_outgoing_links', { noteId })
+  },
+
+  async findBacklinks(noteId: number): Promise<NoteLink[]> {
+    return await invoke('find_note_backlinks', { noteId })
+  },
+
+  async createLink(sourceNoteId: number, targetNoteId: number, relation?: string): Promise<void> {
+    return await invoke('create_note_link', { sourceNoteId, targetNoteId, relation })
   },
 
   async deleteLink(linkId: number): Promise<void> {
@@ -263,6 +331,33 @@ export const noteLinkApi = {
   async searchLinkable(noteId: number, keyword: string): Promise<NoteLink[]> {
     return await invoke('search_linkable_notes', { noteId, keyword })
   },
+
+  /** 检查链接完整性；repair 为 true 时删除悬空链接并重新同步内容链接 */
+  async checkIntegrity(repair: boolean): Promise<LinkIntegrityReport> {
+    return await invoke('check_note_link_integrity', { repair })
+  },
+}
+
+export const noteGraphApi = {
+  async getGraph(query: NoteGraphQuery = {}): Promise<NoteGraph> {
+    return await invoke('get_note_graph', { query })
+  },
+
+  async getNeighbourhood(noteId: number, hops: number): Promise<NoteGraph> {
+    return await invoke('get_note_neighbourhood', { noteId, hops })
+  },
+
+  async findOrphans(query: NoteGraphQuery = {}): Promise<NoteGraphNode[]> {
+    return await invoke('find_orphan_notes', { query })
+  },
+
+  async exportGraph(
+    format: 'graphml' | 'dot' | 'json',
+    path: string,
+    query: NoteGraphQuery = {},
+  ): Promise<void> {
+    return await invoke('export_note_graph', { query, format, path })
+  },
 }
 
 export const attachmentApi = {
@@ -301,6 +396,10 @@ export const noteApi = {
     return await invokeWithRetry('find_all_notebooks')
   },
 
+  async getNotebookTree(): Promise<NotebookTreeEntry[]> {
+    return await invoke('find_notebook_tree')
+  },
+
   async createNotebook(notebook: Notebook): Promise<Notebook> {
     return await invoke('create_notebook', { notebook })
   },
@@ -313,6 +412,11 @@ export const noteApi = {
     return await invoke('delete_notebook_by_id', { id })
   },
 
+  /** 移动笔记本（连同子笔记本），parentId 为 0 表示移动到顶级 */
+  async moveNotebook(id: number, parentId: number): Promise<Notebook | null> {
+    return await invoke('move_notebook', { id, parentId })
+  },
+
   async createNote(
     notebookId: number,
     title: string,
@@ -347,6 +451,7 @@ export const noteApi = {
     contentType: ContentType,
     tags: Tag[],
     mcpAccess?: number,
+    version?: number,
   ): Promise<Note> {
     return await invoke('update_note', {
       note: {
@@ -362,6 +467,7 @@ export const noteApi = {
         createTime: null,
         updateTime: null,
         deletedAt: null,
+        version: version ?? 0,
       },
     })
   },
@@ -390,6 +496,10 @@ export const noteApi = {
     return await invokeWithRetry('search_page_notes', { searchParam })
   },
 
+  async searchCursorNotes(cursorParam: NoteCursorSearchParam): Promise<CursorPageResult<Note>> {
+    return await invokeWithRetry('search_cursor_notes', { cursorParam })
+  },
+
   async noteStats(searchParam: NoteSearchPageParam): Promise<NoteStatsResult> {
     return await invokeWithRetry('note_stats', { searchParam })
   },
@@ -410,6 +520,29 @@ export const noteApi = {
     return await invoke('delete_tag_by_id', { id })
   },
 
+  async getTagTree(): Promise<TagTreeEntry[]> {
+    return await invoke('find_tag_tree')
+  },
+
+  async moveTag(id: number, parentId: number): Promise<Tag | null> {
+    return await invoke('move_tag', { id, parentId })
+  },
+
+  /** 合并标签，返回标签发生变化的笔记数量 */
+  async mergeTags(sourceIds: number[], targetId: number): Promise<number> {
+    return await invoke('merge_tags', { sourceIds, targetId })
+  },
+
+  /** 批量为笔记添加标签，返回新增标签的笔记数量 */
+  async addTagToNotes(noteIds: number[], tagId: number): Promise<number> {
+    return await invoke('add_tag_to_notes', { noteIds, tagId })
+  },
+
+  /** 批量从笔记移除标签，返回移除了该标签的笔记数量 */
+  async removeTagFromNotes(noteIds: number[], tagId: number): Promise<number> {
+    return await invoke('remove_tag_from_notes', { noteIds, tagId })
+  },
+
   async batchMoveNotes(noteIds: number[], notebookId: number): Promise<void> {
     return await invoke('batch_move_notes', { noteIds, notebookId })
   },
@@ -418,11 +551,36 @@ export const noteApi = {
     return await invoke('batch_delete_notes', { noteIds })
   },
 
+  /** 批量设置收藏状态，返回每条笔记的处理结果 */
+  async batchSetNoteStar(noteIds: number[], starred: boolean): Promise<BatchNoteResult[]> {
+    return await invoke('batch_set_note_star', { noteIds, starred })
+  },
+
+  /** 批量设置置顶状态，返回每条笔记的处理结果 */
+  async batchSetNotePin(noteIds: number[], pinned: boolean): Promise<BatchNoteResult[]> {
+    return await invoke('batch_set_note_pin', { noteIds, pinned })
+  },
+
+  /** 批量从回收站恢复笔记，返回每条笔记的处理结果 */
+  async batchRestoreNotes(noteIds: number[]): Promise<BatchNoteResult[]> {
+    return await invoke('batch_restore_notes', { noteIds })
+  },
+
+  /** 批量永久删除回收站中的笔记，返回每条笔记的处理结果 */
+  async batchPermanentDeleteNotes(noteIds: number[]): Promise<BatchNoteResult[]> {
+    return await invoke('batch_permanent_delete_notes', { noteIds })
+  },
+
   async searchPageNoteHistories(
     searchParam: NoteHistorySearchPageParam,
   ): Promise<PageResult<NoteHistory>> {
     return await invokeWithRetry('search_page_note_histories', { searchParam })
   },
+
+  /** version 为读取到的笔记版本，笔记已被修改时返回版本冲突 */
+  async restoreNoteRevision(noteId: number, historyId: number, version?: number): Promise<Note> {
+    return await invoke('restore_note_revision', { noteId, historyId, version })
+  },
 }
 
 // ============================================================================
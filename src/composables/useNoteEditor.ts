@@ -68,6 +68,7 @@ export function useNoteEditor() {
             activeNoteData.value?.contentType ?? ContentType.Html,
             tagList,
             activeNoteData.value?.mcpAccess,
+            activeNoteData.value?.version,
           )
         } else {
           const newNote = await noteApi.createNote(
@@ -1,5 +1,5 @@
 import { useAppStore } from '../stores/app'
-import { noteApi } from '../api/note'
+import { noteApi, savedSearchApi } from '../api/note'
 import { parseId } from '../utils/validation'
 import { withNotification } from '../utils/errorHandler'
 import i18n from '../i18n'
@@ -7,20 +7,37 @@ import { debounce } from '../utils/debounce'
 import { noteToShowNote } from '../utils/converters'
 import { LRUCache } from '../utils/lruCache'
 import { SEARCH_DEBOUNCE_DELAY, SEARCH_CACHE_TTL, SEARCH_CACHE_MAX_SIZE } from '../config/constants'
-import type { ShowNote, Note, PageResult, NoteSearchPageParam } from '../types'
-
-// 搜索结果缓存项
+import type {
+  ShowNote,
+  Note,
+  PageResult,
+  CursorPageResult,
+  NoteSearchPageParam,
+} from '../types'
+
+// 搜索结果缓存项（只缓存第一页）
 interface SearchCacheEntry {
   result: ShowNote[]
   total: number
+  nextCursor: string | null
+}
+
+// 一页查询结果
+interface NotePage {
+  notes: ShowNote[]
+  total: number
+  nextCursor: string | null
 }
 
 // LRU 缓存：缓存搜索结果，自动淘汰最久未使用的项
 const searchCache = new LRUCache<string, SearchCacheEntry>(SEARCH_CACHE_MAX_SIZE, SEARCH_CACHE_TTL)
 
 // 生成缓存 key
-const getSearchCacheKey = (param: NoteSearchPageParam): string => {
-  return `${param.pageIndex}-${param.pageSize}-${param.notebookId}-${param.tagId}-${param.keyword}-${param.sortField}-${param.sortOrder}`
+const getSearchCacheKey = (param: NoteSearchPageParam, savedSearchId: string): string => {
+  if (savedSearchId) {
+    return `saved-${savedSearchId}-${param.pageSize}`
+  }
+  return `${param.pageSize}-${param.notebookId}-${param.tagId}-${param.keyword}-${param.sortField}-${param.sortOrder}`
 }
 
 export function useNoteSearch() {
@@ -29,15 +46,48 @@ export function useNoteSearch() {
   // 请求计数器：用于丢弃过期的搜索响应
   let searchRequestId = 0
 
-  // 搜索笔记（带 LRU 缓存）
+  // 查询一页笔记，cursor 为空表示第一页
+  // 选中保存的搜索时按其条件分页查询（游标即下一页页码），否则按当前条件游标分页查询
+  const fetchNotePage = async (cursor: string | null): Promise<NotePage> => {
+    const param = store.noteSearchPageParam
+
+    if (store.activeSavedSearch) {
+      const pageIndex = cursor ? Number(cursor) : 1
+      const pageResult: PageResult<Note> = await savedSearchApi.run(
+        parseId(store.activeSavedSearch),
+        pageIndex,
+        param.pageSize,
+      )
+      return {
+        notes: pageResult.data.map(noteToShowNote),
+        total: pageResult.total,
+        nextCursor: pageIndex < pageResult.totalPages ? String(pageIndex + 1) : null,
+      }
+    }
+
+    // 只在第一页统计总数，之后沿用
+    const cursorResult: CursorPageResult<Note> = await noteApi.searchCursorNotes({
+      ...param,
+      cursor: cursor ?? '',
+      withTotal: cursor === null,
+    })
+    return {
+      notes: cursorResult.data.map(noteToShowNote),
+      total: cursorResult.total ?? store.noteTotal,
+      nextCursor: cursorResult.nextCursor ?? null,
+    }
+  }
+
+  // 搜索笔记第一页（带 LRU 缓存）
   const searchNotes = async (skipCache = false): Promise<ShowNote[]> => {
     // 检查缓存
-    const cacheKey = getSearchCacheKey(store.noteSearchPageParam)
+    const cacheKey = getSearchCacheKey(store.noteSearchPageParam, store.activeSavedSearch)
 
     if (!skipCache) {
       const cached = searchCache.get(cacheKey)
       if (cached) {
         store.noteTotal = cached.total
+        store.noteNextCursor = cached.nextCursor
         return cached.result
       }
     }
@@ -48,26 +98,24 @@ export function useNoteSearch() {
     try {
       const result = await withNotification(
         async () => {
-          const pageResult: PageResult<Note> = await noteApi.searchPageNotes(
-            store.noteSearchPageParam,
-          )
+          const page = await fetchNotePage(null)
 
           // 丢弃过期的搜索响应（已有更新的请求发出）
           if (currentRequestId !== searchRequestId) {
             return null
           }
 
-          store.noteTotal = pageResult.total
-
-          const notes = pageResult.data.map(noteToShowNote)
+          store.noteTotal = page.total
+          store.noteNextCursor = page.nextCursor
 
           // 更新 LRU 缓存
           searchCache.set(cacheKey, {
-            result: notes,
-            total: pageResult.total,
+            result: page.notes,
+            total: page.total,
+            nextCursor: page.nextCursor,
           })
 
-          return notes
+          return page.notes
         },
         {
           loading: i18n.global.t('composable.loadingNotes'),
@@ -83,6 +131,30 @@ export function useNoteSearch() {
     }
   }
 
+  // 加载下一页并追加到列表（列表滚动到底部时调用）
+  const loadMoreNotes = async () => {
+    const cursor = store.noteNextCursor
+    if (!cursor || store.notesLoading || store.notesLoadingMore) return
+
+    const currentRequestId = searchRequestId
+    store.notesLoadingMore = true
+
+    try {
+      const page = await withNotification(() => fetchNotePage(cursor), {
+        error: i18n.global.t('composable.loadNotesFailed'),
+      })
+
+      // 加载期间已重新搜索时丢弃
+      if (!page || currentRequestId !== searchRequestId) return
+
+      store.noteTotal = page.total
+      store.noteNextCursor = page.nextCursor
+      store.appendNotes(page.notes)
+    } finally {
+      store.notesLoadingMore = false
+    }
+  }
+
   // 清除搜索缓存（在数据变更后调用）
   const clearSearchCache = () => {
     searchCache.clear()
@@ -121,6 +193,8 @@ export function useNoteSearch() {
 
   // 执行搜索的核心函数
   const executeSearch = async (updateStats: () => Promise<void>) => {
+    // 输入关键词即退出保存的搜索，回到普通搜索
+    store.activeSavedSearch = ''
     store.noteSearchPageParam.keyword = store.query
     const notes = await searchNotes()
     store.setNotes(notes)
@@ -142,12 +216,6 @@ export function useNoteSearch() {
     store.setNotes(notes)
   }
 
-  // 页码变化
-  const handleNoteCurrentChange = async (pageIndex: number) => {
-    store.notePageIndex = pageIndex
-    const notes = await searchNotes()
-    store.setNotes(notes)
-  }
 
   return {
     searchNotes,
@@ -156,6 +224,6 @@ export function useNoteSearch() {
     stats,
     handleUpdateSearchQuery,
     handleNoteSizeChange,
-    handleNoteCurrentChange,
+    loadMoreNotes,
   }
 }
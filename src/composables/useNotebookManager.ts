@@ -85,6 +85,7 @@ export function useNotebookManager() {
   // 设置活动笔记本
   const setActiveNotebook = async (notebookId: string, searchNotes: () => Promise<void>) => {
     store.activeNotebook = notebookId
+    store.activeSavedSearch = ''
     store.activeNote = null
     store.notePageIndex = 1
     store.noteSearchPageParam.notebookId = parseId(notebookId)
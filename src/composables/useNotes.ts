@@ -5,6 +5,7 @@ import { useTagManager } from './useTagManager'
 import { useNoteSearch } from './useNoteSearch'
 import { useNoteEditor } from './useNoteEditor'
 import { useNoteHistory } from './useNoteHistory'
+import { useSavedSearches } from './useSavedSearches'
 import { showNotification } from '../components/ui/notification'
 import { showError } from '../utils/errorHandler'
 import i18n from '../i18n'
@@ -22,6 +23,7 @@ export function useNotes() {
     histories,
     activeNotebook,
     activeTag,
+    activeSavedSearch,
     activeNote,
     editMode,
     loading,
@@ -39,6 +41,7 @@ export function useNotes() {
   const noteSearch = useNoteSearch()
   const noteEditor = useNoteEditor()
   const noteHistory = useNoteHistory()
+  const savedSearch = useSavedSearches()
 
   // 刷新所有数据
   const refreshAllData = async () => {
@@ -46,6 +49,7 @@ export function useNotes() {
     await Promise.all([
       notebookManager.getNotebooks(),
       tagManager.getTags(),
+      savedSearch.getSavedSearches(),
       noteSearch.refreshNotes(),
     ])
   }
@@ -86,16 +90,28 @@ export function useNotes() {
     await tagManager.setActiveTag(tagId, noteSearch.refreshNotes)
   }
 
+  // 包装的设置活动保存的搜索方法
+  const setActiveSavedSearch = async (id: string) => {
+    await savedSearch.setActiveSavedSearch(id, noteSearch.refreshNotes)
+  }
+
+  // 包装的删除保存的搜索方法
+  const deleteSavedSearch = async (id: string) => {
+    await savedSearch.deleteSavedSearch(id, noteSearch.refreshNotes)
+  }
+
   // 包装的保存笔记方法
   const saveNote = async () => {
     await noteEditor.saveNote(noteSearch.refreshNotes)
     noteHistory.clearHistoryCache()
+    await savedSearch.getSavedSearches()
   }
 
   // 包装的删除笔记方法
   const deleteNote = async () => {
     await noteEditor.deleteNote(noteSearch.refreshNotes)
     noteHistory.clearHistoryCache()
+    await savedSearch.getSavedSearches()
   }
 
   // 包装的搜索查询更新方法
@@ -115,7 +131,11 @@ export function useNotes() {
 
     try {
       // 第一阶段：并行加载笔记本和标签
-      await Promise.all([notebookManager.getNotebooks(), tagManager.getTags()])
+      await Promise.all([
+        notebookManager.getNotebooks(),
+        tagManager.getTags(),
+        savedSearch.getSavedSearches(),
+      ])
 
       // 第二阶段：设置默认选中（需要等第一阶段完成）
       store.activeNotebook = store.notebooks[0]?.id ?? '0'
@@ -144,6 +164,7 @@ export function useNotes() {
     // UI 状态 refs（直接导出，不再使用 Proxy 兼容层）
     activeNotebook,
     activeTag,
+    activeSavedSearch,
     activeNote,
     editMode,
     loading,
@@ -169,6 +190,13 @@ export function useNotes() {
     setActiveTag,
     getTagById: tagManager.getTagById,
 
+    // 保存的搜索操作
+    savedSearches: savedSearch.savedSearches,
+    savedSearchCounts: savedSearch.savedSearchCounts,
+    saveCurrentSearch: savedSearch.saveCurrentSearch,
+    deleteSavedSearch,
+    setActiveSavedSearch,
+
     // 笔记操作
     setActiveNote: noteEditor.setActiveNote,
     createNewNote: noteEditor.createNewNote,
@@ -183,7 +211,7 @@ export function useNotes() {
     // 搜索操作
     handleUpdateSearchQuery,
     handleNoteSizeChange: noteSearch.handleNoteSizeChange,
-    handleNoteCurrentChange: noteSearch.handleNoteCurrentChange,
+    loadMoreNotes: noteSearch.loadMoreNotes,
 
     // 历史记录操作
     openHistoryDialog: noteHistory.openHistoryDialog,
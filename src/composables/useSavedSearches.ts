@@ -0,0 +1,125 @@
+import { ref } from 'vue'
+import { useAppStore } from '../stores/app'
+import { noteApi, savedSearchApi } from '../api/note'
+import { parseId } from '../utils/validation'
+import { withNotification } from '../utils/errorHandler'
+import i18n from '../i18n'
+import type { SavedSearch } from '../types'
+
+// 保存的搜索列表与命中数量（侧边栏共享）
+const savedSearches = ref<SavedSearch[]>([])
+const savedSearchCounts = ref<Record<string, number>>({})
+
+export function useSavedSearches() {
+  const store = useAppStore()
+
+  // 获取保存的搜索及其命中数量
+  const getSavedSearches = async () => {
+    const result = await withNotification(
+      async () => {
+        // 命中数量随笔记统计一起返回
+        const [list, stats] = await Promise.all([
+          savedSearchApi.findAll(),
+          noteApi.noteStats(store.noteSearchPageParam),
+        ])
+        return { list, counts: stats.savedSearchCounts ?? {} }
+      },
+      // 保存/删除笔记后也会刷新命中数量，不显示加载提示
+      { error: i18n.global.t('composable.loadSavedSearchesFailed') },
+    )
+
+    if (result) {
+      savedSearches.value = result.list
+      savedSearchCounts.value = result.counts
+      // 选中的保存的搜索已被删除时退出
+      if (
+        store.activeSavedSearch &&
+        !result.list.some((s) => String(s.id) === store.activeSavedSearch)
+      ) {
+        store.activeSavedSearch = ''
+      }
+    }
+  }
+
+  // 将当前笔记本/标签/关键词/排序条件保存为新的保存的搜索
+  const saveCurrentSearch = async (name: string) => {
+    if (!name.trim()) return
+
+    const param = store.noteSearchPageParam
+    await withNotification(
+      async () => {
+        await savedSearchApi.create({
+          id: 0,
+          name: name.trim(),
+          icon: '',
+          sortOrder: 0,
+          filter: {
+            keyword: store.query.trim(),
+            notebookIds: param.notebookId > 0 ? [param.notebookId] : [],
+            includeDescendants: param.includeDescendants ?? false,
+            allTagIds: param.tagId > 0 ? [param.tagId] : [],
+            anyTagIds: [],
+            excludeTagIds: [],
+            includeTagDescendants: param.includeTagDescendants ?? false,
+            isStarred: param.isStarred ?? false,
+            updatedAfter: null,
+            updatedBefore: null,
+            sortField: param.sortField,
+            sortOrder: param.sortOrder,
+          },
+          createTime: null,
+          updateTime: null,
+        })
+        await getSavedSearches()
+      },
+      {
+        loading: i18n.global.t('composable.savingSavedSearch'),
+        success: i18n.global.t('composable.savedSearchSaved'),
+        error: i18n.global.t('composable.saveSavedSearchFailed'),
+      },
+    )
+  }
+
+  // 删除保存的搜索
+  const deleteSavedSearch = async (id: string, searchNotes: () => Promise<void>) => {
+    const savedSearchId = parseId(id)
+    if (savedSearchId === 0) return
+
+    await withNotification(
+      async () => {
+        await savedSearchApi.delete(savedSearchId)
+        const wasActive = store.activeSavedSearch === id
+        await getSavedSearches()
+        if (wasActive) {
+          await searchNotes()
+        }
+      },
+      {
+        loading: i18n.global.t('composable.deletingSavedSearch'),
+        success: i18n.global.t('composable.savedSearchDeleted'),
+        error: i18n.global.t('composable.deleteSavedSearchFailed'),
+      },
+    )
+  }
+
+  // 设置活动的保存的搜索：取消笔记本/标签筛选，按保存的条件查询
+  const setActiveSavedSearch = async (id: string, searchNotes: () => Promise<void>) => {
+    store.activeSavedSearch = id
+    store.activeNotebook = ''
+    store.activeTag = ''
+    store.activeNote = null
+    store.notePageIndex = 1
+    store.noteSearchPageParam.notebookId = 0
+    store.noteSearchPageParam.tagId = 0
+    await searchNotes()
+  }
+
+  return {
+    savedSearches,
+    savedSearchCounts,
+    getSavedSearches,
+    saveCurrentSearch,
+    deleteSavedSearch,
+    setActiveSavedSearch,
+  }
+}
@@ -83,6 +83,7 @@ export function useTagManager() {
   // 设置活动标签
   const setActiveTag = async (tagId: string, searchNotes: () => Promise<void>) => {
     store.activeTag = tagId
+    store.activeSavedSearch = ''
     store.activeNote = null
     store.notePageIndex = 1
     store.noteSearchPageParam.tagId = parseId(tagId)
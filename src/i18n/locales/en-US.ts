@@ -112,6 +112,17 @@ export default {
       message: 'Are you sure you want to delete this tag? This action cannot be undone.',
       confirmText: 'Delete',
     },
+    savedSearches: 'Saved Searches',
+    saveCurrentSearch: 'Save Current Search',
+    noSavedSearches: 'No saved searches',
+    savedSearchNameLabel: 'Name',
+    savedSearchNamePlaceholder: 'Enter a name',
+    deleteSavedSearchConfirm: {
+      title: 'Delete Saved Search',
+      message:
+        'Are you sure you want to delete this saved search? Its notes will not be deleted.',
+      confirmText: 'Delete',
+    },
     recentEdits: 'Recent Edits',
   },
 
@@ -149,6 +160,8 @@ export default {
     batchDelete: 'Batch Delete',
     batchDeleteConfirm: 'Delete {count} selected notes?',
     cancelSelect: 'Cancel',
+    loadingMore: 'Loading more...',
+    loadedCount: 'Loaded {count} of {total}',
   },
 
   // Editor
@@ -522,9 +535,22 @@ export default {
     UNSUPPORTED_EXPORT_FORMAT: 'Unsupported export format',
     UNSUPPORTED_IMPORT_FORMAT: 'Unsupported import format',
     SYNC_LOG_NOT_FOUND: 'Sync log not found',
+    SAVED_SEARCH_NOT_FOUND: 'Saved search not found',
+    REVISION_NOT_FOUND: 'Revision not found',
+    NOTE_VERSION_CONFLICT:
+      'This note was changed elsewhere (current version {0}). Reopen it, merge your edits and save again.',
     JSON_SERIALIZE_FAILED: 'Data serialization failed: {0}',
     FILE_WRITE_FAILED: 'Failed to write file: {0}',
     CANNOT_LINK_SELF: 'Cannot link a note to itself',
+    NOTEBOOK_INVALID_ID: 'Notebook ID must be greater than 0',
+    NOTEBOOK_PARENT_NOT_FOUND: 'Parent notebook not found',
+    NOTEBOOK_MOVE_CYCLE: 'Cannot move a notebook into itself or one of its sub-notebooks',
+    TAG_PARENT_NOT_FOUND: 'Parent tag not found',
+    TAG_MOVE_CYCLE: 'Cannot move a tag into itself or one of its sub-tags',
+    TAG_PATH_INVALID: 'Tag path contains no valid name',
+    TAG_NOT_FOUND: 'Tag not found',
+    TAG_MERGE_NOTES_CHANGED:
+      'Notes using the source tags changed during the merge (note ID {0}). Please try again.',
     PROFILE_NOT_FOUND: 'Profile not found: {0}',
     NO_VALID_SQL_STATEMENTS: 'No valid INSERT statements found in SQL file',
     SQL_EXEC_FAILED: 'Failed to execute SQL: {0}',
@@ -573,6 +599,13 @@ export default {
     deletingTag: 'Deleting tag',
     tagDeleted: 'Tag deleted',
     deleteTagFailed: 'Failed to delete tag',
+    loadSavedSearchesFailed: 'Failed to load saved searches',
+    savingSavedSearch: 'Saving search',
+    savedSearchSaved: 'Search saved',
+    saveSavedSearchFailed: 'Failed to save search',
+    deletingSavedSearch: 'Deleting saved search',
+    savedSearchDeleted: 'Saved search deleted',
+    deleteSavedSearchFailed: 'Failed to delete saved search',
     loadingNotes: 'Loading notes',
     loadNotesFailed: 'Failed to load notes',
     countingNotes: 'Counting notes',
@@ -645,6 +678,7 @@ export default {
     operateCreate: 'Create',
     operateUpdate: 'Update',
     operateDelete: 'Delete',
+    operateRestore: 'Restore',
     operateSource: 'Source',
     sourceUser: 'User',
     sourceMcp: 'AI Tool',
@@ -767,6 +801,26 @@ export default {
     autoBackupFailed: 'Auto backup failed',
     lastBackup: 'Last backup',
     never: 'Never',
+    historyRetention: 'History Retention',
+    historyKeepAll: 'Keep every revision for',
+    historyKeepDaily: 'Keep one revision per day for',
+    historyKeepWeekly: 'Keep one revision per week for',
+    historyDays: '{n} days',
+    historyForever: 'Forever',
+    historyCompactAfterBackup: 'Compact history after auto backup',
+    historyCompactHint: 'Remove revisions outside the retention rules',
+    historyCompactNow: 'Compact Now',
+    historyCompactSuccess: 'Removed {n} revisions, freed {size}',
+    historyCompactFailed: 'Failed to compact history',
+    // Trash Retention
+    trashRetention: 'Trash Retention',
+    trashRetentionDays: 'Keep deleted notes for',
+    trashPurgeHint: 'Permanently delete notes kept in the trash longer than this',
+    trashPurgePreview: 'Preview',
+    trashPurgePreviewResult: '{n} notes would be permanently deleted',
+    trashPurgeNow: 'Purge Now',
+    trashPurgeSuccess: 'Permanently deleted {n} notes',
+    trashPurgeFailed: 'Failed to purge trash',
     // Cloud Backup
     cloudBackup: 'Cloud Backup',
     cloudBackupEnabled: 'Enable cloud backup',
@@ -854,7 +908,16 @@ export default {
     mcpToolCreateNotebook: 'Create Notebook',
     mcpToolListTags: 'List Tags',
     mcpToolCreateTag: 'Create Tag',
+    mcpToolMergeTags: 'Merge Tags',
+    mcpToolAddTagToNotes: 'Add Tag to Notes',
+    mcpToolRemoveTagFromNotes: 'Remove Tag from Notes',
+    mcpToolSetNotesStarred: 'Star Notes',
+    mcpToolSetNotesPinned: 'Pin Notes',
+    mcpToolRestoreNotes: 'Restore Notes',
+    mcpToolPermanentDeleteNotes: 'Permanently Delete Notes',
     mcpToolNoteStats: 'Note Statistics',
+    mcpToolOutgoingLinks: 'Get Outgoing Links',
+    mcpToolBacklinks: 'Get Backlinks',
     // System Maintenance
     maintenance: 'System Maintenance',
     syncDesc: 'Sync data from current profile to another profile',
@@ -991,6 +1054,9 @@ export default {
     noLinks: 'No linked notes',
     noResults: 'No matching notes found',
     removeLink: 'Remove Link',
+    contentLink: 'Referenced in content, edit the note to remove',
+    outgoing: 'This note links to it',
+    backlink: 'Links to this note',
   },
 
   // Profile / Setup
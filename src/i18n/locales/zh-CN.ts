@@ -109,6 +109,16 @@ export default {
       message: '确定要删除这个标签吗？此操作不可恢复。',
       confirmText: '删除',
     },
+    savedSearches: '保存的搜索',
+    saveCurrentSearch: '保存当前搜索',
+    noSavedSearches: '暂无保存的搜索',
+    savedSearchNameLabel: '名称',
+    savedSearchNamePlaceholder: '请输入名称',
+    deleteSavedSearchConfirm: {
+      title: '删除保存的搜索',
+      message: '确定要删除这个保存的搜索吗？不会删除其中的笔记。',
+      confirmText: '删除',
+    },
     recentEdits: '最近编辑',
   },
 
@@ -146,6 +156,8 @@ export default {
     batchDelete: '批量删除',
     batchDeleteConfirm: '确定要删除选中的 {count} 条笔记吗？',
     cancelSelect: '取消',
+    loadingMore: '正在加载更多...',
+    loadedCount: '已加载 {count} / {total}',
   },
 
   // 编辑器
@@ -518,9 +530,21 @@ export default {
     UNSUPPORTED_EXPORT_FORMAT: '不支持的导出格式',
     UNSUPPORTED_IMPORT_FORMAT: '不支持的导入格式',
     SYNC_LOG_NOT_FOUND: '同步日志不存在',
+    SAVED_SEARCH_NOT_FOUND: '保存的搜索不存在',
+    REVISION_NOT_FOUND: '历史版本不存在',
+    NOTE_VERSION_CONFLICT:
+      '笔记已在其他位置被修改（当前版本 {0}），请重新打开笔记合并修改后再保存',
     JSON_SERIALIZE_FAILED: '数据序列化失败：{0}',
     FILE_WRITE_FAILED: '写入文件失败：{0}',
     CANNOT_LINK_SELF: '不能链接到自身',
+    NOTEBOOK_INVALID_ID: '笔记本 ID 必须大于 0',
+    NOTEBOOK_PARENT_NOT_FOUND: '父笔记本不存在',
+    NOTEBOOK_MOVE_CYCLE: '不能将笔记本移动到自身或其子笔记本下',
+    TAG_PARENT_NOT_FOUND: '父标签不存在',
+    TAG_MOVE_CYCLE: '不能将标签移动到自身或其子标签下',
+    TAG_PATH_INVALID: '标签路径中没有有效的名称',
+    TAG_NOT_FOUND: '标签不存在',
+    TAG_MERGE_NOTES_CHANGED: '合并期间使用源标签的笔记发生了变化（笔记 ID {0}），请重试',
     PROFILE_NOT_FOUND: 'Profile 不存在：{0}',
     NO_VALID_SQL_STATEMENTS: 'SQL 文件中没有找到有效的 INSERT 语句',
     SQL_EXEC_FAILED: '执行 SQL 失败：{0}',
@@ -569,6 +593,13 @@ export default {
     deletingTag: '正在删除标签',
     tagDeleted: '标签已删除',
     deleteTagFailed: '删除标签失败',
+    loadSavedSearchesFailed: '加载保存的搜索失败',
+    savingSavedSearch: '正在保存搜索',
+    savedSearchSaved: '搜索已保存',
+    saveSavedSearchFailed: '保存搜索失败',
+    deletingSavedSearch: '正在删除保存的搜索',
+    savedSearchDeleted: '保存的搜索已删除',
+    deleteSavedSearchFailed: '删除保存的搜索失败',
     loadingNotes: '正在加载笔记',
     loadNotesFailed: '加载笔记失败',
     countingNotes: '正在统计笔记',
@@ -641,6 +672,7 @@ export default {
     operateCreate: '添加',
     operateUpdate: '修改',
     operateDelete: '删除',
+    operateRestore: '恢复',
     operateSource: '操作来源',
     sourceUser: '用户操作',
     sourceMcp: 'AI 工具',
@@ -763,6 +795,26 @@ export default {
     autoBackupFailed: '自动备份失败',
     lastBackup: '上次备份',
     never: '从未备份',
+    historyRetention: '历史记录保留',
+    historyKeepAll: '全部保留',
+    historyKeepDaily: '每天保留一条',
+    historyKeepWeekly: '每周保留一条',
+    historyDays: '{n} 天',
+    historyForever: '永久',
+    historyCompactAfterBackup: '自动备份后精简历史记录',
+    historyCompactHint: '删除超出保留规则的历史版本',
+    historyCompactNow: '立即精简',
+    historyCompactSuccess: '已删除 {n} 条历史记录，释放 {size}',
+    historyCompactFailed: '精简历史记录失败',
+    // 回收站保留
+    trashRetention: '回收站保留',
+    trashRetentionDays: '已删除笔记保留',
+    trashPurgeHint: '彻底删除在回收站中超过保留期的笔记',
+    trashPurgePreview: '预览',
+    trashPurgePreviewResult: '将彻底删除 {n} 篇笔记',
+    trashPurgeNow: '立即清理',
+    trashPurgeSuccess: '已彻底删除 {n} 篇笔记',
+    trashPurgeFailed: '清理回收站失败',
     // 云备份
     cloudBackup: '云备份',
     cloudBackupEnabled: '启用云备份',
@@ -850,7 +902,16 @@ export default {
     mcpToolCreateNotebook: '创建笔记本',
     mcpToolListTags: '列出标签',
     mcpToolCreateTag: '创建标签',
+    mcpToolMergeTags: '合并标签',
+    mcpToolAddTagToNotes: '批量添加标签',
+    mcpToolRemoveTagFromNotes: '批量移除标签',
+    mcpToolSetNotesStarred: '批量收藏',
+    mcpToolSetNotesPinned: '批量置顶',
+    mcpToolRestoreNotes: '批量恢复笔记',
+    mcpToolPermanentDeleteNotes: '批量永久删除笔记',
     mcpToolNoteStats: '笔记统计',
+    mcpToolOutgoingLinks: '获取笔记出链',
+    mcpToolBacklinks: '获取反向链接',
     // 系统维护
     maintenance: '系统维护',
     syncDesc: '将当前 Profile 的数据同步到其他 Profile',
@@ -984,6 +1045,9 @@ export default {
     noLinks: '暂无关联笔记',
     noResults: '未找到匹配的笔记',
     removeLink: '移除链接',
+    contentLink: '来自笔记内容中的引用，修改内容即可移除',
+    outgoing: '当前笔记链接到该笔记',
+    backlink: '该笔记链接到当前笔记',
   },
 
   // Profile / Setup
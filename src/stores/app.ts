@@ -30,10 +30,12 @@ export const useAppStore = defineStore('app', () => {
     notePageIndex,
     notePageSize,
     noteTotal,
+    noteNextCursor,
     noteSortField,
     noteSortOrder,
     noteSearchPageParam,
     notesLoading,
+    notesLoadingMore,
     isSelectMode,
     selectedNotes,
   } = storeToRefs(noteStore)
@@ -49,7 +51,7 @@ export const useAppStore = defineStore('app', () => {
     historyTotal,
     historyLoading,
   } = storeToRefs(editorStore)
-  const { activeNotebook, activeTag, loading } = storeToRefs(uiStore)
+  const { activeNotebook, activeTag, activeSavedSearch, loading } = storeToRefs(uiStore)
 
   // 语言切换时更新默认项
   const updateDefaultItems = () => {
@@ -84,12 +86,15 @@ export const useAppStore = defineStore('app', () => {
     notePageIndex,
     notePageSize,
     noteTotal,
+    noteNextCursor,
     noteSortField,
     noteSortOrder,
     noteSearchPageParam,
     notesLoading,
+    notesLoadingMore,
     // 笔记列表（方法）
     setNotes: noteStore.setNotes,
+    appendNotes: noteStore.appendNotes,
     prependNote: noteStore.prependNote,
     updateNote: noteStore.updateNote,
     removeNote: noteStore.removeNote,
@@ -127,6 +132,7 @@ export const useAppStore = defineStore('app', () => {
     // ==================== UI（响应式属性） ====================
     activeNotebook,
     activeTag,
+    activeSavedSearch,
     loading,
 
     // ==================== 国际化 ====================
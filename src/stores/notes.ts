@@ -31,6 +31,8 @@ export const useNoteStore = defineStore('notes', () => {
     },
   })
   const noteTotal = ref<number>(0)
+  // 下一页游标（列表滚动加载），为空表示没有更多
+  const noteNextCursor = ref<string | null>(null)
   const noteSortField = computed({
     get: () => noteSearchPageParam.value.sortField,
     set: (v: string) => {
@@ -64,6 +66,15 @@ export const useNoteStore = defineStore('notes', () => {
     }
   }
 
+  // 追加下一页（已存在的笔记不重复添加）
+  const appendNotes = (items: ShowNote[]) => {
+    for (const item of items) {
+      if (!notesMap.value.has(item.id)) {
+        notesMap.value.set(item.id, item)
+      }
+    }
+  }
+
   const prependNote = (note: ShowNote) => {
     const newMap = new Map<string, ShowNote>()
     newMap.set(note.id, note)
@@ -134,10 +145,13 @@ export const useNoteStore = defineStore('notes', () => {
     notePageIndex,
     notePageSize,
     noteTotal,
+    noteNextCursor,
     noteSortField,
     noteSortOrder,
     noteSearchPageParam,
     notesLoading: ref<boolean>(false),
+    // 正在加载下一页
+    notesLoadingMore: ref<boolean>(false),
 
     // 多选
     isSelectMode,
@@ -149,6 +163,7 @@ export const useNoteStore = defineStore('notes', () => {
 
     // CRUD
     setNotes,
+    appendNotes,
     prependNote,
     updateNote,
     removeNote,
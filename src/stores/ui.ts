@@ -4,11 +4,14 @@ import { ref } from 'vue'
 export const useUiStore = defineStore('ui', () => {
   const activeNotebook = ref<string>('')
   const activeTag = ref<string>('')
+  // 当前选中的保存的搜索，为空表示按笔记本/标签筛选
+  const activeSavedSearch = ref<string>('')
   const loading = ref<boolean>(false)
 
   return {
     activeNotebook,
     activeTag,
+    activeSavedSearch,
     loading,
   }
 })
@@ -4,6 +4,14 @@ export interface PageResult<T> {
   data: T[]
 }
 
+export interface CursorPageResult<T> {
+  data: T[]
+  /** 下一页游标，没有更多数据时为空 */
+  nextCursor?: string | null
+  /** 总数（仅 withTotal 为 true 时返回） */
+  total?: number | null
+}
+
 export interface Notebook {
   id: number
   parentId?: number
@@ -19,6 +27,11 @@ export interface Notebook {
   updateTime?: string | null
 }
 
+/** 后端返回的笔记本树节点：笔记本字段加上子笔记本 */
+export interface NotebookTreeEntry extends Notebook {
+  children: NotebookTreeEntry[]
+}
+
 export interface ShowNotebook {
   id: string
   parentId?: number
@@ -40,6 +53,9 @@ export interface NotebookTreeNode extends ShowNotebook {
 
 export interface Tag {
   id: number
+  /** 父标签 ID，0 表示顶级 */
+  parentId?: number
+  /** 标签名称，创建时可传入 a/b/c 形式的路径 */
   name: string
   icon?: string
   cls?: string
@@ -50,8 +66,14 @@ export interface Tag {
   updateTime?: string | null
 }
 
+/** 后端返回的标签树节点：标签字段加上子标签 */
+export interface TagTreeEntry extends Tag {
+  children: TagTreeEntry[]
+}
+
 export interface ShowTag {
   id: string
+  parentId?: number
   name: string
   icon?: string
   cls?: string
@@ -110,6 +132,8 @@ export interface Note {
   updateTime: string | null
   /** 软删除时间 */
   deletedAt: string | null
+  /** 版本号（每次编辑后加 1，更新时用于冲突检测） */
+  version?: number
 }
 
 export interface ShowNote {
@@ -130,6 +154,8 @@ export interface ShowNote {
   createTime: string | null
   updateTime: string | null
   deletedAt?: string | null
+  /** 版本号 */
+  version?: number
 }
 
 /** 新建笔记时的部分类型（某些字段可以为空） */
@@ -149,9 +175,19 @@ export interface PartialShowNote {
   deletedAt?: string | null
 }
 
+/** 批量操作中单条笔记的处理结果 */
+export type BatchNoteStatus = 'changed' | 'unchanged' | 'notFound' | 'skipped' | 'denied'
+
+export interface BatchNoteResult {
+  noteId: number
+  status: BatchNoteStatus
+}
+
 export interface NoteStatsResult {
   total: number
   notebookCounts: Map<number, number>
+  // 各保存的搜索当前命中的笔记数量，键为保存的搜索 ID
+  savedSearchCounts: Record<string, number>
 }
 
 export interface NoteHistoryExtra {
@@ -176,6 +212,30 @@ export interface NoteHistory {
   createTime: string
 }
 
+export interface HistoryCompactResult {
+  scanned: number
+  deleted: number
+  /** 释放的内容字节数（估算） */
+  freedBytes: number
+}
+
+export interface TrashPurgeItem {
+  noteId: number
+  title: string
+  /** 移入回收站的时间 */
+  deletedAt: string
+}
+
+export interface TrashPurgeResult {
+  /** 是否为预览（不删除任何数据） */
+  dryRun: boolean
+  /** 回收站保留天数（0 表示永久保留） */
+  retentionDays: number
+  notes: TrashPurgeItem[]
+  /** 实际删除的笔记数 */
+  purged: number
+}
+
 export interface NoteSearchPageParam {
   pageIndex: number
   pageSize: number
@@ -185,6 +245,17 @@ export interface NoteSearchPageParam {
   sortField: string
   sortOrder: string
   isStarred?: boolean
+  /** 笔记本筛选是否包含子孙笔记本 */
+  includeDescendants?: boolean
+  /** 标签筛选是否包含子孙标签 */
+  includeTagDescendants?: boolean
+}
+
+export interface NoteCursorSearchParam extends NoteSearchPageParam {
+  /** 上一页返回的 nextCursor，为空表示第一页 */
+  cursor?: string
+  /** 是否统计总数 */
+  withTotal?: boolean
 }
 
 export interface NoteHistorySearchPageParam {
@@ -197,9 +268,69 @@ export interface NoteLink {
   id: number
   noteId: number
   noteTitle: string
+  /** 链接来源：0 = 手动创建，1 = 从笔记内容解析 */
+  origin?: number
+  /** 关系类型（如 references、depends-on），空字符串表示未指定 */
+  relation?: string
+  /** true = 当前笔记指向该笔记（出链），false = 该笔记指向当前笔记（反向链接） */
+  outgoing?: boolean
   createTime: string | null
 }
 
+/** 悬空链接：source 或 target 笔记已不存在 */
+export interface DanglingLink {
+  id: number
+  sourceNoteId: number
+  targetNoteId: number
+  origin: number
+  relation: string
+}
+
+/** 笔记内容中无法解析的引用（按 ID 引用时 targetTitle 为空，按标题引用时 targetId 为 0） */
+export interface BrokenReference {
+  noteId: number
+  noteTitle: string
+  targetId: number
+  targetTitle: string
+}
+
+export interface LinkIntegrityReport {
+  repaired: boolean
+  danglingLinks: DanglingLink[]
+  staleContentLinks: number
+  brokenReferences: BrokenReference[]
+  skippedNotes: number
+}
+
+/** 笔记关系图查询条件（省略或为 0 表示不限） */
+export interface NoteGraphQuery {
+  notebookId?: number
+  tagId?: number
+}
+
+export interface NoteGraphNode {
+  id: number
+  title: string
+  notebookId: number
+  notebookName: string
+  tags: string[]
+}
+
+/** 关系图的边：source 笔记指向 target 笔记 */
+export interface NoteGraphEdge {
+  id: number
+  source: number
+  target: number
+  relation: string
+  /** 链接来源：0 = 手动创建，1 = 从笔记内容解析 */
+  origin: number
+}
+
+export interface NoteGraph {
+  nodes: NoteGraphNode[]
+  edges: NoteGraphEdge[]
+}
+
 export interface NoteTemplate {
   id: number
   name: string
@@ -211,6 +342,114 @@ export interface NoteTemplate {
   updateTime: string | null
 }
 
+// ============================================================================
+// 笔记自动规则相关类型
+// ============================================================================
+
+/**
+ * 规则条件
+ * - title / content：contains、not_contains、starts_with、ends_with、equals（不区分大小写）
+ * - source：equals、not_equals，值为 user、mcp、sync
+ * - notebook：equals、not_equals、within（含子笔记本），值为笔记本 ID
+ * - tag：has、not_has、has_within（含子标签），值为标签 ID
+ */
+export interface NoteRuleCondition {
+  field: 'title' | 'content' | 'source' | 'notebook' | 'tag'
+  operator: string
+  value: string
+}
+
+/**
+ * 规则动作
+ * - move：值为笔记本 ID；add_tag / remove_tag：值为标签 ID
+ * - star / unstar / pin / unpin：忽略值
+ * - set_mcp_access：值为 0-3
+ */
+export interface NoteRuleAction {
+  action:
+    | 'move'
+    | 'add_tag'
+    | 'remove_tag'
+    | 'star'
+    | 'unstar'
+    | 'pin'
+    | 'unpin'
+    | 'set_mcp_access'
+  value: string
+}
+
+export interface NoteRule {
+  id: number
+  name: string
+  enabled: boolean
+  runOnCreate: boolean
+  runOnUpdate: boolean
+  /** false = 全部条件满足，true = 任一条件满足 */
+  matchAny: boolean
+  conditions: NoteRuleCondition[]
+  actions: NoteRuleAction[]
+  sortOrder: number
+  createTime: string | null
+  updateTime: string | null
+}
+
+/** 规则对一篇笔记产生的变更（仅包含与当前状态不同的部分） */
+export interface NoteRuleEffect {
+  notebookId: number | null
+  addTagIds: number[]
+  removeTagIds: number[]
+  isStarred: boolean | null
+  isPinned: boolean | null
+  mcpAccess: number | null
+}
+
+export interface NoteRuleMatch {
+  noteId: number
+  title: string
+  ruleIds: number[]
+  effect: NoteRuleEffect
+}
+
+export interface NoteRuleRunResult {
+  dryRun: boolean
+  checked: number
+  matches: NoteRuleMatch[]
+}
+
+// ============================================================================
+// 保存的搜索相关类型
+// ============================================================================
+
+/** 保存的搜索条件，日期为 YYYY-MM-DD */
+export interface SavedSearchFilter {
+  keyword: string
+  /** 笔记本 ID 列表（命中任一即可） */
+  notebookIds: number[]
+  includeDescendants: boolean
+  /** 必须同时包含的标签 */
+  allTagIds: number[]
+  /** 至少包含其一的标签 */
+  anyTagIds: number[]
+  /** 不能包含的标签 */
+  excludeTagIds: number[]
+  includeTagDescendants: boolean
+  isStarred: boolean
+  updatedAfter: string | null
+  updatedBefore: string | null
+  sortField: string
+  sortOrder: string
+}
+
+export interface SavedSearch {
+  id: number
+  name: string
+  icon: string
+  sortOrder: number
+  filter: SavedSearchFilter
+  createTime: string | null
+  updateTime: string | null
+}
+
 // ============================================================================
 // 笔记附件相关类型
 // ============================================================================
@@ -104,6 +104,7 @@ export const noteToShowNote = (note: Note): ShowNote => ({
   createTime: note.createTime,
   updateTime: note.updateTime,
   deletedAt: note.deletedAt,
+  version: note.version,
 })
 
 /**